@@ -0,0 +1,407 @@
+//! Library API
+//!
+//! Runs the same fetch, parse and build pipeline as the `analyze` and `watch`
+//! commands, but hands the results back instead of printing them or launching the TUI.
+
+use crate::data_source::{DataSource, QueryParams, Transaction};
+use crate::parser::datum::validate_datum_hash;
+use crate::parser::schema::SchemaParser;
+use crate::parser::{GenericParser, ParsedDatum, Parser};
+use crate::state_machine::StateGraph;
+use crate::{Error, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Instant;
+use tracing::Instrument;
+
+/// How long each phase of the pipeline took and how much it processed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct PipelineMetrics {
+    /// Fetching transactions and the script's unspent UTXOs
+    pub fetch_ms: u64,
+    /// Resolving and parsing datums and redeemers
+    pub parse_ms: u64,
+    /// Building and classifying the state graph
+    pub build_ms: u64,
+    pub tx_count: usize,
+    /// Outputs carrying a datum
+    pub datum_count: usize,
+}
+
+/// Fetch the transactions at `address` and build its state graph
+///
+/// Datums and redeemers are parsed with `schema` when given, otherwise with the
+/// generic parser. The graph records the script's unspent UTXOs when the source
+/// can provide them.
+pub async fn analyze(
+    address: &str,
+    source: Box<dyn DataSource>,
+    schema: Option<SchemaParser>,
+    params: QueryParams,
+) -> Result<(StateGraph, Vec<Transaction>)> {
+    analyze_data_source(source.as_ref(), address, schema.as_ref(), params).await
+}
+
+/// Like [`analyze`], but borrows the data source and schema so callers can keep
+/// using them, e.g. to refresh the graph later
+pub async fn analyze_data_source(
+    data_source: &dyn DataSource,
+    address: &str,
+    schema: Option<&SchemaParser>,
+    params: QueryParams,
+) -> Result<(StateGraph, Vec<Transaction>)> {
+    let (graph, transactions, _) =
+        analyze_data_source_with_metrics(data_source, address, schema, params).await?;
+    Ok((graph, transactions))
+}
+
+/// Like [`analyze_data_source`], but also reports how long each phase took
+///
+/// Each phase runs in its own tracing span, and the metrics are logged at info level
+/// once the graph is built.
+pub async fn analyze_data_source_with_metrics(
+    data_source: &dyn DataSource,
+    address: &str,
+    schema: Option<&SchemaParser>,
+    params: QueryParams,
+) -> Result<(StateGraph, Vec<Transaction>, PipelineMetrics)> {
+    let elapsed_ms = |started: Instant| started.elapsed().as_millis() as u64;
+
+    let started = Instant::now();
+    let mut transactions = data_source
+        .get_transactions_by_address(address, params)
+        .instrument(tracing::info_span!("fetch", %address))
+        .await?;
+    let mut fetch_ms = elapsed_ms(started);
+
+    let started = Instant::now();
+    tracing::info_span!("parse").in_scope(|| process_transactions(&mut transactions, schema));
+    let parse_ms = elapsed_ms(started);
+
+    let started = Instant::now();
+    let mut graph = tracing::info_span!("build")
+        .in_scope(|| crate::state_machine::build_state_graph(&transactions, address, schema))?;
+    let build_ms = elapsed_ms(started);
+
+    let started = Instant::now();
+    attach_unspent_utxos(&mut graph, data_source, address)
+        .instrument(tracing::info_span!("fetch_utxos", %address))
+        .await;
+    fetch_ms += elapsed_ms(started);
+
+    let metrics = PipelineMetrics {
+        fetch_ms,
+        parse_ms,
+        build_ms,
+        tx_count: transactions.len(),
+        datum_count: transactions
+            .iter()
+            .flat_map(|tx| &tx.outputs)
+            .filter(|output| output.datum.is_some())
+            .count(),
+    };
+    tracing::info!(
+        fetch_ms = metrics.fetch_ms,
+        parse_ms = metrics.parse_ms,
+        build_ms = metrics.build_ms,
+        tx_count = metrics.tx_count,
+        datum_count = metrics.datum_count,
+        "Analyzed {}",
+        address
+    );
+    Ok((graph, transactions, metrics))
+}
+
+/// Process transactions: hydrate datums from witnesses and parse datums/redeemers
+pub(crate) fn process_transactions(
+    transactions: &mut [Transaction],
+    schema_parser: Option<&SchemaParser>,
+) {
+    let parser: &dyn Parser = match schema_parser {
+        Some(p) => p,
+        None => &GenericParser,
+    };
+    parse_transactions(transactions, parser);
+}
+
+fn parse_transactions(transactions: &mut [Transaction], parser: &dyn Parser) {
+    // The same datum often sits in many UTXOs (e.g. a long vesting chain), so each
+    // hash is decoded once
+    let mut parsed_datums: HashMap<String, ParsedDatum> = HashMap::new();
+
+    for tx in transactions {
+        // Clone witnesses to avoid borrow checker issues when iterating outputs
+        let witnesses = tx.witnesses.clone();
+
+        for output in &mut tx.outputs {
+            if let Some(datum) = &mut output.datum {
+                // Hydrate if missing CBOR (lookup hash in witnesses)
+                if datum.raw_cbor.is_empty() {
+                    // Find datum in witnesses
+                    if let Some(resolved) = witnesses.datums.iter().find(|d| d.hash == datum.hash)
+                        && !resolved.raw_cbor.is_empty()
+                    {
+                        datum.raw_cbor = resolved.raw_cbor.clone();
+                        datum.reencoded = resolved.reencoded;
+                    }
+                }
+
+                if datum.raw_cbor.is_empty() {
+                    continue;
+                }
+                if let Some(parsed) = parsed_datums.get(&datum.hash) {
+                    datum.parsed = Some(parsed.clone());
+                    continue;
+                }
+                match parser.parse_datum(&datum.raw_cbor) {
+                    Ok(parsed) => {
+                        parsed_datums.insert(datum.hash.clone(), parsed.clone());
+                        datum.parsed = Some(parsed);
+                    }
+                    Err(e) => tracing::debug!("Failed to parse datum: {}", e),
+                }
+            }
+        }
+
+        // Also parse redeemers if possible
+        for redeemer in &mut tx.witnesses.redeemers {
+            if !redeemer.raw_cbor.is_empty() {
+                match parser.parse_redeemer(&redeemer.raw_cbor) {
+                    Ok(parsed) => redeemer.parsed = Some(parsed),
+                    Err(e) => tracing::debug!("Failed to parse redeemer: {}", e),
+                }
+            }
+        }
+    }
+}
+
+/// Check that every output datum was resolved, matches its hash and parsed
+///
+/// Returns an error listing each offending output, for callers that would rather
+/// fail than show datums as "not parsed". Re-encoded datums skip the hash check:
+/// their CBOR need not match the on-chain bytes (e.g. definite vs indefinite
+/// length arrays), so a mismatch says nothing about the datum itself.
+pub fn check_datums(transactions: &[Transaction]) -> Result<()> {
+    let problems: Vec<String> = transactions
+        .iter()
+        .flat_map(|tx| {
+            tx.outputs.iter().enumerate().filter_map(|(idx, output)| {
+                let datum = output.datum.as_ref()?;
+                let checked = if datum.reencoded {
+                    Ok(!datum.raw_cbor.is_empty())
+                } else {
+                    validate_datum_hash(datum)
+                };
+                let problem = match checked {
+                    Ok(false) => format!("datum {} could not be resolved", datum.hash),
+                    Err(e) => e.to_string(),
+                    Ok(true) if datum.parsed.is_none() => {
+                        format!("datum {} could not be parsed", datum.hash)
+                    }
+                    Ok(true) => return None,
+                };
+                Some(format!("{}#{}: {}", tx.hash, idx, problem))
+            })
+        })
+        .collect();
+
+    if problems.is_empty() {
+        return Ok(());
+    }
+    Err(Error::parser(format!(
+        "{} datum(s) failed strict checks:\n  {}",
+        problems.len(),
+        problems.join("\n  ")
+    )))
+}
+
+/// Record the script's current UTXO set on the graph so live terminal states can be
+/// told apart from completed ones
+pub(crate) async fn attach_unspent_utxos(
+    graph: &mut StateGraph,
+    data_source: &dyn DataSource,
+    address: &str,
+) {
+    match data_source
+        .get_script_utxos(address, QueryParams::default())
+        .await
+    {
+        Ok(utxos) => graph.set_unspent_utxos(utxos.into_iter().map(|(utxo_ref, _)| utxo_ref)),
+        Err(e) => tracing::warn!("Failed to fetch script UTXOs, live states unknown: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_source::mock::{MOCK_SCRIPT_ADDRESS, MockDataSource};
+    use crate::data_source::{Asset, Datum, TxOutput, Witnesses};
+    use crate::parser::ParsedRedeemer;
+    use std::cell::Cell;
+
+    /// Generic parser that counts how many datums it decodes
+    #[derive(Default)]
+    struct CountingParser {
+        datum_calls: Cell<usize>,
+    }
+
+    impl Parser for CountingParser {
+        fn parse_datum(&self, raw: &[u8]) -> Result<ParsedDatum> {
+            self.datum_calls.set(self.datum_calls.get() + 1);
+            GenericParser.parse_datum(raw)
+        }
+
+        fn parse_redeemer(&self, raw: &[u8]) -> Result<ParsedRedeemer> {
+            GenericParser.parse_redeemer(raw)
+        }
+    }
+
+    fn transaction_with_datum(hash: &str, datum_hash: &str) -> Transaction {
+        Transaction {
+            hash: hash.to_string(),
+            block: 1,
+            slot: 1,
+            inputs: vec![],
+            outputs: vec![TxOutput {
+                address: MOCK_SCRIPT_ADDRESS.to_string(),
+                amount: vec![Asset::lovelace(2_000_000)],
+                datum: Some(Datum {
+                    hash: datum_hash.to_string(),
+                    raw_cbor: vec![0xd8, 0x79, 0x80],
+                    parsed: None,
+                    reencoded: false,
+                }),
+                script_ref: None,
+            }],
+            witnesses: Witnesses::default(),
+            metadata: None,
+            size: None,
+            valid: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_datums_accepts_mock_datums() {
+        let (_, transactions) = analyze(
+            MOCK_SCRIPT_ADDRESS,
+            Box::new(MockDataSource::new()),
+            None,
+            QueryParams::default(),
+        )
+        .await
+        .unwrap();
+        check_datums(&transactions).unwrap();
+    }
+
+    #[test]
+    fn test_check_datums_lists_unresolved_and_mismatched() {
+        let valid_hash = crate::data_source::blake2b_256(&[0xd8, 0x79, 0x80]);
+        let mut transactions = vec![
+            transaction_with_datum("tx1", &valid_hash),
+            transaction_with_datum("tx2", "stub"),
+            transaction_with_datum("tx3", "wrong"),
+        ];
+        transactions[1].outputs[0]
+            .datum
+            .as_mut()
+            .unwrap()
+            .raw_cbor
+            .clear();
+
+        // Lenient processing leaves the stub unparsed without failing
+        process_transactions(&mut transactions, None);
+        assert!(
+            transactions[1].outputs[0]
+                .datum
+                .as_ref()
+                .unwrap()
+                .parsed
+                .is_none()
+        );
+
+        let err = check_datums(&transactions).unwrap_err().to_string();
+        assert!(err.contains("2 datum(s)"), "{}", err);
+        assert!(!err.contains("tx1#0"), "{}", err);
+        assert!(
+            err.contains("tx2#0: datum stub could not be resolved"),
+            "{}",
+            err
+        );
+        assert!(
+            err.contains("tx3#0: Parser error: Datum hash mismatch"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_check_datums_skips_hash_of_reencoded_datum() {
+        // On chain the datum is the definite-length `d87980`; the provider's JSON
+        // is re-encoded with an indefinite-length array instead
+        let on_chain_hash = crate::data_source::blake2b_256(&[0xd8, 0x79, 0x80]);
+        let mut transactions = vec![transaction_with_datum("tx1", &on_chain_hash)];
+        let datum = transactions[0].outputs[0].datum.as_mut().unwrap();
+        datum.raw_cbor = vec![0xd8, 0x79, 0x9f, 0xff];
+        datum.reencoded = true;
+        process_transactions(&mut transactions, None);
+
+        check_datums(&transactions).unwrap();
+
+        transactions[0].outputs[0].datum.as_mut().unwrap().reencoded = false;
+        let err = check_datums(&transactions).unwrap_err().to_string();
+        assert!(err.contains("Datum hash mismatch"), "{}", err);
+    }
+
+    #[test]
+    fn test_shared_datum_is_parsed_once() {
+        let mut transactions = vec![
+            transaction_with_datum("tx1", "datum"),
+            transaction_with_datum("tx2", "datum"),
+        ];
+        let parser = CountingParser::default();
+
+        parse_transactions(&mut transactions, &parser);
+
+        assert_eq!(parser.datum_calls.get(), 1);
+        assert!(
+            transactions
+                .iter()
+                .all(|tx| tx.outputs[0].datum.as_ref().unwrap().parsed.is_some())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_analyze_mock_source() {
+        let (graph, transactions) = analyze(
+            MOCK_SCRIPT_ADDRESS,
+            Box::new(MockDataSource::new()),
+            None,
+            QueryParams::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(transactions.len(), 5);
+        assert_eq!(graph.graph.node_count(), 4);
+        assert_eq!(graph.graph.edge_count(), 3);
+        assert!(graph.unspent_utxos.is_some());
+
+        let (_, _, metrics) = analyze_data_source_with_metrics(
+            &MockDataSource::new(),
+            MOCK_SCRIPT_ADDRESS,
+            None,
+            QueryParams::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(metrics.tx_count, 5);
+        assert_eq!(metrics.datum_count, 4);
+        assert!(
+            transactions
+                .iter()
+                .flat_map(|tx| &tx.outputs)
+                .filter_map(|output| output.datum.as_ref())
+                .all(|datum| datum.parsed.is_some())
+        );
+    }
+}
@@ -2,309 +2,1233 @@
 //!
 //! This module contains the implementation for each CLI command.
 
-use crate::data_source::Transaction;
+use crate::api::{self, attach_unspent_utxos, process_transactions};
+use crate::cli::DataSourceType;
+use crate::config::SettingSource;
+use crate::data_source::create_data_source;
+use crate::data_source::{DataSource, Datum, QueryParams, Transaction};
 use crate::parser::schema::SchemaParser;
-use crate::{Config, Result, cli::Cli};
-use std::path::PathBuf;
-
-/// Process transactions: hydrate datums from witnesses and parse datums/redeemers
-fn process_transactions(transactions: &mut [Transaction], schema_parser: Option<&SchemaParser>) {
-    for tx in transactions {
-        // Clone witnesses to avoid borrow checker issues when iterating outputs
-        let witnesses = tx.witnesses.clone();
-
-        for output in &mut tx.outputs {
-            if let Some(datum) = &mut output.datum {
-                // Hydrate if missing CBOR (lookup hash in witnesses)
-                if datum.raw_cbor.is_empty() {
-                    // Find datum in witnesses
-                    if let Some(resolved) = witnesses.datums.iter().find(|d| d.hash == datum.hash)
-                        && !resolved.raw_cbor.is_empty()
-                    {
-                        datum.raw_cbor = resolved.raw_cbor.clone();
-                    }
-                }
+use crate::state_machine::StateGraph;
+use crate::tui::{DataUpdate, GraphUpdate};
+use crate::{Config, Network, Result, cli::Cli};
+use std::path::{Path, PathBuf};
 
-                // Parse using selected parser
-                if !datum.raw_cbor.is_empty() {
-                    let parser: &dyn crate::parser::Parser = if let Some(p) = schema_parser {
-                        p
-                    } else {
-                        &crate::parser::GenericParser
-                    };
-
-                    match parser.parse_datum(&datum.raw_cbor) {
-                        Ok(parsed) => datum.parsed = Some(parsed),
-                        Err(e) => tracing::debug!("Failed to parse datum: {}", e),
-                    }
-                }
-            }
-        }
+/// Data source and network of a command, resolved from its flags and the config file
+struct Settings {
+    config: Config,
+    source: (DataSourceType, SettingSource),
+    network: (Network, SettingSource),
+}
 
-        // Also parse redeemers if possible
-        for redeemer in &mut tx.witnesses.redeemers {
-            if !redeemer.raw_cbor.is_empty() {
-                let parser: &dyn crate::parser::Parser = if let Some(p) = schema_parser {
-                    p
-                } else {
-                    &crate::parser::GenericParser
-                };
+/// Resolve a command's data source and network, checking `address` against the
+/// network when there is one
+fn resolve_settings(
+    config: Config,
+    api_key: Option<String>,
+    source: Option<DataSourceType>,
+    command_default: DataSourceType,
+    network: Option<&str>,
+    address: Option<&str>,
+) -> Result<Settings> {
+    let config = config.with_cli_api_key(api_key);
+    let source = config.resolve_source(source, command_default)?;
+    let network = config.resolve_network(network, source.0)?;
+    if let Some(address) = address {
+        crate::network::validate_address(address, Some(network.0.name()))?;
+    }
+    tracing::debug!(
+        "Resolved configuration:\n{}",
+        config.report(source, network)
+    );
+    Ok(Settings {
+        config,
+        source,
+        network,
+    })
+}
 
-                match parser.parse_redeemer(&redeemer.raw_cbor) {
-                    Ok(parsed) => redeemer.parsed = Some(parsed),
-                    Err(e) => tracing::debug!("Failed to parse redeemer: {}", e),
-                }
-            }
-        }
+/// A command's resolved settings with its data source and schema parser
+struct Prepared {
+    settings: Settings,
+    data_source: Box<dyn DataSource>,
+    schema_parser: Option<SchemaParser>,
+}
+
+/// Resolve the settings of a command that fetches right away (no dry run), then
+/// create its data source with the config file's cache settings and its schema parser
+async fn prepare(
+    config: Config,
+    api_key: Option<String>,
+    source: Option<DataSourceType>,
+    network: Option<&str>,
+    address: Option<&str>,
+    schema: Option<&Path>,
+) -> Result<Prepared> {
+    let settings = resolve_settings(
+        config,
+        api_key,
+        source,
+        DataSourceType::Mock,
+        network,
+        address,
+    )?;
+    let config = &settings.config;
+    let data_source = create_data_source(
+        settings.source.0,
+        settings.network.0,
+        config,
+        config.cache.enabled,
+        config.cache.ttl,
+    )
+    .await?;
+    let schema_parser = config.resolve_schema(schema)?;
+    Ok(Prepared {
+        settings,
+        data_source,
+        schema_parser,
+    })
+}
+
+/// Every output datum as (tx hash, output index, datum), for list outputs
+fn collect_datums(transactions: &[Transaction]) -> Vec<(String, usize, Datum)> {
+    transactions
+        .iter()
+        .flat_map(|tx| {
+            tx.outputs.iter().enumerate().filter_map(|(idx, output)| {
+                let datum = output.datum.as_ref()?;
+                Some((tx.hash.clone(), idx, datum.clone()))
+            })
+        })
+        .collect()
+}
+
+/// Fetch transactions for an address and rebuild its state graph
+///
+/// Used to answer TUI refresh requests in analyze mode.
+async fn fetch_graph_update(
+    data_source: &dyn DataSource,
+    address: &str,
+    query_params: QueryParams,
+    schema_parser: Option<&SchemaParser>,
+) -> GraphUpdate {
+    let (graph, transactions) =
+        api::analyze_data_source(data_source, address, schema_parser, query_params).await?;
+    Ok(DataUpdate {
+        graph,
+        transactions,
+        incremental: false,
+    })
+}
+
+/// Fetch only the transactions after the newest one in `transactions` and merge them
+/// into `graph`
+///
+/// Used by watch-mode polls, so each poll costs as much as the new activity.
+async fn merge_new_transactions(
+    data_source: &dyn DataSource,
+    address: &str,
+    mut query_params: QueryParams,
+    schema_parser: Option<&SchemaParser>,
+    graph: &mut StateGraph,
+    transactions: &mut Vec<Transaction>,
+) -> Result<()> {
+    // Blocks are atomic, so nothing new can land in the newest block already seen.
+    // The block bound lets sources skip transactions before fetching them in full.
+    if let Some(tip) = transactions.iter().max_by_key(|tx| (tx.block, tx.slot)) {
+        query_params = query_params
+            .from_slot(tip.slot + 1)
+            .from_block(tip.block + 1);
     }
+
+    let mut new_txs = data_source
+        .get_transactions_by_address(address, query_params)
+        .await?;
+    new_txs.retain(|new| !transactions.iter().any(|tx| tx.hash == new.hash));
+    process_transactions(&mut new_txs, schema_parser);
+
+    graph.merge(&new_txs, schema_parser);
+    transactions.extend(new_txs);
+    attach_unspent_utxos(graph, data_source, address).await;
+    Ok(())
 }
 
 /// Analyze command implementation
 pub mod analyze {
     use super::*;
-    use crate::parser::schema::ContractSchema;
-    use crate::{
-        cli::{Commands, OutputFormat},
-        data_source::{QueryParams, create_data_source},
-    };
+    use crate::cli::{Commands, OutputFormat};
+    use tokio::sync::mpsc;
 
     /// Execute the analyze command
     pub async fn execute(args: Cli, config: Config) -> Result<()> {
         // Extract command-specific arguments
-        let (address, source, output_format, schema, cache, cache_ttl, max_transactions) =
-            match args.command {
-                Commands::Analyze {
-                    address,
-                    source,
-                    output,
-                    schema,
-                    no_cache,
-                    cache_ttl,
-                    max_transactions,
-                    ..
-                } => (
-                    address,
-                    source,
-                    output,
-                    schema,
-                    !no_cache,
-                    cache_ttl,
-                    max_transactions,
-                ),
-                _ => unreachable!("analyze::execute called with wrong command"),
-            };
+        let theme = args.theme();
+        let mut query_params = args.command.query_params();
+        let (
+            address,
+            address_file,
+            source,
+            network,
+            output_format,
+            schema,
+            cache,
+            cache_ttl,
+            since,
+            max_depth,
+            strict,
+            allow_empty,
+            dry_run,
+            api_key,
+        ) = match args.command {
+            Commands::Analyze {
+                address,
+                address_file,
+                source,
+                network,
+                api_key,
+                output,
+                schema,
+                no_cache,
+                cache_ttl,
+                since,
+                max_depth,
+                strict,
+                allow_empty,
+                dry_run,
+                ..
+            } => (
+                address,
+                address_file,
+                source,
+                network,
+                output,
+                schema,
+                !no_cache,
+                cache_ttl,
+                since,
+                max_depth,
+                strict,
+                allow_empty,
+                dry_run,
+                api_key,
+            ),
+            _ => unreachable!("analyze::execute called with wrong command"),
+        };
+
+        let Settings {
+            config,
+            source,
+            network,
+        } = resolve_settings(
+            config,
+            api_key,
+            source,
+            DataSourceType::Mock,
+            network.as_deref(),
+            address.as_deref(),
+        )?;
+        if address_file.is_some()
+            && !matches!(output_format, OutputFormat::Json | OutputFormat::Dot)
+        {
+            return Err(crate::Error::Config(
+                "--address-file only supports --output json or dot".to_string(),
+            ));
+        }
+        if dry_run {
+            print!("{}", config.report(source, network));
+            return Ok(());
+        }
+        let (source, network) = (source.0, network.0);
+        let started = std::time::Instant::now();
 
-        tracing::info!("Analyzing address: {}", address);
         tracing::debug!("Using data source: {:?}", source);
 
         // Create data source based on args.source
-        let data_source = create_data_source(source, &config, cache, cache_ttl).await?;
+        let data_source = create_data_source(source, network, &config, cache, cache_ttl).await?;
 
         // Fetch transactions for the given address
         tracing::info!("Fetching transactions...");
-        let mut query_params = QueryParams::default();
-        if let Some(limit) = max_transactions {
-            query_params = query_params.limit(limit);
+        if let Some(since) = since {
+            // An explicit --from-slot further in the past is narrowed to the window
+            let from_slot = network
+                .window_start_slot(since, chrono::Utc::now())
+                .max(query_params.from_slot.unwrap_or(0));
+            tracing::info!(
+                "Only including transactions from slot {} on {}",
+                from_slot,
+                network
+            );
+            query_params = query_params.from_slot(from_slot);
         }
 
-        let mut transactions = data_source
-            .get_transactions_by_address(&address, query_params)
-            .await?;
-
-        tracing::info!("Found {} transactions", transactions.len());
-
         // Prepare parser (Schema or Generic)
-        let schema_parser = if let Some(schema_path) = schema {
-            tracing::info!("Loading schema from {:?}", schema_path);
-            let contract_schema = ContractSchema::from_file(schema_path)?;
-            Some(SchemaParser::new(contract_schema))
-        } else {
-            None
+        let schema_parser = config.resolve_schema(schema.as_deref())?;
+        let options = AnalyzeOptions {
+            strict,
+            allow_empty,
+            max_depth,
         };
 
-        // Hydrate and parse datums in transactions
-        tracing::info!("Parsing datums...");
-        process_transactions(&mut transactions, schema_parser.as_ref());
-
-        // Collect all datums for list output
-        let mut all_datums = Vec::new();
-        for tx in &transactions {
-            for (idx, output) in tx.outputs.iter().enumerate() {
-                if let Some(datum) = &output.datum {
-                    all_datums.push((tx.hash.clone(), idx, datum.clone()));
+        let Some(address) = address else {
+            let path = address_file.ok_or_else(|| {
+                crate::Error::Config("--address or --address-file is required".to_string())
+            })?;
+            let addresses = read_address_file(&path)?;
+            let results = analyze_batch(
+                data_source.as_ref(),
+                &addresses,
+                network,
+                schema_parser.as_ref(),
+                query_params,
+                options,
+            )
+            .await;
+            return match output_format {
+                OutputFormat::Dot => {
+                    write_batch_dot(&mut std::io::stdout(), Path::new("."), &results)
                 }
-            }
-        }
+                _ => write_batch_json(&mut std::io::stdout(), &results),
+            };
+        };
 
-        tracing::info!("Extracted {} datums", all_datums.len());
+        tracing::info!("Analyzing address: {}", address);
+        let (graph, transactions, metrics) = analyze_address(
+            data_source.as_ref(),
+            &address,
+            network,
+            schema_parser.as_ref(),
+            query_params.clone(),
+            options,
+        )
+        .await?;
 
         // Output in requested format
         match output_format {
-            OutputFormat::Json => {
-                crate::cli::output::output_json(
-                    &mut std::io::stdout(),
-                    &transactions,
-                    &all_datums,
-                )?;
+            OutputFormat::None => {
+                eprintln!(
+                    "Analyzed {} transactions, {} datums, {} states, {} transitions in {:.2?} \
+                     (fetch {}ms, parse {}ms, build {}ms)",
+                    transactions.len(),
+                    collect_datums(&transactions).len(),
+                    graph.graph.node_count(),
+                    graph.graph.edge_count(),
+                    started.elapsed(),
+                    metrics.fetch_ms,
+                    metrics.parse_ms,
+                    metrics.build_ms
+                );
             }
-            OutputFormat::Table => {
-                crate::cli::output::output_table(
-                    &mut std::io::stdout(),
-                    &transactions,
-                    &all_datums,
-                )?;
-            }
-            OutputFormat::Dot => {
-                tracing::info!("Building state graph for DOT output...");
-                let graph = crate::state_machine::build_state_graph(
-                    &transactions,
-                    &address,
-                    schema_parser.as_ref(),
-                )?;
-                let dot = graph.to_dot();
-                println!("{}", dot);
+            OutputFormat::Json
+            | OutputFormat::Table
+            | OutputFormat::Csv
+            | OutputFormat::Dot
+            | OutputFormat::Mermaid => {
+                write_output(&mut std::io::stdout(), output_format, &graph, &transactions)?;
             }
             OutputFormat::Tui => {
                 tracing::info!("Launching TUI...");
-                let graph = crate::state_machine::build_state_graph(
-                    &transactions,
-                    &address,
-                    schema_parser.as_ref(),
+
+                // Serve refresh requests from the TUI
+                let (update_sender, update_receiver) = mpsc::channel(1);
+                let (refresh_sender, mut refresh_receiver) = mpsc::channel(1);
+                let refresh_parser = schema_parser
+                    .as_ref()
+                    .map(|p| SchemaParser::new(p.schema.clone()));
+                tokio::spawn(async move {
+                    while refresh_receiver.recv().await.is_some() {
+                        let update = fetch_graph_update(
+                            data_source.as_ref(),
+                            &address,
+                            query_params.clone(),
+                            refresh_parser.as_ref(),
+                        )
+                        .await
+                        .map(|mut update| {
+                            if let Some(depth) = max_depth {
+                                update.graph = update.graph.subgraph_within_depth(depth);
+                            }
+                            update
+                        });
+                        if update_sender.send(update).await.is_err() {
+                            break; // Receiver closed
+                        }
+                    }
+                });
+
+                crate::tui::run(
+                    graph,
+                    transactions,
+                    Some(update_receiver),
+                    Some(refresh_sender),
+                    None,
+                    theme,
                 )?;
-                crate::tui::run(graph, transactions, None)?;
             }
         }
 
         Ok(())
     }
+
+    /// Checks and pruning applied to each analyzed address
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct AnalyzeOptions {
+        /// Fail if any datum cannot be resolved, hash-validated or parsed
+        pub strict: bool,
+        /// Accept an address without transactions
+        pub allow_empty: bool,
+        /// Only keep states within this many transitions of an initial state
+        pub max_depth: Option<usize>,
+    }
+
+    /// Outcome of analyzing one address of a batch
+    pub struct AddressAnalysis {
+        pub address: String,
+        pub result: Result<(StateGraph, Vec<Transaction>)>,
+    }
+
+    /// Run the analysis pipeline for one address and apply the `options`
+    pub async fn analyze_address(
+        data_source: &dyn DataSource,
+        address: &str,
+        network: crate::Network,
+        schema_parser: Option<&SchemaParser>,
+        query_params: QueryParams,
+        options: AnalyzeOptions,
+    ) -> Result<(StateGraph, Vec<Transaction>, api::PipelineMetrics)> {
+        let (mut graph, transactions, metrics) = api::analyze_data_source_with_metrics(
+            data_source,
+            address,
+            schema_parser,
+            query_params,
+        )
+        .await?;
+        tracing::info!("Found {} transactions", transactions.len());
+        if transactions.is_empty() && !options.allow_empty {
+            return Err(crate::Error::NoTransactions {
+                address: address.to_string(),
+                network: network.to_string(),
+            });
+        }
+        if options.strict {
+            api::check_datums(&transactions)?;
+        }
+        if let Some(depth) = options.max_depth {
+            graph = graph.subgraph_within_depth(depth);
+            tracing::info!(
+                "Displaying {} states within {} transitions of an initial state",
+                graph.graph.node_count(),
+                depth
+            );
+        }
+        Ok((graph, transactions, metrics))
+    }
+
+    /// Read the addresses of a batch, one per line, skipping blank lines and `#` comments
+    pub fn read_address_file(path: &Path) -> Result<Vec<String>> {
+        let contents = std::fs::read_to_string(path)?;
+        let addresses: Vec<String> = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect();
+        if addresses.is_empty() {
+            return Err(crate::Error::Config(format!(
+                "No addresses in {}",
+                path.display()
+            )));
+        }
+        Ok(addresses)
+    }
+
+    /// Analyze each address in turn with one shared data source
+    ///
+    /// A failing address is recorded in its result and doesn't stop the others.
+    pub async fn analyze_batch(
+        data_source: &dyn DataSource,
+        addresses: &[String],
+        network: crate::Network,
+        schema_parser: Option<&SchemaParser>,
+        query_params: QueryParams,
+        options: AnalyzeOptions,
+    ) -> Vec<AddressAnalysis> {
+        let mut results = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            tracing::info!("Analyzing address: {}", address);
+            let result = match crate::network::validate_address(address, Some(network.name())) {
+                Ok(()) => analyze_address(
+                    data_source,
+                    address,
+                    network,
+                    schema_parser,
+                    query_params.clone(),
+                    options,
+                )
+                .await
+                .map(|(graph, transactions, _)| (graph, transactions)),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = &result {
+                tracing::warn!("Failed to analyze {}: {}", address, e);
+            }
+            results.push(AddressAnalysis {
+                address: address.clone(),
+                result,
+            });
+        }
+        results
+    }
+
+    /// Write a batch as one JSON object keyed by address
+    ///
+    /// Each entry has a `status` of `ok`, with the same fields as `--output json`, or
+    /// `error`, with the error message.
+    pub fn write_batch_json(
+        w: &mut impl std::io::Write,
+        results: &[AddressAnalysis],
+    ) -> Result<()> {
+        let mut output = serde_json::Map::new();
+        for analysis in results {
+            let entry = match &analysis.result {
+                Ok((graph, transactions)) => {
+                    let mut entry = crate::cli::output::json_value(
+                        transactions,
+                        &collect_datums(transactions),
+                        Some(graph),
+                    );
+                    entry["status"] = "ok".into();
+                    entry
+                }
+                Err(e) => serde_json::json!({ "status": "error", "error": e.to_string() }),
+            };
+            output.insert(analysis.address.clone(), entry);
+        }
+
+        serde_json::to_writer_pretty(&mut *w, &output)?;
+        writeln!(w)?;
+        Ok(())
+    }
+
+    /// Write one `<address>.dot` file per analyzed address into `dir`, reporting the
+    /// status of each address to `w`
+    pub fn write_batch_dot(
+        w: &mut impl std::io::Write,
+        dir: &Path,
+        results: &[AddressAnalysis],
+    ) -> Result<()> {
+        for analysis in results {
+            let written = analysis
+                .result
+                .as_ref()
+                .map_err(|e| e.to_string())
+                .and_then(|(graph, _)| {
+                    let path = dir.join(format!("{}.dot", analysis.address));
+                    std::fs::write(&path, format!("{}\n", graph.to_dot()))
+                        .map(|()| path)
+                        .map_err(|e| e.to_string())
+                });
+            match written {
+                Ok(path) => writeln!(w, "{}: wrote {}", analysis.address, path.display())?,
+                Err(e) => writeln!(w, "{}: error: {}", analysis.address, e)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the analysis result to `w` in a non-interactive `format`
+    ///
+    /// The TUI is run by the caller, and `None` writes nothing.
+    pub fn write_output(
+        w: &mut impl std::io::Write,
+        format: OutputFormat,
+        graph: &StateGraph,
+        transactions: &[Transaction],
+    ) -> Result<()> {
+        match format {
+            OutputFormat::Json => {
+                let all_datums = collect_datums(transactions);
+                tracing::info!("Extracted {} datums", all_datums.len());
+                crate::cli::output::output_json(w, transactions, &all_datums, Some(graph))?;
+            }
+            OutputFormat::Table => {
+                let all_datums = collect_datums(transactions);
+                tracing::info!("Extracted {} datums", all_datums.len());
+                crate::cli::output::output_table(w, transactions, &all_datums)?;
+            }
+            OutputFormat::Csv => {
+                let all_datums = collect_datums(transactions);
+                tracing::info!("Extracted {} datums", all_datums.len());
+                crate::cli::output::output_csv(w, transactions, &all_datums)?;
+            }
+            OutputFormat::Dot => writeln!(w, "{}", graph.to_dot())?,
+            OutputFormat::Mermaid => write!(w, "{}", graph.to_mermaid())?,
+            OutputFormat::Tui | OutputFormat::None => {}
+        }
+        Ok(())
+    }
 }
 
 /// Watch command implementation
 pub mod watch {
     use super::*;
-    use crate::parser::schema::ContractSchema;
-    use crate::{
-        cli::Commands,
-        data_source::{QueryParams, create_data_source},
-    };
+    use crate::cli::Commands;
+    use std::sync::Arc;
     use std::time::Duration;
-    use tokio::sync::mpsc;
+    use tokio::sync::{mpsc, watch};
 
     /// Execute the watch command
     pub async fn execute(args: Cli, config: Config) -> Result<()> {
-        let (address, source, interval_secs, schema, max_transactions) = match args.command {
+        let theme = args.theme();
+        let query_params = args.command.query_params();
+        let (
+            address,
+            source,
+            network,
+            api_key,
+            interval,
+            schema,
+            cache,
+            cache_ttl,
+            exit_after,
+            dry_run,
+        ) = match args.command {
             Commands::Watch {
                 address,
                 source,
+                network,
+                api_key,
                 interval,
                 schema,
-                max_transactions,
+                no_cache,
+                cache_ttl,
+                exit_after,
+                dry_run,
                 ..
             } => (
                 address,
                 source,
-                interval.as_secs(),
+                network,
+                api_key,
+                interval,
                 schema,
-                max_transactions,
+                !no_cache,
+                // Cached address listings must expire before the next poll
+                cache_ttl.min(interval),
+                exit_after,
+                dry_run,
             ),
             _ => unreachable!("watch::execute called with wrong command"),
         };
 
+        let Settings {
+            config,
+            source,
+            network,
+        } = resolve_settings(
+            config,
+            api_key,
+            source,
+            DataSourceType::Blockfrost,
+            network.as_deref(),
+            Some(&address),
+        )?;
+        if dry_run {
+            print!("{}", config.report(source, network));
+            return Ok(());
+        }
+        let source = source.0;
+
         // Initial fetch
         tracing::info!("Fetching initial data...");
-        let data_source =
-            create_data_source(source, &config, false, Duration::from_secs(0)).await?;
+        let data_source: Arc<dyn DataSource> =
+            create_data_source(source, network.0, &config, cache, cache_ttl)
+                .await?
+                .into();
+
+        // Prepare parser
+        let schema_parser = config.resolve_schema(schema.as_deref())?;
+
+        let (graph, transactions) = api::analyze_data_source(
+            data_source.as_ref(),
+            &address,
+            schema_parser.as_ref(),
+            query_params.clone(),
+        )
+        .await?;
 
-        let mut query_params = QueryParams::default();
-        if let Some(limit) = max_transactions {
-            query_params = query_params.limit(limit);
+        // Setup channels
+        let (tx_sender, rx_receiver) = mpsc::channel(1);
+        let (refresh_sender, refresh_receiver) = mpsc::channel(1);
+        let (shutdown, shutdown_receiver) = watch::channel(false);
+
+        // Spawn watcher task, which keeps its own copy of the data to merge new
+        // transactions into
+        let watcher = Watcher {
+            data_source,
+            address,
+            query_params,
+            schema_parser: schema_parser
+                .as_ref()
+                .map(|p| SchemaParser::new(p.schema.clone())),
+            graph: graph.clone(),
+            transactions: transactions.clone(),
+        };
+        let watcher =
+            tokio::spawn(watcher.run(interval, refresh_receiver, tx_sender, shutdown_receiver));
+
+        // Stop watching (and close the TUI) once the time is up
+        if let Some(exit_after) = exit_after {
+            let shutdown = shutdown.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(exit_after).await;
+                shutdown.send_replace(true);
+            });
         }
 
-        let mut transactions = data_source
-            .get_transactions_by_address(&address, query_params)
+        // Run TUI with receiver
+        tracing::info!("Launching TUI in watch mode...");
+        let result = crate::tui::run(
+            graph,
+            transactions,
+            Some(rx_receiver),
+            Some(refresh_sender),
+            Some(shutdown.clone()),
+            theme,
+        );
+
+        shutdown.send_replace(true);
+        if let Err(e) = watcher.await {
+            tracing::warn!("Watcher task failed: {}", e);
+        }
+        result
+    }
+
+    /// Polls an address and merges new transactions into its own copy of the graph
+    pub(crate) struct Watcher {
+        /// Built once, so polls share its cache instead of refetching everything
+        pub(crate) data_source: Arc<dyn DataSource>,
+        pub(crate) address: String,
+        pub(crate) query_params: QueryParams,
+        pub(crate) schema_parser: Option<SchemaParser>,
+        pub(crate) graph: StateGraph,
+        pub(crate) transactions: Vec<Transaction>,
+    }
+
+    impl Watcher {
+        /// Poll every `interval` or on refresh requests, sending each update, until
+        /// `shutdown` is set, the TUI goes away or updates can no longer be delivered
+        pub(crate) async fn run(
+            mut self,
+            interval: Duration,
+            mut refresh_receiver: mpsc::Receiver<()>,
+            update_sender: mpsc::Sender<GraphUpdate>,
+            mut shutdown: watch::Receiver<bool>,
+        ) {
+            let mut interval = tokio::time::interval(interval);
+            interval.tick().await; // First tick is immediate, but we already did initial load, so we skip it.
+            while !*shutdown.borrow_and_update() {
+                // Wait for the next poll, or an explicit refresh from the TUI
+                tokio::select! {
+                    _ = shutdown.changed() => break,
+                    _ = interval.tick() => {}
+                    request = refresh_receiver.recv() => {
+                        if request.is_none() {
+                            break; // TUI closed
+                        }
+                        interval.reset();
+                    }
+                }
+
+                // A poll in flight is abandoned on shutdown
+                let update = tokio::select! {
+                    _ = shutdown.changed() => break,
+                    update = self.poll() => update,
+                };
+                if update_sender.send(update).await.is_err() {
+                    break; // Receiver closed
+                }
+            }
+            tracing::debug!("Watcher for {} stopped", self.address);
+        }
+
+        /// Fetch and merge only the transactions since the last poll
+        async fn poll(&mut self) -> GraphUpdate {
+            super::merge_new_transactions(
+                self.data_source.as_ref(),
+                &self.address,
+                self.query_params.clone(),
+                self.schema_parser.as_ref(),
+                &mut self.graph,
+                &mut self.transactions,
+            )
             .await?;
+            Ok(DataUpdate {
+                graph: self.graph.clone(),
+                transactions: self.transactions.clone(),
+                incremental: true,
+            })
+        }
+    }
+}
 
-        // Prepare parser
-        let schema_parser = if let Some(schema_path) = &schema {
-            tracing::info!("Loading schema from {:?}", schema_path);
-            let contract_schema = ContractSchema::from_file(schema_path)?;
-            Some(SchemaParser::new(contract_schema))
+/// Export command implementation
+pub mod export {
+    use super::*;
+    use crate::cli::{Commands, ExportFormat};
+    use std::io::Write;
+    use std::path::Path;
+
+    /// Execute the export command
+    pub async fn execute(args: Cli, config: Config) -> Result<()> {
+        let query_params = args.command.query_params();
+        let (address, source, network, api_key, schema, format, output_path) = match args.command {
+            Commands::Export {
+                address,
+                source,
+                network,
+                api_key,
+                schema,
+                format,
+                output_path,
+            } => (
+                address,
+                source,
+                network,
+                api_key,
+                schema,
+                format,
+                output_path,
+            ),
+            _ => unreachable!("export::execute called with wrong command"),
+        };
+
+        let Prepared {
+            data_source,
+            schema_parser,
+            ..
+        } = prepare(
+            config,
+            api_key,
+            source,
+            network.as_deref(),
+            Some(&address),
+            schema.as_deref(),
+        )
+        .await?;
+
+        let (graph, transactions) = api::analyze_data_source(
+            data_source.as_ref(),
+            &address,
+            schema_parser.as_ref(),
+            query_params,
+        )
+        .await?;
+
+        write_export(&output_path, format, &graph, &transactions)?;
+        let written = if format == ExportFormat::Csv {
+            let (transactions_path, datums_path) = csv_paths(&output_path);
+            format!(
+                "{} and {}",
+                transactions_path.display(),
+                datums_path.display()
+            )
         } else {
-            None
+            output_path.display().to_string()
         };
+        println!(
+            "Wrote {} states and {} transactions to {}",
+            graph.graph.node_count(),
+            transactions.len(),
+            written
+        );
+        Ok(())
+    }
 
-        // Process initial data (hydrate/parse)
-        process_transactions(&mut transactions, schema_parser.as_ref());
+    /// Files written by a CSV export to `path`: `<stem>_transactions.csv` and
+    /// `<stem>_datums.csv` in the same directory
+    pub fn csv_paths(path: &Path) -> (PathBuf, PathBuf) {
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        (
+            path.with_file_name(format!("{}_transactions.csv", stem)),
+            path.with_file_name(format!("{}_datums.csv", stem)),
+        )
+    }
 
-        let graph = crate::state_machine::build_state_graph(
-            &transactions,
+    /// Write the graph and transactions to `path` in `format`
+    ///
+    /// CSV is split into a transactions and a datums file, see [`csv_paths`].
+    pub fn write_export(
+        path: &Path,
+        format: ExportFormat,
+        graph: &StateGraph,
+        transactions: &[Transaction],
+    ) -> Result<()> {
+        match format {
+            ExportFormat::Dot => write_file(path, |file| Ok(writeln!(file, "{}", graph.to_dot())?)),
+            ExportFormat::Mermaid => {
+                write_file(path, |file| Ok(write!(file, "{}", graph.to_mermaid())?))
+            }
+            ExportFormat::Json => write_file(path, |file| {
+                crate::cli::output::output_json(
+                    file,
+                    transactions,
+                    &collect_datums(transactions),
+                    Some(graph),
+                )
+            }),
+            ExportFormat::Table => write_file(path, |file| {
+                crate::cli::output::output_table(file, transactions, &collect_datums(transactions))
+            }),
+            ExportFormat::Csv => {
+                let (transactions_path, datums_path) = csv_paths(path);
+                write_file(&transactions_path, |file| {
+                    crate::cli::output::output_transactions_csv(file, transactions)
+                })?;
+                write_file(&datums_path, |file| {
+                    crate::cli::output::output_datums_csv(file, &collect_datums(transactions))
+                })
+            }
+            ExportFormat::Snapshot => graph.save(path),
+        }
+    }
+
+    /// Create `path` and fill it with `write`
+    fn write_file(
+        path: &Path,
+        write: impl FnOnce(&mut std::io::BufWriter<std::fs::File>) -> Result<()>,
+    ) -> Result<()> {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        write(&mut file)?;
+        file.flush()?;
+        Ok(())
+    }
+}
+
+/// Inspect command implementation
+pub mod inspect {
+    use super::*;
+    use crate::cli::{Commands, InspectFormat};
+
+    /// Execute the inspect command
+    pub async fn execute(args: Cli, config: Config) -> Result<()> {
+        let (tx_hash, source, network, api_key, schema, output) = match args.command {
+            Commands::Inspect {
+                tx_hash,
+                source,
+                network,
+                api_key,
+                schema,
+                output,
+            } => (tx_hash, source, network, api_key, schema, output),
+            _ => unreachable!("inspect::execute called with wrong command"),
+        };
+
+        let Prepared {
+            data_source,
+            schema_parser,
+            ..
+        } = prepare(
+            config,
+            api_key,
+            source,
+            network.as_deref(),
+            None,
+            schema.as_deref(),
+        )
+        .await?;
+
+        let transaction =
+            fetch_transaction(data_source.as_ref(), &tx_hash, schema_parser.as_ref()).await?;
+        write_transaction(&mut std::io::stdout(), output, &transaction)
+    }
+
+    /// Fetch a transaction and parse its datums and redeemers
+    pub async fn fetch_transaction(
+        data_source: &dyn DataSource,
+        tx_hash: &str,
+        schema_parser: Option<&SchemaParser>,
+    ) -> Result<Transaction> {
+        tracing::info!("Fetching transaction {}", tx_hash);
+        let mut transactions = [data_source.get_transaction(tx_hash).await?];
+        process_transactions(&mut transactions, schema_parser);
+        let [transaction] = transactions;
+        Ok(transaction)
+    }
+
+    /// Write a single transaction to `w` in `format`
+    pub fn write_transaction(
+        w: &mut impl std::io::Write,
+        format: InspectFormat,
+        transaction: &Transaction,
+    ) -> Result<()> {
+        match format {
+            InspectFormat::Json => crate::cli::output::output_transaction_json(w, transaction),
+            InspectFormat::Table => crate::cli::output::output_transaction_table(w, transaction),
+        }
+    }
+}
+
+/// Stats command implementation
+pub mod stats {
+    use super::*;
+    use crate::api::PipelineMetrics;
+    use crate::cli::Commands;
+    use crate::state_machine::analyzer::detect_pattern;
+
+    /// Execute the stats command
+    pub async fn execute(args: Cli, config: Config) -> Result<()> {
+        let query_params = args.command.query_params();
+        let (address, source, network, api_key, schema) = match args.command {
+            Commands::Stats {
+                address,
+                source,
+                network,
+                api_key,
+                schema,
+            } => (address, source, network, api_key, schema),
+            _ => unreachable!("stats::execute called with wrong command"),
+        };
+
+        let Prepared {
+            settings,
+            data_source,
+            schema_parser,
+        } = prepare(
+            config,
+            api_key,
+            source,
+            network.as_deref(),
+            Some(&address),
+            schema.as_deref(),
+        )
+        .await?;
+
+        let (graph, _, metrics) = api::analyze_data_source_with_metrics(
+            data_source.as_ref(),
             &address,
             schema_parser.as_ref(),
-        )?;
+            query_params,
+        )
+        .await?;
 
-        // Setup channel
-        let (tx_sender, rx_receiver) = mpsc::channel(1);
+        // An empty summary would pass a CI check that should fail
+        if graph.graph.node_count() == 0 {
+            return Err(crate::Error::NoTransactions {
+                address,
+                network: settings.network.0.to_string(),
+            });
+        }
 
-        // Spawn watcher task
-        let address_clone = address.clone();
-        let source_clone = source;
-        let config_clone = config.clone();
-        let schema_parser_clone = schema_parser
-            .as_ref()
-            .map(|p| SchemaParser::new(p.schema.clone()));
+        let json = stats_json(&graph, Some(&metrics));
+        println!("{}", serde_json::to_string_pretty(&json)?);
+        Ok(())
+    }
 
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
-            interval.tick().await; // First tick is immediate, but we already did initial load, so we skip it.
-            loop {
-                // Fetch new data
-                // TODO: For simplicity, re-fetch all. In prod, use from_block/slot.
-                if let Ok(ds) =
-                    create_data_source(source_clone, &config_clone, false, Duration::from_secs(0))
-                        .await
-                {
-                    let mut query_params = QueryParams::default();
-                    if let Some(limit) = max_transactions {
-                        query_params = query_params.limit(limit);
-                    }
+    /// Graph statistics and pattern analysis of `graph` as one JSON object, with
+    /// the pipeline's timings when they are known
+    pub fn stats_json(graph: &StateGraph, metrics: Option<&PipelineMetrics>) -> serde_json::Value {
+        serde_json::json!({
+            "script_address": graph.script_address,
+            "stats": graph.stats(),
+            "analysis": detect_pattern(graph),
+            "metrics": metrics,
+        })
+    }
+}
 
-                    if let Ok(mut new_txs) = ds
-                        .get_transactions_by_address(&address_clone, query_params)
-                        .await
-                    {
-                        // Process
-                        super::process_transactions(&mut new_txs, schema_parser_clone.as_ref());
-
-                        if let Ok(new_graph) = crate::state_machine::build_state_graph(
-                            &new_txs,
-                            &address_clone,
-                            schema_parser_clone.as_ref(),
-                        ) && tx_sender.send((new_graph, new_txs)).await.is_err()
-                        {
-                            break; // Receiver closed
-                        }
-                    }
-                }
+/// Schema init command implementation
+pub mod schema_init {
+    use super::*;
+    use crate::cli::Commands;
+    use crate::parser::PlutusData;
+    use crate::parser::cbor::decode_plutus_data;
+    use crate::parser::schema::{
+        ContractInfo, ContractSchema, DatumDefinition, DatumSchema, FieldDef, RedeemerSchema,
+    };
+    use std::collections::{BTreeMap, BTreeSet};
 
-                interval.tick().await;
-            }
-        });
+    /// Execute the schema-init command
+    pub async fn execute(args: Cli, config: Config) -> Result<()> {
+        let query_params = args.command.query_params();
+        let (address, source, network, api_key, output) = match args.command {
+            Commands::SchemaInit {
+                address,
+                source,
+                network,
+                api_key,
+                output,
+            } => (address, source, network, api_key, output),
+            _ => unreachable!("schema_init::execute called with wrong command"),
+        };
 
-        // Run TUI with receiver
-        tracing::info!("Launching TUI in watch mode...");
-        crate::tui::run(graph, transactions, Some(rx_receiver))?;
+        // The starter schema is inferred, so a configured one doesn't apply
+        let Prepared { data_source, .. } = prepare(
+            config,
+            api_key,
+            source,
+            network.as_deref(),
+            Some(&address),
+            None,
+        )
+        .await?;
+        let transactions = data_source
+            .get_transactions_by_address(&address, query_params)
+            .await?;
+        let schema = scaffold_schema(&address, &transactions)?;
+        let toml = schema_to_toml(&schema)?;
 
+        match output {
+            Some(path) => {
+                std::fs::write(&path, toml)?;
+                eprintln!("Wrote starter schema to {}", path.display());
+            }
+            None => print!("{}", toml),
+        }
         Ok(())
     }
+
+    /// Infer a starter schema from the datums locked at `address` and the redeemers
+    /// spending them
+    ///
+    /// The most common top-level datum constructor becomes the datum layout, with
+    /// `field_0..N` typed after the first datum seen with that constructor.
+    pub fn scaffold_schema(address: &str, transactions: &[Transaction]) -> Result<ContractSchema> {
+        let mut constructors: BTreeMap<u64, (usize, Vec<PlutusData>)> = BTreeMap::new();
+        for output in transactions
+            .iter()
+            .flat_map(|tx| &tx.outputs)
+            .filter(|output| output.address == address)
+        {
+            if let Some(datum) = &output.datum
+                && let Ok(PlutusData::Constr { tag, fields }) = decode_plutus_data(&datum.raw_cbor)
+            {
+                constructors.entry(tag).or_insert((0, fields)).0 += 1;
+            }
+        }
+
+        // Ties go to the lowest constructor index
+        let (constructor_index, (samples, fields)) = constructors
+            .into_iter()
+            .max_by(|(a_tag, (a, _)), (b_tag, (b, _))| a.cmp(b).then(b_tag.cmp(a_tag)))
+            .ok_or_else(|| {
+                crate::Error::custom(format!("No constr datums found at {}", address))
+            })?;
+
+        let redeemer_indices: BTreeSet<u64> = transactions
+            .iter()
+            .filter(|tx| {
+                tx.inputs
+                    .iter()
+                    .any(|input| input.address.as_deref() == Some(address))
+            })
+            .flat_map(|tx| &tx.witnesses.redeemers)
+            .filter(|redeemer| matches!(redeemer.tag, crate::data_source::RedeemerTag::Spend))
+            .filter_map(|redeemer| decode_plutus_data(&redeemer.raw_cbor).ok()?.as_constr_tag())
+            .collect();
+
+        Ok(ContractSchema {
+            contract: ContractInfo {
+                name: "contract".to_string(),
+                description: Some(format!(
+                    "Generated by schema-init from {} datum(s)",
+                    samples
+                )),
+                script_address: address.to_string(),
+            },
+            datum: DatumDefinition::Single(DatumSchema {
+                datum_type: "constr".to_string(),
+                constructor_index,
+                fields: fields
+                    .iter()
+                    .enumerate()
+                    .map(|(i, field)| FieldDef {
+                        name: format!("field_{}", i),
+                        field_type: field.kind_name().to_string(),
+                        desc: None,
+                    })
+                    .collect(),
+            }),
+            redeemer: redeemer_indices
+                .into_iter()
+                .map(|index| RedeemerSchema {
+                    name: format!("redeemer_{}", index),
+                    constructor_index: index,
+                })
+                .collect(),
+            states: Default::default(),
+            transitions: Default::default(),
+        })
+    }
+
+    /// Serialize a schema as TOML, headed by a note to rename the generated names
+    pub fn schema_to_toml(schema: &ContractSchema) -> Result<String> {
+        let body = toml::to_string_pretty(schema)
+            .map_err(|e| crate::Error::custom(format!("Failed to serialize schema: {}", e)))?;
+        Ok(format!(
+            "# Starter schema generated by `cardano-state-viz schema-init`.\n\
+             # Rename the fields and redeemers, then check it with `schema-validate`.\n\n{}",
+            body
+        ))
+    }
 }
 
 /// Schema validate command implementation
 pub mod schema_validate {
     use super::*;
-    use crate::parser::schema::ContractSchema;
+    use crate::parser::schema::{ContractSchema, DatumDefinition, FieldDef};
+    use serde::Serialize;
+    use std::collections::HashMap;
+    use std::path::Path;
+
+    /// How serious a schema problem is
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum Severity {
+        /// The schema can't be used
+        Error,
+        /// The schema loads but is probably not what was meant
+        Warning,
+    }
+
+    /// One problem found in a schema
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+    pub struct ValidationIssue {
+        pub severity: Severity,
+        pub message: String,
+        /// Where in the schema the problem is, e.g. `datum.fields[2]`
+        pub location: String,
+    }
+
+    impl ValidationIssue {
+        fn error(location: impl Into<String>, message: impl Into<String>) -> Self {
+            Self {
+                severity: Severity::Error,
+                message: message.into(),
+                location: location.into(),
+            }
+        }
+
+        fn warning(location: impl Into<String>, message: impl Into<String>) -> Self {
+            Self {
+                severity: Severity::Warning,
+                message: message.into(),
+                location: location.into(),
+            }
+        }
+    }
+
+    impl std::fmt::Display for ValidationIssue {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}: {}", self.location, self.message)
+        }
+    }
 
     /// Execute the schema-validate command
-    pub fn execute(schema_path: PathBuf) -> Result<()> {
+    ///
+    /// With `json`, only a machine-readable summary of the issues is printed.
+    pub fn execute(schema_path: PathBuf, json: bool) -> Result<()> {
         tracing::info!("Validating schema: {:?}", schema_path);
 
         // Load schema file
@@ -316,34 +1240,125 @@ pub mod schema_validate {
             }
         };
 
-        // Validate structure
-        let mut errors = Vec::new();
-        let mut warnings = Vec::new();
+        let issues = validate(&schema);
+        let (errors, warnings): (Vec<_>, Vec<_>) = issues
+            .iter()
+            .partition(|issue| issue.severity == Severity::Error);
+
+        if json {
+            let summary = serde_json::json!({
+                "file": schema_path,
+                "valid": errors.is_empty(),
+                "errors": errors,
+                "warnings": warnings,
+            });
+            println!("{}", serde_json::to_string_pretty(&summary)?);
+        } else {
+            print_report(&schema_path, &schema, &errors, &warnings);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(crate::Error::custom("Schema validation failed"))
+        }
+    }
+
+    /// Check a schema's structure, naming the offending entry in each issue
+    pub fn validate(schema: &ContractSchema) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
 
         if schema.contract.name.is_empty() {
-            errors.push("Contract name cannot be empty");
+            issues.push(ValidationIssue::error(
+                "contract.name",
+                "Contract name cannot be empty",
+            ));
         }
         if schema.contract.script_address.is_empty() {
-            errors.push("Script address cannot be empty");
+            issues.push(ValidationIssue::error(
+                "contract.script_address",
+                "Script address cannot be empty",
+            ));
         } else if !schema.contract.script_address.starts_with("addr") {
-            warnings.push("Script address should start with 'addr'");
+            issues.push(ValidationIssue::warning(
+                "contract.script_address",
+                format!(
+                    "Script address '{}' should start with 'addr'",
+                    schema.contract.script_address
+                ),
+            ));
         }
-        if schema.datum.datum_type.is_empty() {
-            errors.push("Datum type cannot be empty");
+        match &schema.datum {
+            DatumDefinition::Single(datum) => {
+                if datum.datum_type.is_empty() {
+                    issues.push(ValidationIssue::error(
+                        "datum.type",
+                        "Datum type cannot be empty",
+                    ));
+                }
+                duplicate_names(&datum.fields, "datum", &mut issues);
+            }
+            DatumDefinition::Variants(variants) => {
+                if variants.is_empty() {
+                    issues.push(ValidationIssue::error(
+                        "datum",
+                        "Datum must have at least one variant",
+                    ));
+                }
+                let mut variant_indices = HashMap::new();
+                for (i, variant) in variants.iter().enumerate() {
+                    if let Some(other) =
+                        variant_indices.insert(variant.constructor_index, &variant.name)
+                    {
+                        issues.push(ValidationIssue::error(
+                            format!("datum[{}]", i),
+                            format!(
+                                "Duplicate datum variant constructor index {} ('{}' and '{}')",
+                                variant.constructor_index, other, variant.name
+                            ),
+                        ));
+                    }
+                    duplicate_names(&variant.fields, &format!("datum[{}]", i), &mut issues);
+                }
+            }
         }
-        let mut field_names = std::collections::HashSet::new();
-        for field in &schema.datum.fields {
-            if !field_names.insert(&field.name) {
-                errors.push("Duplicate field name");
+        let mut redeemer_indices = HashMap::new();
+        for (i, redeemer) in schema.redeemer.iter().enumerate() {
+            if let Some(other) = redeemer_indices.insert(redeemer.constructor_index, &redeemer.name)
+            {
+                issues.push(ValidationIssue::error(
+                    format!("redeemer[{}]", i),
+                    format!(
+                        "Duplicate redeemer constructor index {} ('{}' and '{}')",
+                        redeemer.constructor_index, other, redeemer.name
+                    ),
+                ));
             }
         }
-        let mut redeemer_indices = std::collections::HashSet::new();
-        for redeemer in &schema.redeemer {
-            if !redeemer_indices.insert(redeemer.constructor_index) {
-                errors.push("Duplicate redeemer constructor index");
+
+        issues
+    }
+
+    /// Report every field of a datum (at `location`) whose name was already used
+    fn duplicate_names(fields: &[FieldDef], location: &str, issues: &mut Vec<ValidationIssue>) {
+        let mut names = std::collections::HashSet::new();
+        for (i, field) in fields.iter().enumerate() {
+            if !names.insert(&field.name) {
+                issues.push(ValidationIssue::error(
+                    format!("{}.fields[{}]", location, i),
+                    format!("Duplicate field name '{}'", field.name),
+                ));
             }
         }
+    }
 
+    /// Print the human-readable validation report
+    fn print_report(
+        schema_path: &Path,
+        schema: &ContractSchema,
+        errors: &[&ValidationIssue],
+        warnings: &[&ValidationIssue],
+    ) {
         // Print validation report
         println!("📋 Schema Validation Report");
         println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
@@ -357,10 +1372,28 @@ pub mod schema_validate {
         println!("  Script Address: {}", schema.contract.script_address);
         println!();
         println!("Datum:");
-        println!("  Type: {}", schema.datum.datum_type);
-        println!("  Fields: {}", schema.datum.fields.len());
-        for field in &schema.datum.fields {
-            println!("    - {} ({})", field.name, field.field_type);
+        match &schema.datum {
+            DatumDefinition::Single(datum) => {
+                println!("  Type: {}", datum.datum_type);
+                println!("  Fields: {}", datum.fields.len());
+                for field in &datum.fields {
+                    println!("    - {} ({})", field.name, field.field_type);
+                }
+            }
+            DatumDefinition::Variants(variants) => {
+                println!("  Variants: {}", variants.len());
+                for variant in variants {
+                    println!(
+                        "    {} (index {}): {} fields",
+                        variant.name,
+                        variant.constructor_index,
+                        variant.fields.len()
+                    );
+                    for field in &variant.fields {
+                        println!("      - {} ({})", field.name, field.field_type);
+                    }
+                }
+            }
         }
         println!();
         println!("Redeemers: {}", schema.redeemer.len());
@@ -375,7 +1408,7 @@ pub mod schema_validate {
         // Print errors
         if !errors.is_empty() {
             println!("❌ Errors:");
-            for error in &errors {
+            for error in errors {
                 println!("   {}", error);
             }
             println!();
@@ -384,19 +1417,609 @@ pub mod schema_validate {
         // Print warnings
         if !warnings.is_empty() {
             println!("⚠️  Warnings:");
-            for warning in &warnings {
+            for warning in warnings {
                 println!("   {}", warning);
             }
             println!();
         }
 
-        // Final verdict
         if errors.is_empty() {
             println!("✅ Schema is valid!");
-            Ok(())
         } else {
             println!("❌ Schema validation failed with {} error(s)", errors.len());
-            Err(crate::Error::custom("Schema validation failed"))
         }
     }
 }
+
+/// Diff command implementation
+pub mod diff {
+    use super::*;
+    use crate::state_machine::GraphDiff;
+    use std::path::Path;
+
+    /// Execute the diff command
+    pub fn execute(old_snapshot: &Path, new_snapshot: &Path) -> Result<()> {
+        let old = StateGraph::load(old_snapshot)?;
+        let new = StateGraph::load(new_snapshot)?;
+        write_diff(&mut std::io::stdout(), &old.diff(&new))
+    }
+
+    /// Write the added, removed and newly terminal states of a diff
+    pub fn write_diff(w: &mut impl std::io::Write, diff: &GraphDiff) -> Result<()> {
+        if diff.is_empty() {
+            writeln!(w, "No changes")?;
+            return Ok(());
+        }
+
+        for (title, marker, ids) in [
+            ("Added states", "+", &diff.added_states),
+            ("Removed states", "-", &diff.removed_states),
+            ("Newly terminal states", "■", &diff.newly_terminal),
+        ] {
+            writeln!(w, "{} ({}):", title, ids.len())?;
+            for id in ids {
+                writeln!(w, "  {} {}", marker, id)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_source::models::TxOutput;
+    use crate::data_source::{
+        UtxoRef,
+        mock::{MOCK_SCRIPT_ADDRESS, MockDataSource},
+    };
+    use async_trait::async_trait;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    /// Mock source counting address queries
+    #[derive(Default)]
+    struct CountingSource {
+        inner: MockDataSource,
+        address_queries: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl DataSource for CountingSource {
+        async fn get_transaction(&self, tx_hash: &str) -> Result<Transaction> {
+            self.inner.get_transaction(tx_hash).await
+        }
+
+        async fn get_transactions_by_address(
+            &self,
+            address: &str,
+            params: QueryParams,
+        ) -> Result<Vec<Transaction>> {
+            self.address_queries.fetch_add(1, Ordering::SeqCst);
+            self.inner
+                .get_transactions_by_address(address, params)
+                .await
+        }
+
+        async fn get_script_utxos(
+            &self,
+            address: &str,
+            params: QueryParams,
+        ) -> Result<Vec<(UtxoRef, TxOutput)>> {
+            self.inner.get_script_utxos(address, params).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_watcher_stops_on_shutdown() {
+        use tokio::sync::mpsc;
+
+        let (graph, transactions) = api::analyze(
+            MOCK_SCRIPT_ADDRESS,
+            Box::new(crate::data_source::mock::MockDataSource::new()),
+            None,
+            QueryParams::default(),
+        )
+        .await
+        .unwrap();
+        let source = Arc::new(CountingSource::default());
+        let watcher = watch::Watcher {
+            data_source: source.clone(),
+            address: MOCK_SCRIPT_ADDRESS.to_string(),
+            query_params: QueryParams::default(),
+            schema_parser: None,
+            graph,
+            transactions,
+        };
+
+        let (update_sender, mut update_receiver) = mpsc::channel(1);
+        let (refresh_sender, refresh_receiver) = mpsc::channel(1);
+        let (shutdown, shutdown_receiver) = tokio::sync::watch::channel(false);
+        let handle = tokio::spawn(watcher.run(
+            Duration::from_secs(3600),
+            refresh_receiver,
+            update_sender,
+            shutdown_receiver,
+        ));
+
+        // Still serving refresh requests until told to stop
+        refresh_sender.send(()).await.unwrap();
+        let update = update_receiver.recv().await.unwrap().unwrap();
+        assert!(update.incremental);
+        assert_eq!(update.transactions.len(), 5);
+        refresh_sender.send(()).await.unwrap();
+        update_receiver.recv().await.unwrap().unwrap();
+        // Every poll goes through the source built in `execute`, and its cache
+        assert_eq!(source.address_queries.load(Ordering::SeqCst), 2);
+
+        shutdown.send_replace(true);
+        tokio::time::timeout(Duration::from_secs(5), handle)
+            .await
+            .expect("watcher did not stop")
+            .unwrap();
+        assert!(update_receiver.recv().await.is_none());
+        drop(refresh_sender);
+    }
+
+    #[tokio::test]
+    async fn test_analyze_output_none() {
+        use crate::cli::OutputFormat;
+        use clap::Parser;
+
+        let args = Cli::try_parse_from([
+            "cardano-state-viz",
+            "analyze",
+            "--address",
+            MOCK_SCRIPT_ADDRESS,
+            "--source",
+            "mock",
+            "--no-cache",
+            "--output",
+            "none",
+        ])
+        .unwrap();
+        assert!(analyze::execute(args, Config::default()).await.is_ok());
+
+        let (graph, transactions) = api::analyze(
+            MOCK_SCRIPT_ADDRESS,
+            Box::new(crate::data_source::mock::MockDataSource::new()),
+            None,
+            QueryParams::default(),
+        )
+        .await
+        .unwrap();
+        let mut output = Vec::new();
+        analyze::write_output(&mut output, OutputFormat::None, &graph, &transactions).unwrap();
+        assert!(output.is_empty());
+
+        analyze::write_output(&mut output, OutputFormat::Mermaid, &graph, &transactions).unwrap();
+        assert!(!output.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_analyze_address_without_transactions() {
+        use clap::Parser;
+
+        let address = "addr_test1wrphkx6acpnf78fuvxn0mkew3l0fd058hzquvz7w36x4gtcl6szpr";
+        let args = |extra: &[&str]| {
+            let mut args = vec![
+                "cardano-state-viz",
+                "analyze",
+                "--address",
+                address,
+                "--source",
+                "mock",
+                "--network",
+                "preview",
+                "--no-cache",
+                "--output",
+                "none",
+            ];
+            args.extend(extra);
+            Cli::try_parse_from(args).unwrap()
+        };
+
+        let err = analyze::execute(args(&[]), Config::default())
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, crate::Error::NoTransactions { address: a, network } if a == address && network == "preview"),
+            "{:?}",
+            err
+        );
+        assert!(err.to_string().contains("preview"));
+
+        assert!(
+            analyze::execute(args(&["--allow-empty"]), Config::default())
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stats_json_for_mock_vesting_graph() {
+        let (graph, _, metrics) = api::analyze_data_source_with_metrics(
+            &crate::data_source::mock::MockDataSource::new(),
+            MOCK_SCRIPT_ADDRESS,
+            None,
+            QueryParams::default(),
+        )
+        .await
+        .unwrap();
+
+        let json = stats::stats_json(&graph, Some(&metrics));
+        assert_eq!(json["script_address"], MOCK_SCRIPT_ADDRESS);
+        assert_eq!(json["stats"]["total_states"], 4);
+        assert_eq!(json["stats"]["total_transitions"], 3);
+        assert_eq!(json["stats"]["total_ada"], 25_000_000_000u64);
+        assert_eq!(json["analysis"]["pattern"], "Vesting");
+        assert_eq!(json["analysis"]["max_depth"], 3);
+        assert_eq!(json["analysis"]["has_cycles"], false);
+        assert!(json["analysis"]["branching_factor"].is_f64());
+        assert_eq!(json["analysis"]["cycles"], serde_json::json!([]));
+        assert_eq!(json["metrics"]["tx_count"], 5);
+        assert!(stats::stats_json(&graph, None)["metrics"].is_null());
+    }
+
+    #[test]
+    fn test_schema_validate_names_offending_entries() {
+        use crate::parser::schema::ContractSchema;
+        use schema_validate::{Severity, validate};
+
+        let schema: ContractSchema = toml::from_str(
+            r#"
+[contract]
+name = "Vesting"
+script_address = "script1xyz"
+
+[datum]
+type = "constr"
+fields = [
+    { name = "owner", type = "bytes" },
+    { name = "deadline", type = "posix_time" },
+    { name = "owner", type = "int" },
+]
+
+[[redeemer]]
+name = "Claim"
+constructor_index = 0
+
+[[redeemer]]
+name = "Cancel"
+constructor_index = 0
+            "#,
+        )
+        .unwrap();
+
+        let issues = validate(&schema);
+        let summary: Vec<_> = issues
+            .iter()
+            .map(|issue| (issue.severity, issue.to_string()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (
+                    Severity::Warning,
+                    "contract.script_address: Script address 'script1xyz' should start with 'addr'"
+                        .to_string()
+                ),
+                (
+                    Severity::Error,
+                    "datum.fields[2]: Duplicate field name 'owner'".to_string()
+                ),
+                (
+                    Severity::Error,
+                    "redeemer[1]: Duplicate redeemer constructor index 0 ('Claim' and 'Cancel')"
+                        .to_string()
+                ),
+            ]
+        );
+
+        let json = serde_json::to_value(&issues[1]).unwrap();
+        assert_eq!(json["severity"], "error");
+        assert_eq!(json["location"], "datum.fields[2]");
+    }
+
+    #[tokio::test]
+    async fn test_schema_init_from_mock_vesting_datum() {
+        use crate::data_source::mock::MockDataSource;
+        use crate::parser::schema::{ContractSchema, DatumDefinition};
+
+        let transactions = MockDataSource::new()
+            .get_transactions_by_address(MOCK_SCRIPT_ADDRESS, QueryParams::default())
+            .await
+            .unwrap();
+        let schema = schema_init::scaffold_schema(MOCK_SCRIPT_ADDRESS, &transactions).unwrap();
+
+        let DatumDefinition::Single(datum) = &schema.datum else {
+            panic!("expected a single datum layout");
+        };
+        assert_eq!(datum.datum_type, "constr");
+        assert_eq!(datum.constructor_index, 0);
+        let fields: Vec<_> = datum
+            .fields
+            .iter()
+            .map(|field| (field.name.as_str(), field.field_type.as_str()))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                ("field_0", "bytes"),
+                ("field_1", "int"),
+                ("field_2", "int"),
+                ("field_3", "int")
+            ]
+        );
+        assert_eq!(schema.redeemer.len(), 1);
+        assert_eq!(schema.redeemer[0].constructor_index, 0);
+
+        // The generated file loads back as a schema
+        let toml = schema_init::schema_to_toml(&schema).unwrap();
+        let reloaded: ContractSchema = toml::from_str(&toml).unwrap();
+        assert_eq!(reloaded.contract.script_address, MOCK_SCRIPT_ADDRESS);
+
+        assert!(schema_init::scaffold_schema("addr_other", &transactions).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_inspect_mock_transaction() {
+        use crate::cli::InspectFormat;
+        use crate::data_source::mock::MockDataSource;
+
+        let tx_hash = format!("{:064x}", 2);
+        let transaction = inspect::fetch_transaction(&MockDataSource::new(), &tx_hash, None)
+            .await
+            .unwrap();
+        assert!(transaction.witnesses.redeemers[0].parsed.is_some());
+
+        let mut output = Vec::new();
+        inspect::write_transaction(&mut output, InspectFormat::Json, &transaction).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(value["hash"], tx_hash);
+        assert_eq!(value["redeemers"][0]["tag"], "Spend");
+        assert!(value["redeemers"][0]["parsed"].is_string());
+        let datums: Vec<_> = value["outputs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|output| output["datum"]["parsed"].is_string())
+            .collect();
+        assert_eq!(datums.len(), 1);
+
+        let mut output = Vec::new();
+        inspect::write_transaction(&mut output, InspectFormat::Table, &transaction).unwrap();
+        let table = String::from_utf8(output).unwrap();
+        assert!(table.contains(&tx_hash));
+        assert!(table.contains("Redeemers:"));
+
+        let missing = inspect::fetch_transaction(&MockDataSource::new(), "ff", None).await;
+        assert!(missing.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_analyze_mock_source_end_to_end() {
+        let config = Config::default();
+        let source = config.resolve_source(None, DataSourceType::Mock).unwrap().0;
+        let data_source = create_data_source(
+            source,
+            crate::Network::default(),
+            &config,
+            true,
+            Duration::from_secs(60),
+        )
+        .await
+        .unwrap();
+
+        let update = fetch_graph_update(
+            data_source.as_ref(),
+            MOCK_SCRIPT_ADDRESS,
+            QueryParams::default(),
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert!(!update.incremental);
+        assert_eq!(update.transactions.len(), 5);
+        assert_eq!(update.graph.graph.node_count(), 4);
+        assert_eq!(update.graph.graph.edge_count(), 3);
+        assert_eq!(update.graph.find_initial_states().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_export_writes_dot_file() {
+        let (graph, transactions) = api::analyze(
+            MOCK_SCRIPT_ADDRESS,
+            Box::new(crate::data_source::mock::MockDataSource::new()),
+            None,
+            QueryParams::default(),
+        )
+        .await
+        .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.dot");
+
+        export::write_export(&path, crate::cli::ExportFormat::Dot, &graph, &transactions).unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+
+        assert_eq!(contents.trim_end(), graph.to_dot().trim_end());
+        assert!(contents.starts_with("digraph"));
+        for state in graph.all_states() {
+            assert!(contents.contains(&state.id), "{}", state.id);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_analyze_address_file_batch() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("addresses.txt");
+        std::fs::write(&path, format!("{}\nnot_an_address\n", MOCK_SCRIPT_ADDRESS)).unwrap();
+
+        let addresses = analyze::read_address_file(&path).unwrap();
+        let results = analyze::analyze_batch(
+            &crate::data_source::mock::MockDataSource::new(),
+            &addresses,
+            crate::Network::default(),
+            None,
+            QueryParams::default(),
+            analyze::AnalyzeOptions::default(),
+        )
+        .await;
+
+        let mut output = Vec::new();
+        analyze::write_batch_json(&mut output, &results).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(value[MOCK_SCRIPT_ADDRESS]["status"], "ok");
+        assert_eq!(
+            value[MOCK_SCRIPT_ADDRESS]["summary"]["total_transactions"],
+            5
+        );
+        assert_eq!(
+            value[MOCK_SCRIPT_ADDRESS]["graph"]["nodes"]
+                .as_array()
+                .unwrap()
+                .len(),
+            4
+        );
+        assert_eq!(value["not_an_address"]["status"], "error");
+        assert!(
+            value["not_an_address"]["error"]
+                .as_str()
+                .unwrap()
+                .contains("Invalid Cardano address")
+        );
+
+        let mut output = Vec::new();
+        analyze::write_batch_dot(&mut output, dir.path(), &results).unwrap();
+        let report = String::from_utf8(output).unwrap();
+        let dot = std::fs::read_to_string(dir.path().join(format!("{}.dot", MOCK_SCRIPT_ADDRESS)));
+
+        assert!(dot.unwrap().starts_with("digraph"));
+        assert!(report.contains(&format!("{}: wrote", MOCK_SCRIPT_ADDRESS)));
+        assert!(report.contains("not_an_address: error: Invalid Cardano address"));
+    }
+
+    #[tokio::test]
+    async fn test_diff_against_snapshot_missing_last_transaction() {
+        use crate::data_source::mock::MockDataSource;
+
+        let transactions = MockDataSource::new()
+            .get_transactions_by_address(MOCK_SCRIPT_ADDRESS, QueryParams::default())
+            .await
+            .unwrap();
+        let graph =
+            StateGraph::build_from_transactions(&transactions, MOCK_SCRIPT_ADDRESS, None).unwrap();
+        // The last transaction claims the funds without creating a state, so a
+        // snapshot missing only that one has the same states and nothing to report
+        let before_claim = StateGraph::build_from_transactions(
+            &transactions[..transactions.len() - 1],
+            MOCK_SCRIPT_ADDRESS,
+            None,
+        )
+        .unwrap();
+        assert!(before_claim.diff(&graph).is_empty());
+
+        // Snapshot taken before the last transition as well
+        let earlier = StateGraph::build_from_transactions(
+            &transactions[..transactions.len() - 2],
+            MOCK_SCRIPT_ADDRESS,
+            None,
+        )
+        .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("snapshot.json");
+        export::write_export(
+            &path,
+            crate::cli::ExportFormat::Snapshot,
+            &earlier,
+            &transactions,
+        )
+        .unwrap();
+        let snapshot = StateGraph::load(&path);
+
+        let diff = snapshot.unwrap().diff(&graph);
+        let mut output = Vec::new();
+        diff::write_diff(&mut output, &diff).unwrap();
+        let report = String::from_utf8(output).unwrap();
+
+        let last = format!("{:064x}#1", 4);
+        assert_eq!(diff.added_states, [last.as_str()]);
+        assert!(diff.removed_states.is_empty());
+        assert_eq!(diff.newly_terminal, [last.as_str()]);
+        assert_eq!(
+            report,
+            format!(
+                "Added states (1):\n  + {last}\nRemoved states (0):\n\
+                 Newly terminal states (1):\n  ■ {last}\n"
+            )
+        );
+
+        let mut output = Vec::new();
+        diff::write_diff(&mut output, &graph.diff(&graph)).unwrap();
+        assert_eq!(output, b"No changes\n");
+    }
+
+    #[tokio::test]
+    async fn test_export_writes_csv_files() {
+        let (graph, transactions) = api::analyze(
+            MOCK_SCRIPT_ADDRESS,
+            Box::new(crate::data_source::mock::MockDataSource::new()),
+            None,
+            QueryParams::default(),
+        )
+        .await
+        .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.csv");
+
+        export::write_export(&path, crate::cli::ExportFormat::Csv, &graph, &transactions).unwrap();
+        let (transactions_path, datums_path) = export::csv_paths(&path);
+        let transactions_csv = std::fs::read_to_string(&transactions_path).unwrap();
+        let datums_csv = std::fs::read_to_string(&datums_path).unwrap();
+
+        assert!(!path.exists());
+        assert!(transactions_csv.starts_with("hash,block,slot,inputs,outputs\n"));
+        assert_eq!(transactions_csv.lines().count(), transactions.len() + 1);
+        assert!(datums_csv.starts_with("transaction,output_index,hash,parsed\n"));
+        assert_eq!(
+            datums_csv.lines().count(),
+            collect_datums(&transactions).len() + 1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_watch_polls_merge_only_new_transactions() {
+        let data_source = crate::data_source::mock::MockDataSource::new();
+        let params = QueryParams::new().limit(2);
+
+        // Start from the first two transactions, as if the rest had not happened yet
+        let mut transactions = data_source
+            .get_transactions_by_address(MOCK_SCRIPT_ADDRESS, params.clone())
+            .await
+            .unwrap();
+        let mut graph =
+            crate::state_machine::build_state_graph(&transactions, MOCK_SCRIPT_ADDRESS, None)
+                .unwrap();
+
+        for expected in [4, 5, 5] {
+            merge_new_transactions(
+                &data_source,
+                MOCK_SCRIPT_ADDRESS,
+                params.clone(),
+                None,
+                &mut graph,
+                &mut transactions,
+            )
+            .await
+            .unwrap();
+            assert_eq!(transactions.len(), expected);
+        }
+
+        assert_eq!(graph.graph.node_count(), 4);
+        assert_eq!(graph.graph.edge_count(), 3);
+        assert_eq!(graph.find_initial_states().len(), 1);
+    }
+}
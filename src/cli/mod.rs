@@ -3,6 +3,8 @@
 //! This module defines the command-line interface using clap and implements
 //! the command execution logic.
 
+use crate::data_source::QueryParams;
+use crate::tui::theme::Theme;
 use crate::{Config, Result};
 use clap::error::ErrorKind;
 use clap::{Parser, Subcommand, ValueEnum};
@@ -21,6 +23,10 @@ pub struct Cli {
     #[arg(short, long, global = true)]
     pub config: Option<PathBuf>,
 
+    /// Draw the TUI without colors
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
     /// Subcommand to execute
     #[command(subcommand)]
     pub command: Commands,
@@ -32,19 +38,24 @@ pub enum Commands {
     /// Analyze transactions at a script address
     Analyze {
         /// Script address to analyze
-        #[arg(short, long)]
-        address: String,
+        #[arg(short, long, required_unless_present = "address_file")]
+        address: Option<String>,
 
-        /// Data source type
-        #[arg(short, long, value_enum, default_value = "mock")]
-        source: DataSourceType,
+        /// File with one script address per line to analyze in a batch (`json` or `dot`
+        /// output only)
+        #[arg(long, conflicts_with = "address")]
+        address_file: Option<PathBuf>,
+
+        /// Data source type [default: config file, else mock]
+        #[arg(short, long, value_enum)]
+        source: Option<DataSourceType>,
 
         /// Network (mainnet, preprod, preview)
         #[arg(short, long)]
         network: Option<String>,
 
-        /// Blockfrost API key (overrides config)
-        #[arg(long, env = "BLOCKFROST_API_KEY")]
+        /// Blockfrost API key (overrides BLOCKFROST_API_KEY and config)
+        #[arg(long)]
         api_key: Option<String>,
 
         /// Path to schema file
@@ -66,6 +77,43 @@ pub enum Commands {
         /// Limit the number of transactions to fetch
         #[arg(long)]
         max_transactions: Option<usize>,
+
+        /// Only include transactions from this far back (e.g. 12h, 7d, 2w)
+        #[arg(long, value_parser = duration_parser)]
+        since: Option<Duration>,
+
+        /// Only include transactions at or after this slot
+        #[arg(long)]
+        from_slot: Option<u64>,
+
+        /// Only include transactions at or before this slot
+        #[arg(long)]
+        to_slot: Option<u64>,
+
+        /// Only include transactions at or after this block height
+        #[arg(long)]
+        from_block: Option<u64>,
+
+        /// Only include transactions at or before this block height
+        #[arg(long)]
+        to_block: Option<u64>,
+
+        /// Only display states within this many transitions of an initial state
+        #[arg(long)]
+        max_depth: Option<usize>,
+
+        /// Fail if any datum cannot be resolved, hash-validated or parsed
+        #[arg(long)]
+        strict: bool,
+
+        /// Carry on with an empty graph (e.g. open the TUI) when the address has no
+        /// transactions, instead of failing
+        #[arg(long)]
+        allow_empty: bool,
+
+        /// Print the resolved configuration and exit without fetching
+        #[arg(long)]
+        dry_run: bool,
     },
 
     /// Watch for new transactions in real-time
@@ -74,9 +122,16 @@ pub enum Commands {
         #[arg(short, long)]
         address: String,
 
-        /// Data source type
-        #[arg(short, long, value_enum, default_value = "blockfrost")]
-        source: DataSourceType,
+        /// Data source type [default: config file, else blockfrost]
+        #[arg(short, long, value_enum)]
+        source: Option<DataSourceType>,
+
+        /// Network (mainnet, preprod, preview)
+        #[arg(short, long)]
+        network: Option<String>,
+        /// Blockfrost API key (overrides BLOCKFROST_API_KEY and config)
+        #[arg(long)]
+        api_key: Option<String>,
 
         /// Polling interval
         #[arg(long, default_value = "30", value_parser = duration_parser)]
@@ -90,19 +145,143 @@ pub enum Commands {
         #[arg(long)]
         no_cache: bool,
 
-        /// Cache TTL
+        /// Cache TTL (capped at the polling interval)
         #[arg(long, default_value = "3600", value_parser = duration_parser)]
         cache_ttl: Duration,
 
         /// Limit the number of transactions to fetch
         #[arg(long)]
         max_transactions: Option<usize>,
+
+        /// Stop watching and exit after this long (e.g. 30m, 2h)
+        #[arg(long, value_parser = duration_parser)]
+        exit_after: Option<Duration>,
+
+        /// Print the resolved configuration and exit without fetching
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Analyze an address and write the result to a file
+    Export {
+        /// Script address to analyze
+        #[arg(short, long)]
+        address: String,
+
+        /// Data source type [default: config file, else mock]
+        #[arg(short, long, value_enum)]
+        source: Option<DataSourceType>,
+
+        /// Network (mainnet, preprod, preview)
+        #[arg(short, long)]
+        network: Option<String>,
+
+        /// Blockfrost API key (overrides BLOCKFROST_API_KEY and config)
+        #[arg(long)]
+        api_key: Option<String>,
+
+        /// Path to schema file
+        #[arg(long)]
+        schema: Option<PathBuf>,
+
+        /// Export format
+        #[arg(short, long, value_enum)]
+        format: ExportFormat,
+
+        /// File to write
+        #[arg(short, long = "output")]
+        output_path: PathBuf,
+    },
+
+    /// Print graph statistics and the detected pattern as JSON
+    Stats {
+        /// Script address to analyze
+        #[arg(short, long)]
+        address: String,
+
+        /// Data source type [default: config file, else mock]
+        #[arg(short, long, value_enum)]
+        source: Option<DataSourceType>,
+
+        /// Network (mainnet, preprod, preview)
+        #[arg(short, long)]
+        network: Option<String>,
+
+        /// Blockfrost API key (overrides BLOCKFROST_API_KEY and config)
+        #[arg(long)]
+        api_key: Option<String>,
+
+        /// Path to schema file
+        #[arg(long)]
+        schema: Option<PathBuf>,
+    },
+
+    /// Fetch a single transaction and print its datums and redeemers
+    Inspect {
+        /// Transaction hash
+        tx_hash: String,
+
+        /// Data source type [default: config file, else mock]
+        #[arg(short, long, value_enum)]
+        source: Option<DataSourceType>,
+
+        /// Network (mainnet, preprod, preview)
+        #[arg(short, long)]
+        network: Option<String>,
+
+        /// Blockfrost API key (overrides BLOCKFROST_API_KEY and config)
+        #[arg(long)]
+        api_key: Option<String>,
+
+        /// Path to schema file
+        #[arg(long)]
+        schema: Option<PathBuf>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "table")]
+        output: InspectFormat,
+    },
+
+    /// Generate a starter schema from the datums observed at a script address
+    SchemaInit {
+        /// Script address to sample
+        #[arg(short, long)]
+        address: String,
+
+        /// Data source type [default: config file, else mock]
+        #[arg(short, long, value_enum)]
+        source: Option<DataSourceType>,
+
+        /// Network (mainnet, preprod, preview)
+        #[arg(short, long)]
+        network: Option<String>,
+
+        /// Blockfrost API key (overrides BLOCKFROST_API_KEY and config)
+        #[arg(long)]
+        api_key: Option<String>,
+
+        /// File to write [default: stdout]
+        #[arg(short, long)]
+        output: Option<PathBuf>,
     },
 
     /// Validate a schema file
     SchemaValidate {
         /// Path to schema file
         schema: PathBuf,
+
+        /// Print the issues found as JSON instead of a report
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Compare two graph snapshots written by `export --format snapshot`
+    Diff {
+        /// Snapshot from the earlier run
+        old_snapshot: PathBuf,
+
+        /// Snapshot from the later run
+        new_snapshot: PathBuf,
     },
 }
 
@@ -124,10 +303,86 @@ pub enum OutputFormat {
     Json,
     /// DOT format (Graphviz)
     Dot,
+    /// Mermaid state diagram
+    Mermaid,
     /// Interactive TUI
     Tui,
     /// Plain text table
     Table,
+    /// CSV sections for transactions and datums
+    Csv,
+    /// No output besides a one-line summary on stderr, for benchmarking
+    None,
+}
+
+impl Cli {
+    /// Colors for the TUI, monochrome with `--no-color`
+    pub fn theme(&self) -> Theme {
+        if self.no_color {
+            Theme::monochrome()
+        } else {
+            Theme::default()
+        }
+    }
+}
+
+impl Commands {
+    /// Query parameters from the command's transaction limit and slot/block bounds
+    pub fn query_params(&self) -> QueryParams {
+        let mut params = QueryParams::new();
+        match self {
+            Commands::Analyze {
+                max_transactions,
+                from_slot,
+                to_slot,
+                from_block,
+                to_block,
+                ..
+            } => {
+                params.limit = *max_transactions;
+                params.from_slot = *from_slot;
+                params.to_slot = *to_slot;
+                params.from_block = *from_block;
+                params.to_block = *to_block;
+            }
+            Commands::Watch {
+                max_transactions, ..
+            } => params.limit = *max_transactions,
+            Commands::Export { .. }
+            | Commands::Stats { .. }
+            | Commands::Inspect { .. }
+            | Commands::SchemaInit { .. }
+            | Commands::SchemaValidate { .. }
+            | Commands::Diff { .. } => {}
+        }
+        params
+    }
+}
+
+/// File formats for the export command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// DOT format (Graphviz)
+    Dot,
+    /// Mermaid state diagram
+    Mermaid,
+    /// JSON output
+    Json,
+    /// Plain text table
+    Table,
+    /// Transactions and datums as two CSV files next to the output path
+    Csv,
+    /// Graph snapshot that can be compared with `diff`
+    Snapshot,
+}
+
+/// Output formats for the inspect command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum InspectFormat {
+    /// JSON output
+    Json,
+    /// Plain text table
+    Table,
 }
 
 /// Execute the CLI command
@@ -135,7 +390,17 @@ pub async fn execute(args: Cli, config: Config) -> Result<()> {
     match args.command {
         Commands::Analyze { .. } => commands::analyze::execute(args, config).await,
         Commands::Watch { .. } => commands::watch::execute(args, config).await,
-        Commands::SchemaValidate { schema } => commands::schema_validate::execute(schema),
+        Commands::Export { .. } => commands::export::execute(args, config).await,
+        Commands::Stats { .. } => commands::stats::execute(args, config).await,
+        Commands::Inspect { .. } => commands::inspect::execute(args, config).await,
+        Commands::SchemaInit { .. } => commands::schema_init::execute(args, config).await,
+        Commands::SchemaValidate { schema, json } => {
+            commands::schema_validate::execute(schema, json)
+        }
+        Commands::Diff {
+            old_snapshot,
+            new_snapshot,
+        } => commands::diff::execute(&old_snapshot, &new_snapshot),
     }
 }
 
@@ -145,18 +410,21 @@ fn duration_parser(arg: &str) -> std::result::Result<Duration, clap::Error> {
 
 /// It accepts a number and a unit:
 ///
+///  - w: week
+///  - d: day
 ///  - h: hour
 ///  - m: minute
 ///  - s: second
 ///  - ms: millisecond
 ///
-/// For example: 1ms, 2s, 10m
+/// For example: 1ms, 2s, 10m, 7d
 /// Defaults to seconds if no unit is provided.
 fn parse_duration(arg: &str) -> std::result::Result<Duration, clap::Error> {
-    let needles = regex::Regex::new(r"(?P<numeric_duration>[0-9]+)(?P<length_sigil>d|h|m|s|ms)?$")
-        .unwrap()
-        .captures(arg)
-        .ok_or_else(|| clap::Error::raw(ErrorKind::InvalidValue, "Invalid duration"))?;
+    let needles =
+        regex::Regex::new(r"(?P<numeric_duration>[0-9]+)(?P<length_sigil>w|d|h|m|s|ms)?$")
+            .unwrap()
+            .captures(arg)
+            .ok_or_else(|| clap::Error::raw(ErrorKind::InvalidValue, "Invalid duration"))?;
     let time = needles["numeric_duration"]
         .parse::<u64>()
         .map_err(|_| clap::Error::raw(ErrorKind::InvalidValue, "Invalid duration"))?;
@@ -168,6 +436,7 @@ fn parse_duration(arg: &str) -> std::result::Result<Duration, clap::Error> {
             "m" => Ok(Duration::from_secs(60 * time)),
             "h" => Ok(Duration::from_secs(60 * 60 * time)),
             "d" => Ok(Duration::from_secs(60 * 60 * 24 * time)),
+            "w" => Ok(Duration::from_secs(60 * 60 * 24 * 7 * time)),
             _ => unreachable!("Alternatives excluded by regex."),
         },
         None => Ok(Duration::from_secs(time)),
@@ -192,4 +461,85 @@ mod tests {
 
         assert!(cli.is_ok());
     }
+
+    #[test]
+    fn test_output_none_parses() {
+        let cli = Cli::try_parse_from([
+            "cardano-state-viz",
+            "analyze",
+            "--address",
+            "addr_test1...",
+            "--output",
+            "none",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Analyze {
+                output: OutputFormat::None,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_analyze_needs_address_or_address_file() {
+        let parse = |args: &[&str]| {
+            Cli::try_parse_from(["cardano-state-viz", "analyze"].iter().chain(args))
+        };
+
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["--address-file", "addresses.txt"]).is_ok());
+        assert!(parse(&["--address", "mock", "--address-file", "addresses.txt"]).is_err());
+    }
+
+    #[test]
+    fn test_analyze_range_flags_populate_query_params() {
+        let cli = Cli::try_parse_from([
+            "cardano-state-viz",
+            "analyze",
+            "--address",
+            "addr_test1...",
+            "--from-slot",
+            "1000",
+            "--to-slot",
+            "2000",
+            "--from-block",
+            "10",
+            "--to-block",
+            "20",
+            "--max-transactions",
+            "5",
+        ])
+        .unwrap();
+
+        let params = cli.command.query_params();
+        assert_eq!(params.from_slot, Some(1000));
+        assert_eq!(params.to_slot, Some(2000));
+        assert_eq!(params.from_block, Some(10));
+        assert_eq!(params.to_block, Some(20));
+        assert_eq!(params.limit, Some(5));
+
+        let cli =
+            Cli::try_parse_from(["cardano-state-viz", "analyze", "--address", "addr_test1..."])
+                .unwrap();
+        let params = cli.command.query_params();
+        assert_eq!(params.from_slot, None);
+        assert_eq!(params.to_block, None);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("250ms").unwrap(), Duration::from_millis(250));
+        assert_eq!(parse_duration("30").unwrap(), Duration::from_secs(30));
+        assert_eq!(
+            parse_duration("7d").unwrap(),
+            Duration::from_secs(7 * 86_400)
+        );
+        assert_eq!(
+            parse_duration("2w").unwrap(),
+            Duration::from_secs(14 * 86_400)
+        );
+        assert!(parse_duration("soon").is_err());
+    }
 }
@@ -5,19 +5,57 @@
 use crate::{
     Result,
     data_source::{Datum, Transaction},
+    state_machine::StateGraph,
 };
 use serde_json::json;
 
-/// Output transactions and datums as JSON
+/// Size totals over the transactions that report a size
+struct SizeSummary {
+    total: u64,
+    average: u64,
+}
+
+impl SizeSummary {
+    /// Returns `None` when no transaction has size information
+    fn from_transactions(transactions: &[Transaction]) -> Option<Self> {
+        let sizes: Vec<u64> = transactions.iter().filter_map(|tx| tx.size).collect();
+        if sizes.is_empty() {
+            return None;
+        }
+
+        let total: u64 = sizes.iter().sum();
+        Some(Self {
+            total,
+            average: total / sizes.len() as u64,
+        })
+    }
+}
+
+/// Output transactions and datums as JSON, plus the state graph when one was built
 pub fn output_json(
     w: &mut impl std::io::Write,
     transactions: &[Transaction],
     datums: &[(String, usize, Datum)],
+    graph: Option<&StateGraph>,
 ) -> Result<()> {
-    let output = json!({
+    serde_json::to_writer_pretty(&mut *w, &json_value(transactions, datums, graph))?;
+    writeln!(w)?; // Add trailing newline
+    Ok(())
+}
+
+/// JSON value written by [`output_json`]
+pub fn json_value(
+    transactions: &[Transaction],
+    datums: &[(String, usize, Datum)],
+    graph: Option<&StateGraph>,
+) -> serde_json::Value {
+    let sizes = SizeSummary::from_transactions(transactions);
+    let mut output = json!({
         "summary": {
             "total_transactions": transactions.len(),
             "total_datums": datums.len(),
+            "total_size": sizes.as_ref().map(|s| s.total),
+            "average_size": sizes.as_ref().map(|s| s.average),
         },
         "transactions": transactions.iter().map(|tx| {
             json!({
@@ -26,6 +64,13 @@ pub fn output_json(
                 "slot": tx.slot,
                 "inputs": tx.inputs.len(),
                 "outputs": tx.outputs.len(),
+                "size": tx.size,
+                "reference_scripts": tx.outputs.iter().filter_map(|o| o.script_ref.as_ref()).map(|script| {
+                    json!({
+                        "type": script.script_type,
+                        "size": script.size,
+                    })
+                }).collect::<Vec<_>>(),
             })
         }).collect::<Vec<_>>(),
         "datums": datums.iter().map(|(tx_hash, output_idx, datum)| {
@@ -37,12 +82,77 @@ pub fn output_json(
             })
         }).collect::<Vec<_>>(),
     });
+    if let Some(graph) = graph {
+        output["graph"] = graph.to_json();
+    }
+    output
+}
 
-    serde_json::to_writer_pretty(&mut *w, &output)?;
-    writeln!(w)?; // Add trailing newline
+/// Output transactions and datums as CSV: a transactions section, a blank line, then a
+/// datums section, each with its own header row
+pub fn output_csv(
+    w: &mut impl std::io::Write,
+    transactions: &[Transaction],
+    datums: &[(String, usize, Datum)],
+) -> Result<()> {
+    output_transactions_csv(w, transactions)?;
+    writeln!(w)?;
+    output_datums_csv(w, datums)
+}
+
+/// Output transactions as CSV, one row per transaction
+pub fn output_transactions_csv(
+    w: &mut impl std::io::Write,
+    transactions: &[Transaction],
+) -> Result<()> {
+    writeln!(w, "hash,block,slot,inputs,outputs")?;
+    for tx in transactions {
+        writeln!(
+            w,
+            "{},{},{},{},{}",
+            csv_field(&tx.hash),
+            tx.block,
+            tx.slot,
+            tx.inputs.len(),
+            tx.outputs.len()
+        )?;
+    }
     Ok(())
 }
 
+/// Output datums as CSV, one row per datum; unparsed datums have an empty `parsed` field
+pub fn output_datums_csv(
+    w: &mut impl std::io::Write,
+    datums: &[(String, usize, Datum)],
+) -> Result<()> {
+    writeln!(w, "transaction,output_index,hash,parsed")?;
+    for (tx_hash, output_idx, datum) in datums {
+        let parsed = datum
+            .parsed
+            .as_ref()
+            .map(|pd| pd.raw.to_human_readable())
+            .unwrap_or_default();
+        writeln!(
+            w,
+            "{},{},{},{}",
+            csv_field(tx_hash),
+            output_idx,
+            csv_field(&datum.hash),
+            csv_field(&parsed)
+        )?;
+    }
+    Ok(())
+}
+
+/// Quote a CSV field when it contains a separator, quote or line break (RFC 4180)
+fn csv_field(value: &str) -> std::borrow::Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\"")).into()
+    } else {
+        value.into()
+    }
+}
+
 /// Output transactions and datums as text table
 pub fn output_table(
     w: &mut impl std::io::Write,
@@ -56,6 +166,10 @@ pub fn output_table(
     writeln!(w, "Summary:")?;
     writeln!(w, "  Total Transactions: {}", transactions.len())?;
     writeln!(w, "  Total Datums:       {}", datums.len())?;
+    if let Some(sizes) = SizeSummary::from_transactions(transactions) {
+        writeln!(w, "  Total Size:         {} bytes", sizes.total)?;
+        writeln!(w, "  Average Size:       {} bytes", sizes.average)?;
+    }
     writeln!(w)?;
 
     if !transactions.is_empty() {
@@ -63,8 +177,8 @@ pub fn output_table(
         writeln!(w, "{:-<80}", "")?;
         writeln!(
             w,
-            "{:<16} {:>8} {:>10} {:>8} {:>8}",
-            "TX Hash", "Block", "Slot", "Inputs", "Outputs"
+            "{:<16} {:>8} {:>10} {:>8} {:>8} {:>8}",
+            "TX Hash", "Block", "Slot", "Inputs", "Outputs", "Size"
         )?;
         writeln!(w, "{:-<80}", "")?;
 
@@ -77,12 +191,15 @@ pub fn output_table(
 
             writeln!(
                 w,
-                "{:<16} {:>8} {:>10} {:>8} {:>8}",
+                "{:<16} {:>8} {:>10} {:>8} {:>8} {:>8}",
                 hash_short,
                 tx.block,
                 tx.slot,
                 tx.inputs.len(),
-                tx.outputs.len()
+                tx.outputs.len(),
+                tx.size
+                    .map(|size| size.to_string())
+                    .unwrap_or_else(|| "-".to_string())
             )?;
         }
         writeln!(w)?;
@@ -122,6 +239,119 @@ pub fn output_table(
     Ok(())
 }
 
+/// Output a single transaction with its datums and redeemers as JSON
+pub fn output_transaction_json(w: &mut impl std::io::Write, tx: &Transaction) -> Result<()> {
+    let output = json!({
+        "hash": tx.hash,
+        "block": tx.block,
+        "slot": tx.slot,
+        "valid": tx.valid,
+        "size": tx.size,
+        "metadata": tx.metadata.as_ref().map(|metadata| &metadata.json),
+        "inputs": tx.inputs.iter().map(|input| {
+            json!({
+                "utxo": input.utxo_ref.to_string(),
+                "address": input.address,
+            })
+        }).collect::<Vec<_>>(),
+        "outputs": tx.outputs.iter().enumerate().map(|(idx, output)| {
+            json!({
+                "index": idx,
+                "address": output.address,
+                "amount": output.amount.iter().map(|asset| {
+                    json!({ "unit": asset.unit, "quantity": asset.quantity })
+                }).collect::<Vec<_>>(),
+                "datum": output.datum.as_ref().map(|datum| {
+                    json!({
+                        "hash": datum.hash,
+                        "parsed": datum.parsed.as_ref().map(|pd| pd.raw.to_human_readable()),
+                        "fields": datum.parsed.as_ref().map(|pd| &pd.fields),
+                    })
+                }),
+            })
+        }).collect::<Vec<_>>(),
+        "redeemers": tx.witnesses.redeemers.iter().map(|redeemer| {
+            json!({
+                "tag": redeemer.tag,
+                "index": redeemer.index,
+                "name": redeemer.parsed.as_ref().and_then(|pr| pr.name.as_ref()),
+                "parsed": redeemer.parsed.as_ref().map(|pr| pr.raw.to_human_readable()),
+                "ex_units": { "mem": redeemer.ex_units.mem, "steps": redeemer.ex_units.steps },
+            })
+        }).collect::<Vec<_>>(),
+    });
+
+    serde_json::to_writer_pretty(&mut *w, &output)?;
+    writeln!(w)?;
+    Ok(())
+}
+
+/// Output a single transaction with its datums and redeemers as text
+pub fn output_transaction_table(w: &mut impl std::io::Write, tx: &Transaction) -> Result<()> {
+    writeln!(w, "Transaction {}", tx.hash)?;
+    writeln!(w, "{}", "=".repeat(80))?;
+    writeln!(w, "  Block: {}", tx.block)?;
+    writeln!(w, "  Slot:  {}", tx.slot)?;
+    if !tx.valid {
+        writeln!(w, "  Status: failed script validation")?;
+    }
+    if let Some(size) = tx.size {
+        writeln!(w, "  Size:  {} bytes", size)?;
+    }
+    writeln!(w)?;
+
+    writeln!(w, "Inputs:")?;
+    for input in &tx.inputs {
+        writeln!(
+            w,
+            "  {} {}",
+            input.utxo_ref,
+            input.address.as_deref().unwrap_or("-")
+        )?;
+    }
+    writeln!(w)?;
+
+    writeln!(w, "Outputs:")?;
+    for (idx, output) in tx.outputs.iter().enumerate() {
+        writeln!(w, "  #{} {}", idx, output.address)?;
+        if let Some(datum) = &output.datum {
+            let parsed = datum
+                .parsed
+                .as_ref()
+                .map(|pd| pd.raw.to_human_readable())
+                .unwrap_or_else(|| "Not parsed".to_string());
+            writeln!(w, "     Datum {}: {}", datum.hash, parsed)?;
+        }
+    }
+    writeln!(w)?;
+
+    if !tx.witnesses.redeemers.is_empty() {
+        writeln!(w, "Redeemers:")?;
+        for redeemer in &tx.witnesses.redeemers {
+            let parsed = redeemer
+                .parsed
+                .as_ref()
+                .map(|pr| match &pr.name {
+                    Some(name) => format!("{} {}", name, pr.raw.to_human_readable()),
+                    None => pr.raw.to_human_readable(),
+                })
+                .unwrap_or_else(|| "Not parsed".to_string());
+            writeln!(
+                w,
+                "  {:?}[{}] {} (mem {}, steps {})",
+                redeemer.tag,
+                redeemer.index,
+                parsed,
+                redeemer.ex_units.mem,
+                redeemer.ex_units.steps
+            )?;
+        }
+        writeln!(w)?;
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -146,6 +376,8 @@ mod tests {
             }],
             witnesses: Witnesses::default(),
             metadata: None,
+            size: None,
+            valid: true,
         }
     }
 
@@ -154,10 +386,12 @@ mod tests {
             hash: "datum_hash_123".to_string(),
             raw_cbor: vec![0x18, 0x2a],
             parsed: None,
+            reencoded: false,
         };
         datum.parsed = Some(crate::parser::ParsedDatum {
             raw: PlutusData::Integer(42),
             fields: std::collections::HashMap::new(),
+            schema_mismatch: None,
         });
         datum
     }
@@ -169,10 +403,105 @@ mod tests {
         let datums = vec![("tx_test_123".to_string(), 0, datum)];
 
         let mut output = Vec::new();
-        let result = output_json(&mut output, &[tx], &datums);
+        let result = output_json(&mut output, &[tx], &datums, None);
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_output_json_sizes() {
+        let mut tx1 = create_test_transaction();
+        tx1.size = Some(300);
+        let mut tx2 = create_test_transaction();
+        tx2.size = Some(500);
+        let tx3 = create_test_transaction();
+
+        let mut output = Vec::new();
+        output_json(&mut output, &[tx1, tx2, tx3], &[], None).unwrap();
+
+        let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(value["summary"]["total_size"], 800);
+        assert_eq!(value["summary"]["average_size"], 400);
+        assert_eq!(value["transactions"][0]["size"], 300);
+        assert!(value["transactions"][2]["size"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_output_json_graph() {
+        use crate::data_source::{
+            DataSource, QueryParams,
+            mock::{MOCK_SCRIPT_ADDRESS, MockDataSource},
+        };
+
+        let transactions = MockDataSource::new()
+            .get_transactions_by_address(MOCK_SCRIPT_ADDRESS, QueryParams::default())
+            .await
+            .unwrap();
+        let graph =
+            StateGraph::build_from_transactions(&transactions, MOCK_SCRIPT_ADDRESS, None).unwrap();
+
+        let mut output = Vec::new();
+        output_json(&mut output, &transactions, &[], Some(&graph)).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+        let state_id = |n: u8| format!("{:064x}#{}", n, if n == 1 { 0 } else { 1 });
+        let nodes: Vec<&str> = value["graph"]["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|node| node["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(nodes, [state_id(1), state_id(2), state_id(3), state_id(4)]);
+        assert_eq!(value["graph"]["nodes"][0]["classification"], "Initial");
+        assert_eq!(value["graph"]["nodes"][0]["ada_value"], 10_000_000_000u64);
+
+        let edges: Vec<(&str, &str)> = value["graph"]["edges"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|edge| (edge["from"].as_str().unwrap(), edge["to"].as_str().unwrap()))
+            .collect();
+        assert_eq!(
+            edges,
+            [
+                (state_id(1).as_str(), state_id(2).as_str()),
+                (state_id(2).as_str(), state_id(3).as_str()),
+                (state_id(3).as_str(), state_id(4).as_str()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_output_csv() {
+        let tx = create_test_transaction();
+        let mut datum = create_test_datum();
+        datum.parsed.as_mut().unwrap().raw = PlutusData::List(vec![
+            PlutusData::Integer(1),
+            PlutusData::Bytes(b"a\"b".to_vec()),
+        ]);
+        let parsed = datum.parsed.as_ref().unwrap().raw.to_human_readable();
+        assert!(parsed.contains(','));
+        let datums = vec![("tx_test_123".to_string(), 0, datum)];
+
+        let mut output = Vec::new();
+        output_csv(&mut output, &[tx], &datums).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines[0], "hash,block,slot,inputs,outputs");
+        assert_eq!(lines[1], "tx_test_123,100,1000,1,1");
+        assert_eq!(lines[2], "");
+        assert_eq!(lines[3], "transaction,output_index,hash,parsed");
+        assert_eq!(
+            lines[4],
+            format!(
+                "tx_test_123,0,datum_hash_123,\"{}\"",
+                parsed.replace('"', "\"\"")
+            )
+        );
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,\"b\""), "\"a,\"\"b\"\"\"");
+    }
+
     #[test]
     fn test_output_table() {
         let tx = create_test_transaction();
@@ -6,9 +6,15 @@
 //! - Configuration files (TOML)
 //! - Defaults
 
-use crate::error::{Error, Result};
+use crate::Network;
+use crate::cli::DataSourceType;
+use crate::data_source::blockfrost::project_network;
+use crate::error::{Error, Result, describe_toml_error};
+use crate::parser::schema::{ContractSchema, SchemaParser};
+use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::fmt;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 /// Top-level configuration
@@ -28,16 +34,28 @@ pub struct Config {
 
     #[serde(default)]
     pub logging: LoggingConfig,
+
+    /// Contract schema embedded as a `[schema]` table, used when `--schema` is absent
+    #[serde(default)]
+    pub schema: Option<ContractSchema>,
+
+    /// File this configuration was loaded from (`None` when using defaults)
+    #[serde(skip)]
+    pub loaded_from: Option<PathBuf>,
+
+    /// Blockfrost API key passed on the command line, which outranks every other layer
+    #[serde(skip)]
+    pub cli_api_key: Option<String>,
 }
 
-/// Default settings
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Default settings; `None` means the config file leaves the setting to the command
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct DefaultConfig {
-    #[serde(default = "default_source")]
-    pub source: String,
+    #[serde(default)]
+    pub source: Option<String>,
 
-    #[serde(default = "default_network")]
-    pub network: String,
+    #[serde(default)]
+    pub network: Option<String>,
 }
 
 /// Blockfrost API configuration
@@ -46,6 +64,10 @@ pub struct BlockfrostConfig {
     /// Blockfrost API key
     pub api_key: Option<String>,
 
+    /// API base URL, for self-hosted Blockfrost or a proxy (default: picked from the key's network)
+    #[serde(default)]
+    pub base_url: Option<String>,
+
     /// Enable caching
     #[serde(default)]
     pub cache_enabled: bool,
@@ -61,6 +83,14 @@ pub struct BlockfrostConfig {
     /// Delay between retries
     #[serde(default = "default_retry_delay")]
     pub retry_delay_ms: Duration,
+
+    /// Maximum number of transactions fetched concurrently
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+
+    /// How long a single request may take before it is retried
+    #[serde(default = "default_request_timeout")]
+    pub request_timeout: Duration,
 }
 
 /// Cardano node configuration
@@ -86,6 +116,11 @@ pub struct CacheConfig {
     /// Cache TTL
     #[serde(default = "default_cache_ttl")]
     pub ttl: Duration,
+
+    /// Size limit for the cache directory in bytes; the oldest entries are
+    /// removed once it is exceeded
+    #[serde(default)]
+    pub max_bytes: Option<u64>,
 }
 
 /// Logging configuration
@@ -101,14 +136,6 @@ pub struct LoggingConfig {
 
 // Default value functions
 
-fn default_source() -> String {
-    "mock".to_string()
-}
-
-fn default_network() -> String {
-    "preprod".to_string()
-}
-
 fn default_cache_ttl() -> Duration {
     Duration::from_secs(3600)
 }
@@ -121,29 +148,31 @@ fn default_retry_delay() -> Duration {
     Duration::from_millis(1000)
 }
 
+fn default_concurrency() -> usize {
+    3
+}
+
+fn default_request_timeout() -> Duration {
+    Duration::from_secs(30)
+}
+
 fn default_log_level() -> String {
     "info".to_string()
 }
 
 // Default implementations
 
-impl Default for DefaultConfig {
-    fn default() -> Self {
-        Self {
-            source: default_source(),
-            network: default_network(),
-        }
-    }
-}
-
 impl Default for BlockfrostConfig {
     fn default() -> Self {
         Self {
             api_key: None,
+            base_url: None,
             cache_enabled: false,
             cache_ttl: default_cache_ttl(),
             max_retries: default_max_retries(),
             retry_delay_ms: default_retry_delay(),
+            concurrency: default_concurrency(),
+            request_timeout: default_request_timeout(),
         }
     }
 }
@@ -154,6 +183,7 @@ impl Default for CacheConfig {
             enabled: false,
             directory: None,
             ttl: default_cache_ttl(),
+            max_bytes: None,
         }
     }
 }
@@ -174,10 +204,18 @@ impl Config {
         let contents = std::fs::read_to_string(&path)
             .map_err(|e| Error::Config(format!("Failed to read config file {:?}: {}", path, e)))?;
 
-        let config: Config = toml::from_str(&contents)
-            .map_err(|e| Error::Config(format!("Failed to parse config file {:?}: {}", path, e)))?;
+        let config: Config = toml::from_str(&contents).map_err(|e| {
+            Error::Config(format!(
+                "Failed to parse config file {:?} at {}",
+                path,
+                describe_toml_error(&contents, &e)
+            ))
+        })?;
 
-        Ok(config)
+        Ok(Config {
+            loaded_from: Some(path),
+            ..config
+        })
     }
 
     /// Load configuration from default locations
@@ -206,31 +244,179 @@ impl Config {
         Ok(Config::default())
     }
 
-    /// Get Blockfrost API key from config or environment
+    /// Set the Blockfrost API key given on the command line
+    pub fn with_cli_api_key(mut self, api_key: Option<String>) -> Self {
+        self.cli_api_key = api_key;
+        self
+    }
+
+    /// Get Blockfrost API key from the CLI flag, environment or config file
+    ///
+    /// Precedence: `--api-key` > `BLOCKFROST_API_KEY` > `[blockfrost] api_key`.
     pub fn blockfrost_api_key(&self) -> Result<String> {
-        if let Some(key) = &self.blockfrost.api_key {
-            return Ok(key.clone());
+        self.blockfrost_api_key_with_source()
+            .map(|(key, _)| key)
+            .ok_or_else(|| Error::MissingConfig(
+                "Blockfrost API key not found. Pass --api-key, set BLOCKFROST_API_KEY environment variable or configure in config file".to_string()
+            ))
+    }
+
+    /// Get Blockfrost API key together with the layer that supplied it
+    pub fn blockfrost_api_key_with_source(&self) -> Option<(String, SettingSource)> {
+        self.resolve_api_key(env_var("BLOCKFROST_API_KEY"))
+    }
+
+    fn resolve_api_key(&self, env: Option<String>) -> Option<(String, SettingSource)> {
+        first_set([
+            (self.cli_api_key.clone(), SettingSource::CliFlag),
+            (env, SettingSource::Env),
+            (self.blockfrost.api_key.clone(), SettingSource::ConfigFile),
+        ])
+    }
+
+    /// Get the Blockfrost base URL, if the public endpoint is overridden
+    ///
+    /// Precedence: `BLOCKFROST_BASE_URL` > `[blockfrost] base_url`.
+    pub fn blockfrost_base_url(&self) -> Option<String> {
+        self.blockfrost_base_url_with_source().map(|(url, _)| url)
+    }
+
+    /// Get the Blockfrost base URL together with the layer that supplied it
+    pub fn blockfrost_base_url_with_source(&self) -> Option<(String, SettingSource)> {
+        self.resolve_base_url(env_var("BLOCKFROST_BASE_URL"))
+    }
+
+    fn resolve_base_url(&self, env: Option<String>) -> Option<(String, SettingSource)> {
+        first_set([
+            (env, SettingSource::Env),
+            (self.blockfrost.base_url.clone(), SettingSource::ConfigFile),
+        ])
+    }
+
+    /// Resolve the data source: CLI flag, then config file, then the command's default
+    pub fn resolve_source(
+        &self,
+        flag: Option<DataSourceType>,
+        command_default: DataSourceType,
+    ) -> Result<(DataSourceType, SettingSource)> {
+        if let Some(source) = flag {
+            return Ok((source, SettingSource::CliFlag));
         }
 
-        std::env::var("BLOCKFROST_API_KEY")
-            .map_err(|_| Error::MissingConfig(
-                "Blockfrost API key not found. Set BLOCKFROST_API_KEY environment variable or configure in config file".to_string()
-            ))
+        if let Some(name) = &self.default.source {
+            let source = DataSourceType::from_str(name, true)
+                .map_err(|_| Error::Config(format!("Unknown data source '{}'", name)))?;
+            return Ok((source, SettingSource::ConfigFile));
+        }
+
+        Ok((command_default, SettingSource::Default))
     }
 
-    /// Get node socket path from config or environment
-    pub fn node_socket_path(&self) -> Result<PathBuf> {
-        if let Some(path) = &self.node.socket_path {
-            return Ok(path.clone());
+    /// Resolve the contract schema: the `--schema` file, then the config's `[schema]`
+    /// table; `None` means datums are parsed generically
+    pub fn resolve_schema(&self, flag: Option<&Path>) -> Result<Option<SchemaParser>> {
+        let schema = match flag {
+            Some(path) => {
+                tracing::info!("Loading schema from {:?}", path);
+                ContractSchema::from_file(path)?
+            }
+            None => match &self.schema {
+                Some(schema) => {
+                    tracing::info!("Using the schema from the config file");
+                    schema.clone()
+                }
+                None => return Ok(None),
+            },
+        };
+        Ok(Some(SchemaParser::new(schema)))
+    }
+
+    /// Resolve the network: CLI flag, then config file, then the network the
+    /// Blockfrost API key is scoped to (with the Blockfrost source), then default
+    pub fn resolve_network(
+        &self,
+        flag: Option<&str>,
+        source: DataSourceType,
+    ) -> Result<(Network, SettingSource)> {
+        if let Some(network) = flag {
+            return Ok((network.parse()?, SettingSource::CliFlag));
+        }
+
+        if let Some(network) = &self.default.network {
+            return Ok((network.parse()?, SettingSource::ConfigFile));
+        }
+
+        if source == DataSourceType::Blockfrost
+            && let Some(network) = self
+                .blockfrost_api_key()
+                .ok()
+                .and_then(|key| project_network(&key))
+        {
+            return Ok((network, SettingSource::ApiKey));
+        }
+
+        Ok((Network::default(), SettingSource::Default))
+    }
+
+    /// Describe the effective settings and the layer that supplied each
+    pub fn report(
+        &self,
+        source: (DataSourceType, SettingSource),
+        network: (Network, SettingSource),
+    ) -> ConfigReport {
+        let source_name = source
+            .0
+            .to_possible_value()
+            .map(|v| v.get_name().to_string())
+            .unwrap_or_default();
+        let (api_key, api_key_source) = match self.blockfrost_api_key_with_source() {
+            Some((_, layer)) => ("set".to_string(), layer),
+            None => ("not set".to_string(), SettingSource::Default),
+        };
+        let (base_url, base_url_source) = match self.blockfrost_base_url_with_source() {
+            Some((url, layer)) => (url, layer),
+            None => ("public endpoint".to_string(), SettingSource::Default),
+        };
+        let cache_source = if self.cache.directory.is_some() {
+            SettingSource::ConfigFile
+        } else {
+            SettingSource::Default
+        };
+
+        ConfigReport {
+            config_file: self.loaded_from.clone(),
+            settings: vec![
+                ResolvedSetting::new("source", source_name, source.1),
+                ResolvedSetting::new("network", network.0.to_string(), network.1),
+                ResolvedSetting::new("blockfrost api key", api_key, api_key_source),
+                ResolvedSetting::new("blockfrost base url", base_url, base_url_source),
+                ResolvedSetting::new(
+                    "cache directory",
+                    self.cache_directory().display().to_string(),
+                    cache_source,
+                ),
+            ],
         }
+    }
 
-        std::env::var("CARDANO_NODE_SOCKET_PATH")
-            .map(PathBuf::from)
-            .map_err(|_| Error::MissingConfig(
+    /// Get node socket path from the environment or config file
+    ///
+    /// Precedence: `CARDANO_NODE_SOCKET_PATH` > `[node] socket_path`.
+    pub fn node_socket_path(&self) -> Result<PathBuf> {
+        self.resolve_node_socket_path(env_var("CARDANO_NODE_SOCKET_PATH").map(PathBuf::from))
+            .map(|(path, _)| path)
+            .ok_or_else(|| Error::MissingConfig(
                 "Node socket path not found. Set CARDANO_NODE_SOCKET_PATH environment variable or configure in config file".to_string()
             ))
     }
 
+    fn resolve_node_socket_path(&self, env: Option<PathBuf>) -> Option<(PathBuf, SettingSource)> {
+        first_set([
+            (env, SettingSource::Env),
+            (self.node.socket_path.clone(), SettingSource::ConfigFile),
+        ])
+    }
+
     /// Get cache directory with fallback to default
     pub fn cache_directory(&self) -> PathBuf {
         self.cache.directory.clone().unwrap_or_else(|| {
@@ -241,6 +427,86 @@ impl Config {
     }
 }
 
+/// Read an environment variable, treating an empty value as unset
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+/// Pick the first layer that has a value, in precedence order
+fn first_set<T, const N: usize>(
+    layers: [(Option<T>, SettingSource); N],
+) -> Option<(T, SettingSource)> {
+    layers
+        .into_iter()
+        .find_map(|(value, source)| value.map(|value| (value, source)))
+}
+
+/// Configuration layer that supplied an effective setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingSource {
+    CliFlag,
+    Env,
+    ConfigFile,
+    /// Inferred from the network prefix of the Blockfrost API key
+    ApiKey,
+    Default,
+}
+
+impl fmt::Display for SettingSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            SettingSource::CliFlag => "CLI flag",
+            SettingSource::Env => "environment",
+            SettingSource::ConfigFile => "config file",
+            SettingSource::ApiKey => "Blockfrost API key",
+            SettingSource::Default => "default",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// An effective setting and the layer it came from
+#[derive(Debug, Clone)]
+pub struct ResolvedSetting {
+    pub name: &'static str,
+    pub value: String,
+    pub source: SettingSource,
+}
+
+impl ResolvedSetting {
+    fn new(name: &'static str, value: String, source: SettingSource) -> Self {
+        Self {
+            name,
+            value,
+            source,
+        }
+    }
+}
+
+/// Report of the loaded config file and where each effective setting came from
+#[derive(Debug, Clone)]
+pub struct ConfigReport {
+    pub config_file: Option<PathBuf>,
+    pub settings: Vec<ResolvedSetting>,
+}
+
+impl fmt::Display for ConfigReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.config_file {
+            Some(path) => writeln!(f, "Config file: {}", path.display())?,
+            None => writeln!(f, "Config file: none (using defaults)")?,
+        }
+        for setting in &self.settings {
+            writeln!(
+                f,
+                "  {:<20} {:<40} ({})",
+                setting.name, setting.value, setting.source
+            )?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -248,8 +514,8 @@ mod tests {
     #[test]
     fn test_default_config() {
         let config = Config::default();
-        assert_eq!(config.default.source, "mock");
-        assert_eq!(config.default.network, "preprod");
+        assert_eq!(config.default.source, None);
+        assert_eq!(config.default.network, None);
         assert_eq!(config.logging.level, "info");
     }
 
@@ -269,8 +535,217 @@ level = "debug"
         "#;
 
         let config: Config = toml::from_str(toml).unwrap();
-        assert_eq!(config.default.source, "blockfrost");
+        assert_eq!(config.default.source.as_deref(), Some("blockfrost"));
         assert_eq!(config.blockfrost.api_key, Some("test_key".to_string()));
         assert_eq!(config.logging.level, "debug");
     }
+
+    #[test]
+    fn test_inline_schema() {
+        let toml = r#"
+[schema.contract]
+name = "Vesting"
+script_address = "addr_test1"
+
+[schema.datum]
+type = "constr"
+constructor_index = 0
+fields = [
+    { name = "beneficiary", type = "pubkeyhash" },
+    { name = "unlock_time", type = "posix_time" },
+]
+
+[[schema.redeemer]]
+name = "Unlock"
+constructor_index = 0
+
+[schema.transitions.Unlock]
+label = "Release"
+        "#;
+
+        let config: Config = toml::from_str(toml).unwrap();
+        let parser = config.resolve_schema(None).unwrap().unwrap();
+        assert_eq!(parser.schema.contract.name, "Vesting");
+        assert_eq!(parser.schema.redeemer[0].name, "Unlock");
+        assert_eq!(
+            parser.label_transition("Unlock"),
+            Some("Release".to_string())
+        );
+
+        // --schema wins over the embedded table
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("schema.toml");
+        let mut file_schema = config.schema.clone().unwrap();
+        file_schema.contract.name = "From file".to_string();
+        std::fs::write(&path, toml::to_string(&file_schema).unwrap()).unwrap();
+        let parser = config.resolve_schema(Some(&path));
+        assert_eq!(parser.unwrap().unwrap().schema.contract.name, "From file");
+
+        assert!(Config::default().resolve_schema(None).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_from_file_reports_location() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("broken-config.toml");
+        std::fs::write(&path, "[default]\nsource = \"mock\"\nnetwork = preprod\n").unwrap();
+
+        let err = Config::from_file(&path).unwrap_err();
+
+        let message = err.to_string();
+        assert!(message.contains(&*path.to_string_lossy()), "{}", message);
+        assert!(message.contains("line 3"), "{}", message);
+    }
+
+    #[test]
+    fn test_resolve_source_and_network_layers() {
+        let mut config = Config::default();
+        assert_eq!(
+            config
+                .resolve_source(None, DataSourceType::Blockfrost)
+                .unwrap(),
+            (DataSourceType::Blockfrost, SettingSource::Default)
+        );
+        assert_eq!(
+            config
+                .resolve_network(Some("mainnet"), DataSourceType::Mock)
+                .unwrap(),
+            (Network::Mainnet, SettingSource::CliFlag)
+        );
+
+        config.loaded_from = Some(PathBuf::from("config.toml"));
+        config.default.source = Some("blockfrost".to_string());
+        config.default.network = Some("preview".to_string());
+        assert_eq!(
+            config.resolve_source(None, DataSourceType::Mock).unwrap(),
+            (DataSourceType::Blockfrost, SettingSource::ConfigFile)
+        );
+        assert_eq!(
+            config
+                .resolve_source(Some(DataSourceType::Mock), DataSourceType::Mock)
+                .unwrap(),
+            (DataSourceType::Mock, SettingSource::CliFlag)
+        );
+        assert_eq!(
+            config.resolve_network(None, DataSourceType::Mock).unwrap(),
+            (Network::Preview, SettingSource::ConfigFile)
+        );
+
+        let report = config.report(
+            (DataSourceType::Mock, SettingSource::CliFlag),
+            (Network::Preview, SettingSource::ConfigFile),
+        );
+        let text = report.to_string();
+        assert!(text.contains("Config file: config.toml"), "{}", text);
+        assert!(text.contains("(CLI flag)"), "{}", text);
+    }
+
+    #[test]
+    fn test_file_setting_the_default_values() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("default-values.toml");
+        std::fs::write(
+            &path,
+            "[default]\nsource = \"mock\"\nnetwork = \"preprod\"\n",
+        )
+        .unwrap();
+        let config = Config::from_file(&path).unwrap();
+
+        // Explicitly chosen, even though they match the built-in defaults
+        assert_eq!(
+            config
+                .resolve_source(None, DataSourceType::Blockfrost)
+                .unwrap(),
+            (DataSourceType::Mock, SettingSource::ConfigFile)
+        );
+        assert_eq!(
+            config.resolve_network(None, DataSourceType::Mock).unwrap(),
+            (Network::Preprod, SettingSource::ConfigFile)
+        );
+    }
+
+    #[test]
+    fn test_network_inferred_from_api_key() {
+        let config = Config::default().with_cli_api_key(Some("mainnetAbC123".to_string()));
+        assert_eq!(
+            config
+                .resolve_network(None, DataSourceType::Blockfrost)
+                .unwrap(),
+            (Network::Mainnet, SettingSource::ApiKey)
+        );
+        // Other sources don't use the key
+        assert_eq!(
+            config.resolve_network(None, DataSourceType::Mock).unwrap(),
+            (Network::Preprod, SettingSource::Default)
+        );
+        // An explicit choice wins, and is then checked against the key
+        assert_eq!(
+            config
+                .resolve_network(Some("preprod"), DataSourceType::Blockfrost)
+                .unwrap(),
+            (Network::Preprod, SettingSource::CliFlag)
+        );
+
+        let config = Config::default().with_cli_api_key(Some("selfhosted".to_string()));
+        assert_eq!(
+            config
+                .resolve_network(None, DataSourceType::Blockfrost)
+                .unwrap(),
+            (Network::Preprod, SettingSource::Default)
+        );
+    }
+
+    #[test]
+    fn test_api_key_precedence() {
+        let mut config = Config::default();
+        config.blockfrost.api_key = Some("from_file".to_string());
+        assert_eq!(
+            config.resolve_api_key(None),
+            Some(("from_file".to_string(), SettingSource::ConfigFile))
+        );
+        assert_eq!(
+            config.resolve_api_key(Some("from_env".to_string())),
+            Some(("from_env".to_string(), SettingSource::Env))
+        );
+
+        let config = config.with_cli_api_key(Some("from_flag".to_string()));
+        assert_eq!(
+            config.resolve_api_key(Some("from_env".to_string())),
+            Some(("from_flag".to_string(), SettingSource::CliFlag))
+        );
+        assert_eq!(Config::default().resolve_api_key(None), None);
+    }
+
+    #[test]
+    fn test_base_url_precedence() {
+        let mut config = Config::default();
+        assert_eq!(config.resolve_base_url(None), None);
+
+        config.blockfrost.base_url = Some("http://file:3000".to_string());
+        assert_eq!(
+            config.resolve_base_url(None),
+            Some(("http://file:3000".to_string(), SettingSource::ConfigFile))
+        );
+        assert_eq!(
+            config.resolve_base_url(Some("http://env:3000".to_string())),
+            Some(("http://env:3000".to_string(), SettingSource::Env))
+        );
+    }
+
+    #[test]
+    fn test_node_socket_precedence() {
+        let mut config = Config::default();
+        config.node.socket_path = Some(PathBuf::from("/file/node.socket"));
+        assert_eq!(
+            config.resolve_node_socket_path(None),
+            Some((
+                PathBuf::from("/file/node.socket"),
+                SettingSource::ConfigFile
+            ))
+        );
+        assert_eq!(
+            config.resolve_node_socket_path(Some(PathBuf::from("/env/node.socket"))),
+            Some((PathBuf::from("/env/node.socket"), SettingSource::Env))
+        );
+    }
 }
@@ -7,45 +7,143 @@
 //! detailed mapping from blockfrost-openapi types to our internal types.
 
 use super::{
-    Asset, DataSource, Datum, ExUnits, QueryParams, Redeemer, RedeemerTag, Script, Transaction,
-    TxInput, TxOutput, UtxoRef, Witnesses,
+    Asset, DataSource, Datum, ExUnits, Metadata, QueryParams, Redeemer, RedeemerTag, Script,
+    Transaction, TxInput, TxOutput, UtxoRef, Witnesses, blake2b_256,
 };
 use crate::data_source::cache::DataSourceCache;
-use crate::{Error, Result};
+use crate::parser::cbor::{PlutusData, encode_plutus_data};
+use crate::parser::{GenericParser, Parser};
+use crate::{Error, Network, Result};
 use async_trait::async_trait;
-use blockfrost::{BlockFrostSettings, BlockfrostAPI, Order, Pagination};
-use blockfrost_openapi::models::AddressTransactionsContentInner;
+use blockfrost::{BlockFrostSettings, BlockfrostAPI, BlockfrostError, Order, Pagination};
+use blockfrost_openapi::models::Script as BlockfrostScript;
+use blockfrost_openapi::models::script::Type as ScriptType;
+use blockfrost_openapi::models::tx_content_redeemers_inner::Purpose;
+use blockfrost_openapi::models::{
+    AddressTransactionsContentInner, TxContentMetadataInner, TxContentMetadataInnerJsonMetadata,
+    TxContentRedeemersInner,
+};
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::time::sleep;
+use tokio::time::{sleep, timeout};
+
+/// Whether a failed request may succeed if sent again
+///
+/// Rate limiting (429) and server errors (5xx) are transient, as are transport
+/// failures that never got a response. Other statuses (bad request, unauthorized,
+/// not found, ...) and malformed responses would fail the same way on every attempt.
+fn is_retryable(err: &BlockfrostError) -> bool {
+    let retryable_status = |status: u16| status == 429 || (500..600).contains(&status);
+    match err {
+        BlockfrostError::Response { reason, .. } => retryable_status(reason.status_code),
+        BlockfrostError::Reqwest { reason, .. } => reason
+            .status()
+            .is_none_or(|status| retryable_status(status.as_u16())),
+        BlockfrostError::Io(_) => true,
+        BlockfrostError::Json { .. } | BlockfrostError::Parsing { .. } => false,
+    }
+}
 
-/// Calculate Blake2b-256 hash of data
-fn blake2b_256(data: &[u8]) -> String {
-    use blake2::digest::consts::U32;
-    use blake2::{Blake2b, Digest};
+/// Whether Blockfrost rejected a request for exceeding the project's rate limit
+fn is_rate_limited(err: &BlockfrostError) -> bool {
+    match err {
+        BlockfrostError::Response { reason, .. } => reason.status_code == 429,
+        BlockfrostError::Reqwest { reason, .. } => {
+            reason.status().is_some_and(|status| status.as_u16() == 429)
+        }
+        _ => false,
+    }
+}
+
+/// Error for a request that still failed after every retry
+///
+/// Running out of retries on a rate limit is reported as
+/// [`Error::RateLimitExceeded`], which tells the user how to avoid it.
+fn retries_exhausted(attempts: u32, context: &str, last_error: Option<BlockfrostError>) -> Error {
+    match last_error {
+        Some(err) if is_rate_limited(&err) => {
+            tracing::warn!(
+                "{} still rate limited by Blockfrost after {} attempts",
+                context,
+                attempts
+            );
+            Error::RateLimitExceeded
+        }
+        err => Error::Blockfrost(format!(
+            "{} failed after {} attempts: {:?}",
+            context, attempts, err
+        )),
+    }
+}
 
-    let mut hasher = Blake2b::<U32>::new();
-    hasher.update(data);
-    hex::encode(hasher.finalize())
+/// Error for a request whose last attempt got no response within `request_timeout`
+fn requests_timed_out(attempts: u32, context: &str, request_timeout: Duration) -> Error {
+    Error::Blockfrost(format!(
+        "{} timed out after {} attempts of {:?} each",
+        context, attempts, request_timeout
+    ))
+}
+
+/// Map a transaction's metadata entries to a JSON object keyed by label
+///
+/// Returns `None` for transactions without metadata.
+fn metadata_from_blockfrost(entries: &[TxContentMetadataInner]) -> Option<Metadata> {
+    if entries.is_empty() {
+        return None;
+    }
+    let json = entries
+        .iter()
+        .map(|entry| {
+            let value = match entry.json_metadata.as_ref() {
+                TxContentMetadataInnerJsonMetadata::String(text) => {
+                    serde_json::Value::String(text.clone())
+                }
+                TxContentMetadataInnerJsonMetadata::Object(fields) => serde_json::Value::Object(
+                    fields.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+                ),
+            };
+            (entry.label.clone(), value)
+        })
+        .collect();
+    Some(Metadata {
+        json: serde_json::Value::Object(json),
+    })
 }
 
 /// Blockfrost API client with rate limiting and retry logic
 #[derive(Debug, Clone)] // Derived Clone to easily pass to tasks if needed, though client is cloneable
 pub struct BlockfrostDataSource {
     client: BlockfrostAPI,
+    /// Custom API endpoint, `None` for the public one matching the key's network
+    base_url: Option<String>,
     max_retries: u32,
     retry_delay: Duration,
+    /// How long a single attempt at a request may take before it is retried
+    request_timeout: Duration,
     cache: Option<Arc<DataSourceCache>>,
+    /// Maximum number of full transactions fetched at once
+    concurrency: usize,
 }
 
 impl BlockfrostDataSource {
     /// Create a new Blockfrost data source
     pub fn new(api_key: String) -> Result<Self> {
+        Self::with_base_url(api_key, None)
+    }
+
+    /// Create a Blockfrost data source talking to `base_url` (e.g. a self-hosted
+    /// instance or a proxy) instead of the public endpoint
+    pub fn with_base_url(api_key: String, base_url: Option<String>) -> Result<Self> {
+        let mut settings = BlockFrostSettings::new();
+        settings.base_url = base_url.clone();
         Ok(Self {
-            client: BlockfrostAPI::new(&api_key, BlockFrostSettings::new()),
+            client: BlockfrostAPI::new(&api_key, settings),
+            base_url,
             max_retries: 3,
             retry_delay: Duration::from_secs(10),
+            request_timeout: Duration::from_secs(30),
             cache: None,
+            concurrency: 3,
         })
     }
 
@@ -61,12 +159,44 @@ impl BlockfrostDataSource {
         self
     }
 
+    /// Set how long a single request may take before it is abandoned and retried
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Set how many transactions are fetched concurrently (at least one)
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Maximum number of transactions fetched concurrently
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// Semaphore bounding concurrent full-transaction fetches
+    fn fetch_semaphore(&self) -> Arc<tokio::sync::Semaphore> {
+        Arc::new(tokio::sync::Semaphore::new(self.concurrency))
+    }
+
     /// Set cache
     pub fn with_cache(mut self, cache: DataSourceCache) -> Self {
         self.cache = Some(Arc::new(cache));
         self
     }
 
+    /// Custom API endpoint, if the public one is overridden
+    pub fn base_url(&self) -> Option<&str> {
+        self.base_url.as_deref()
+    }
+
+    /// Whether responses are cached
+    pub fn has_cache(&self) -> bool {
+        self.cache.is_some()
+    }
+
     /// Execute a Blockfrost API call with retry logic
     async fn execute_with_retry<F, T, Fut>(&self, operation: F) -> Result<T>
     where
@@ -74,6 +204,7 @@ impl BlockfrostDataSource {
         Fut: Future<Output = blockfrost::error::BlockfrostResult<T>>,
     {
         let mut last_error = None;
+        let mut timed_out = false;
 
         for attempt in 0..=self.max_retries {
             if attempt > 0 {
@@ -82,32 +213,43 @@ impl BlockfrostDataSource {
                 sleep(delay).await;
             }
 
-            match operation().await {
-                Ok(result) => return Ok(result),
-                Err(e) => {
+            match timeout(self.request_timeout, operation()).await {
+                Ok(Ok(result)) => return Ok(result),
+                Ok(Err(e)) => {
+                    tracing::warn!("Blockfrost API error (attempt {}): {:?}", attempt + 1, e);
+                    if !is_retryable(&e) {
+                        return Err(Error::Blockfrost(format!(
+                            "Request failed after {} attempts: {:?}",
+                            attempt + 1,
+                            e
+                        )));
+                    }
                     last_error = Some(e);
+                    timed_out = false;
+                }
+                Err(_) => {
                     tracing::warn!(
-                        "Blockfrost API error (attempt {}): {:?}",
-                        attempt + 1,
-                        last_error
+                        "Blockfrost request timed out after {:?} (attempt {})",
+                        self.request_timeout,
+                        attempt + 1
                     );
-
-                    // Don't retry on certain errors
-                    if let Some(ref err) = last_error {
-                        let err_str = format!("{:?}", err);
-                        if err_str.contains("404") || err_str.contains("Invalid") {
-                            break; // Don't retry on not found or invalid requests
-                        }
-                    }
+                    timed_out = true;
                 }
             }
         }
 
-        Err(Error::Blockfrost(format!(
-            "Failed after {} attempts: {:?}",
+        if timed_out {
+            return Err(requests_timed_out(
+                self.max_retries + 1,
+                "Request",
+                self.request_timeout,
+            ));
+        }
+        Err(retries_exhausted(
             self.max_retries + 1,
-            last_error
-        )))
+            "Request",
+            last_error,
+        ))
     }
 
     /// Fetch a single transaction with all details (static helper for concurrency)
@@ -116,6 +258,7 @@ impl BlockfrostDataSource {
         tx_hash: String,
         max_retries: u32,
         retry_delay: Duration,
+        request_timeout: Duration,
         cache: Option<Arc<DataSourceCache>>,
     ) -> Result<Transaction> {
         // Check cache first
@@ -138,6 +281,7 @@ impl BlockfrostDataSource {
             },
             max_retries,
             retry_delay,
+            request_timeout,
             "transaction_by_hash",
         );
 
@@ -151,6 +295,7 @@ impl BlockfrostDataSource {
             },
             max_retries,
             retry_delay,
+            request_timeout,
             "transactions_utxos",
         );
 
@@ -164,11 +309,30 @@ impl BlockfrostDataSource {
             },
             max_retries,
             retry_delay,
+            request_timeout,
             "transactions_redeemers",
         );
 
-        let (tx_result, utxos_result, redeemers_result) =
-            tokio::join!(details_future, utxos_future, redeemers_future);
+        let c4 = client.clone();
+        let h4 = tx_hash.clone();
+        let metadata_future = Self::fetch_with_retry(
+            move || {
+                let c = c4.clone();
+                let h = h4.clone();
+                async move { c.transactions_metadata(&h).await }
+            },
+            max_retries,
+            retry_delay,
+            request_timeout,
+            "transactions_metadata",
+        );
+
+        let (tx_result, utxos_result, redeemers_result, metadata_result) = tokio::join!(
+            details_future,
+            utxos_future,
+            redeemers_future,
+            metadata_future
+        );
 
         let tx = tx_result?;
         let utxos = utxos_result?;
@@ -176,6 +340,10 @@ impl BlockfrostDataSource {
             tracing::warn!("Failed to fetch redeemers for {}: {}", tx_hash, e);
             vec![]
         });
+        let metadata = metadata_result.unwrap_or_else(|e| {
+            tracing::warn!("Failed to fetch metadata for {}: {}", tx_hash, e);
+            vec![]
+        });
 
         // Map inputs
         let inputs = utxos
@@ -200,7 +368,7 @@ impl BlockfrostDataSource {
             .collect();
 
         // Map outputs
-        let outputs = utxos
+        let mut outputs: Vec<TxOutput> = utxos
             .outputs
             .iter()
             .map(|output| {
@@ -213,51 +381,68 @@ impl BlockfrostDataSource {
                     })
                     .collect();
 
-                let datum = if let Some(ref inline_datum) = output.inline_datum {
-                    Some(Self::parse_datum_from_hex_static(inline_datum))
-                } else {
-                    output.data_hash.as_ref().map(|data_hash| Datum {
-                        hash: data_hash.clone(),
-                        raw_cbor: vec![],
-                        parsed: None,
-                    })
-                };
+                let datum = Self::datum_from_output(&output.inline_datum, &output.data_hash);
 
                 TxOutput {
                     address: output.address.clone(),
                     amount,
                     datum,
-                    script_ref: output.reference_script_hash.as_ref().map(|_hash| Script {
-                        script_type: "Unknown".to_string(),
-                        bytes: vec![],
-                    }),
+                    script_ref: None,
                 }
             })
             .collect();
 
-        let mapped_redeemers: Vec<Redeemer> = redeemers
-            .iter()
-            .map(|r| {
-                let tag = match format!("{:?}", r.purpose).to_lowercase().as_str() {
-                    "spend" => RedeemerTag::Spend,
-                    "mint" => RedeemerTag::Mint,
-                    "cert" => RedeemerTag::Cert,
-                    "reward" => RedeemerTag::Reward,
-                    _ => RedeemerTag::Spend,
-                };
+        // Resolve hash-referenced datums and reference scripts
+        for datum in outputs.iter_mut().filter_map(|o| o.datum.as_mut()) {
+            Self::hydrate_datum(
+                &client,
+                datum,
+                max_retries,
+                retry_delay,
+                request_timeout,
+                cache.as_deref(),
+            )
+            .await;
+        }
+        for (output, raw) in outputs.iter_mut().zip(&utxos.outputs) {
+            if let Some(ref script_hash) = raw.reference_script_hash {
+                output.script_ref = Some(
+                    Self::fetch_reference_script(
+                        &client,
+                        script_hash,
+                        max_retries,
+                        retry_delay,
+                        request_timeout,
+                        cache.as_deref(),
+                    )
+                    .await,
+                );
+            }
+        }
 
-                Redeemer {
-                    tag,
-                    index: r.tx_index as u64,
-                    raw_cbor: vec![],
-                    parsed: None,
-                    ex_units: ExUnits {
-                        mem: r.unit_mem.parse().unwrap_or(0),
-                        steps: r.unit_steps.parse().unwrap_or(0),
-                    },
-                }
-            })
-            .collect();
+        // The redeemer listing only carries the hash of the redeemer data, which
+        // resolves like a datum
+        let mut mapped_redeemers = Vec::with_capacity(redeemers.len());
+        for r in &redeemers {
+            let raw_cbor = Self::resolve_datum_by_hash(
+                &client,
+                &r.redeemer_data_hash,
+                max_retries,
+                retry_delay,
+                request_timeout,
+                cache.as_deref(),
+            )
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!(
+                    "Failed to resolve redeemer data {}: {}",
+                    r.redeemer_data_hash,
+                    e
+                );
+                vec![]
+            });
+            mapped_redeemers.push(redeemer_from_blockfrost(r, raw_cbor));
+        }
 
         let transaction = Transaction {
             hash: tx.hash.clone(),
@@ -269,7 +454,9 @@ impl BlockfrostDataSource {
                 redeemers: mapped_redeemers,
                 ..Default::default()
             },
-            metadata: None,
+            metadata: metadata_from_blockfrost(&metadata),
+            size: Some(tx.size as u64),
+            valid: tx.valid_contract,
         };
 
         // Save to cache
@@ -285,6 +472,7 @@ impl BlockfrostDataSource {
         operation: F,
         max_retries: u32,
         retry_delay: Duration,
+        request_timeout: Duration,
         context: &str,
     ) -> Result<T>
     where
@@ -292,27 +480,271 @@ impl BlockfrostDataSource {
         Fut: Future<Output = blockfrost::error::BlockfrostResult<T>>,
     {
         let mut last_error = None;
+        let mut timed_out = false;
         for attempt in 0..=max_retries {
             if attempt > 0 {
                 let delay = retry_delay + Duration::from_millis(rand::random::<u64>() % 1000);
                 sleep(delay).await;
             }
-            match operation().await {
-                Ok(result) => return Ok(result),
-                Err(e) => {
-                    let err_str = format!("{:?}", e);
-                    if err_str.contains("404") || err_str.contains("Invalid") {
+            match timeout(request_timeout, operation()).await {
+                Ok(Ok(result)) => return Ok(result),
+                Ok(Err(e)) => {
+                    if !is_retryable(&e) {
                         return Err(Error::Blockfrost(format!("{} failed: {:?}", context, e)));
                     }
                     last_error = Some(e);
+                    timed_out = false;
+                }
+                Err(_) => {
+                    tracing::debug!("{} timed out (attempt {})", context, attempt + 1);
+                    timed_out = true;
                 }
             }
         }
-        Err(Error::Blockfrost(format!(
-            "{} failed after retries: {:?}",
-            context, last_error
-        )))
+        if timed_out {
+            return Err(requests_timed_out(
+                max_retries + 1,
+                context,
+                request_timeout,
+            ));
+        }
+        Err(retries_exhausted(max_retries + 1, context, last_error))
+    }
+
+    /// Build the datum of an output, which is either inline or referenced by hash
+    ///
+    /// Hash-referenced datums come back with empty CBOR and must be resolved
+    /// with [`Self::hydrate_datum`].
+    fn datum_from_output(
+        inline_datum: &Option<String>,
+        data_hash: &Option<String>,
+    ) -> Option<Datum> {
+        if let Some(inline_datum) = inline_datum {
+            Some(Self::parse_datum_from_hex_static(inline_datum))
+        } else {
+            data_hash.as_ref().map(|data_hash| Datum {
+                hash: data_hash.clone(),
+                raw_cbor: vec![],
+                parsed: None,
+                reencoded: false,
+            })
+        }
     }
+
+    /// Fetch full transactions concurrently, keeping the order of `tx_hashes`
+    async fn fetch_full_transactions(&self, tx_hashes: Vec<String>) -> Result<Vec<Transaction>> {
+        // Limit concurrency to avoid hitting rate limits too hard: each transaction
+        // fans out into several requests of its own (details, UTXOs, redeemers and
+        // metadata, then any datums and reference scripts), so the number in flight
+        // is a multiple of this bound
+        let semaphore = self.fetch_semaphore();
+        let mut tasks = Vec::new();
+
+        for tx_hash in tx_hashes {
+            let permit = semaphore.clone().acquire_owned().await.unwrap();
+            let client_clone = self.client.clone();
+
+            let max_retries = self.max_retries;
+            let retry_delay = self.retry_delay;
+            let request_timeout = self.request_timeout;
+            let cache_clone = self.cache.clone();
+
+            tasks.push(tokio::spawn(async move {
+                let _permit = permit; // Hold permit
+                Self::fetch_full_transaction(
+                    client_clone,
+                    tx_hash,
+                    max_retries,
+                    retry_delay,
+                    request_timeout,
+                    cache_clone,
+                )
+                .await
+            }));
+        }
+
+        let mut transactions = Vec::new();
+        for task in tasks {
+            match task.await {
+                Ok(Ok(tx)) => transactions.push(tx),
+                // Skipping would silently drop every remaining transaction
+                Ok(Err(Error::RateLimitExceeded)) => return Err(Error::RateLimitExceeded),
+                Ok(Err(e)) => tracing::warn!("Failed to fetch transaction: {}", e),
+                Err(e) => tracing::warn!("Task join error: {}", e),
+            }
+        }
+        Ok(transactions)
+    }
+
+    /// Fill in the CBOR of a hash-referenced datum and parse it
+    ///
+    /// Resolution failures are logged and leave the datum with its hash only.
+    async fn hydrate_datum(
+        client: &BlockfrostAPI,
+        datum: &mut Datum,
+        max_retries: u32,
+        retry_delay: Duration,
+        request_timeout: Duration,
+        cache: Option<&DataSourceCache>,
+    ) {
+        if datum.raw_cbor.is_empty() && datum.hash != "invalid" {
+            match Self::resolve_datum_by_hash(
+                client,
+                &datum.hash,
+                max_retries,
+                retry_delay,
+                request_timeout,
+                cache,
+            )
+            .await
+            {
+                Ok(raw_cbor) => {
+                    datum.raw_cbor = raw_cbor;
+                    datum.reencoded = true;
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to resolve datum {}: {}", datum.hash, e);
+                    return;
+                }
+            }
+        }
+
+        if datum.parsed.is_none() && !datum.raw_cbor.is_empty() {
+            datum.parsed = GenericParser.parse_datum(&datum.raw_cbor).ok();
+        }
+    }
+
+    /// Fetch the CBOR of a datum by its hash (static helper, cached by hash)
+    ///
+    /// Blockfrost's CBOR endpoint is not reachable through the client crate, so
+    /// the detailed-schema JSON is fetched and re-encoded. The result is
+    /// semantically equal to the original datum but may differ byte-wise.
+    async fn resolve_datum_by_hash(
+        client: &BlockfrostAPI,
+        datum_hash: &str,
+        max_retries: u32,
+        retry_delay: Duration,
+        request_timeout: Duration,
+        cache: Option<&DataSourceCache>,
+    ) -> Result<Vec<u8>> {
+        let cache_key = format!("datum_{}", datum_hash);
+        if let Some(c) = cache
+            && let Some(content) = c.get_text(&cache_key).await
+            && let Ok(bytes) = hex::decode(content.trim())
+        {
+            return Ok(bytes);
+        }
+
+        tracing::debug!("Resolving datum {} from Blockfrost", datum_hash);
+
+        let response = Self::fetch_with_retry(
+            || {
+                let c = client.clone();
+                let h = datum_hash.to_string();
+                async move { c.scripts_datum_hash(&h).await }
+            },
+            max_retries,
+            retry_delay,
+            request_timeout,
+            "scripts_datum_hash",
+        )
+        .await?;
+
+        let json_value = response.get("json_value").ok_or_else(|| {
+            Error::Blockfrost(format!("Missing json_value for datum {}", datum_hash))
+        })?;
+        let plutus_data = PlutusData::from_detailed_json(json_value)?;
+        let raw_cbor = encode_plutus_data(&plutus_data)?;
+
+        if let Some(c) = cache {
+            c.save_text(&cache_key, &hex::encode(&raw_cbor)).await;
+        }
+
+        Ok(raw_cbor)
+    }
+
+    /// Look up a reference script's language, CBOR and size by hash (static helper, cached by hash)
+    ///
+    /// Lookup failures are logged and yield a script of unknown type and size.
+    async fn fetch_reference_script(
+        client: &BlockfrostAPI,
+        script_hash: &str,
+        max_retries: u32,
+        retry_delay: Duration,
+        request_timeout: Duration,
+        cache: Option<&DataSourceCache>,
+    ) -> Script {
+        let cache_key = format!("script_{}", script_hash);
+        if let Some(c) = cache
+            && let Some(content) = c.get_text(&cache_key).await
+            && let Ok(script) = serde_json::from_str::<Script>(&content)
+        {
+            return script;
+        }
+
+        let result = Self::fetch_with_retry(
+            || {
+                let c = client.clone();
+                let h = script_hash.to_string();
+                async move { c.scripts_by_id(&h).await }
+            },
+            max_retries,
+            retry_delay,
+            request_timeout,
+            "scripts_by_id",
+        )
+        .await;
+
+        let info = match result {
+            Ok(info) => info,
+            Err(e) => {
+                tracing::warn!("Failed to fetch reference script {}: {}", script_hash, e);
+                return Script {
+                    script_type: "Unknown".to_string(),
+                    bytes: vec![],
+                    size: None,
+                };
+            }
+        };
+
+        // `scripts_hash_cbor` deserializes into the wrong model and drops the CBOR, but
+        // `scripts_datum_hash_cbor` requests the same `/scripts/{hash}/cbor` path as raw JSON
+        let cbor = Self::fetch_with_retry(
+            || {
+                let c = client.clone();
+                let h = script_hash.to_string();
+                async move { c.scripts_datum_hash_cbor(&h).await }
+            },
+            max_retries,
+            retry_delay,
+            request_timeout,
+            "scripts_cbor",
+        )
+        .await;
+        let cbor = match cbor {
+            Ok(response) => response
+                .get("cbor")
+                .and_then(|cbor| cbor.as_str())
+                .map(str::to_string),
+            Err(e) => {
+                tracing::warn!("Failed to fetch CBOR of script {}: {}", script_hash, e);
+                None
+            }
+        };
+
+        let script = script_from_blockfrost(&info, cbor.as_deref());
+
+        // Only cache complete lookups so a missing CBOR is retried next time
+        if let Some(c) = cache
+            && !script.bytes.is_empty()
+            && let Ok(json) = serde_json::to_string(&script)
+        {
+            c.save_text(&cache_key, &json).await;
+        }
+
+        script
+    }
+
     /// Static helper to parse datum from hex
     fn parse_datum_from_hex_static(hex: &str) -> Datum {
         match hex::decode(hex) {
@@ -320,6 +752,7 @@ impl BlockfrostDataSource {
                 hash: blake2b_256(&bytes),
                 raw_cbor: bytes,
                 parsed: None,
+                reencoded: false,
             },
             Err(e) => {
                 tracing::warn!("Failed to decode datum hex: {}", e);
@@ -327,13 +760,94 @@ impl BlockfrostDataSource {
                     hash: "invalid".to_string(),
                     raw_cbor: vec![],
                     parsed: None,
+                    reencoded: false,
                 }
             }
         }
     }
+}
+
+/// Network a Blockfrost project id is scoped to, from the network name it starts with
+///
+/// `None` for ids without a known prefix, e.g. for self-hosted instances.
+pub fn project_network(project_id: &str) -> Option<Network> {
+    [Network::Mainnet, Network::Preprod, Network::Preview]
+        .into_iter()
+        .find(|candidate| project_id.starts_with(candidate.name()))
+}
+
+/// Check that a Blockfrost project id is scoped to `network`
+///
+/// Ids without a known prefix are accepted. When no network is chosen it is inferred
+/// from the key (see [`Config::resolve_network`]), so this only fails for an explicit
+/// choice that conflicts with the key.
+///
+/// [`Config::resolve_network`]: crate::config::Config::resolve_network
+pub fn check_project_network(project_id: &str, network: Network) -> Result<()> {
+    match project_network(project_id) {
+        Some(key_network) if key_network != network => Err(Error::Config(format!(
+            "Blockfrost API key is for {}, but the selected network is {}",
+            key_network, network
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Map a Blockfrost redeemer purpose to a redeemer tag
+///
+/// The client only knows the pre-Conway purposes, so Blockfrost never yields
+/// [`RedeemerTag::Voting`] or [`RedeemerTag::Proposing`].
+fn redeemer_tag(purpose: Purpose) -> RedeemerTag {
+    match purpose {
+        Purpose::Spend => RedeemerTag::Spend,
+        Purpose::Mint => RedeemerTag::Mint,
+        Purpose::Cert => RedeemerTag::Cert,
+        Purpose::Reward => RedeemerTag::Reward,
+    }
+}
+
+/// Map a Blockfrost redeemer and its resolved data to our model
+///
+/// Redeemers whose data could not be resolved keep empty CBOR and stay unparsed.
+fn redeemer_from_blockfrost(redeemer: &TxContentRedeemersInner, raw_cbor: Vec<u8>) -> Redeemer {
+    let parsed = if raw_cbor.is_empty() {
+        None
+    } else {
+        GenericParser.parse_redeemer(&raw_cbor).ok()
+    };
 
-    fn parse_datum_from_hex(&self, hex: &str) -> Datum {
-        Self::parse_datum_from_hex_static(hex)
+    Redeemer {
+        tag: redeemer_tag(redeemer.purpose),
+        index: redeemer.tx_index as u64,
+        raw_cbor,
+        parsed,
+        ex_units: ExUnits {
+            mem: redeemer.unit_mem.parse().unwrap_or(0),
+            steps: redeemer.unit_steps.parse().unwrap_or(0),
+        },
+    }
+}
+
+/// Map a Blockfrost script payload and its hex-encoded CBOR to our model
+fn script_from_blockfrost(info: &BlockfrostScript, cbor: Option<&str>) -> Script {
+    let script_type = match info.r#type {
+        ScriptType::Timelock => "NativeScript",
+        ScriptType::PlutusV1 => "PlutusV1",
+        ScriptType::PlutusV2 => "PlutusV2",
+        ScriptType::PlutusV3 => "PlutusV3",
+    };
+    let bytes = cbor
+        .and_then(|cbor| hex::decode(cbor).ok())
+        .unwrap_or_default();
+    let size = info
+        .serialised_size
+        .map(|size| size as u64)
+        .or_else(|| (!bytes.is_empty()).then_some(bytes.len() as u64));
+
+    Script {
+        script_type: script_type.to_string(),
+        bytes,
+        size,
     }
 }
 
@@ -345,6 +859,7 @@ impl DataSource for BlockfrostDataSource {
             tx_hash.to_string(),
             self.max_retries,
             self.retry_delay,
+            self.request_timeout,
             self.cache.clone(),
         )
         .await
@@ -370,6 +885,7 @@ impl DataSource for BlockfrostDataSource {
             Order::Asc
         };
 
+        let has_slot_bounds = params.from_slot.is_some() || params.to_slot.is_some();
         let mut tx_refs: Vec<AddressTransactionsContentInner> = Vec::new();
 
         loop {
@@ -446,10 +962,17 @@ impl DataSource for BlockfrostDataSource {
             };
 
             let count = page_refs.len();
-            tx_refs.extend(page_refs);
+            // Block bounds are known from the reference, so skip full fetches outside them
+            tx_refs.extend(page_refs.into_iter().filter(|r| {
+                let block = r.block_height as u64;
+                params.from_block.is_none_or(|from| block >= from)
+                    && params.to_block.is_none_or(|to| block <= to)
+            }));
 
-            // Check if we reached the limit
+            // Check if we reached the limit. References carry no slot, so with slot
+            // bounds the limit can only be applied to the fetched transactions.
             if let Some(limit) = params.limit
+                && !has_slot_bounds
                 && tx_refs.len() >= limit
             {
                 tx_refs.truncate(limit);
@@ -464,81 +987,69 @@ impl DataSource for BlockfrostDataSource {
         }
         tracing::info!("Found {} transaction references for address", tx_refs.len());
 
-        // Fetch full transaction data concurrently
-        // Limit concurrency to avoid hitting rate limits too hard
-        // (3 top-level requests * 3 internal requests each = 9 concurrent reqs)
-        let semaphore = Arc::new(tokio::sync::Semaphore::new(3));
-        let mut tasks = Vec::new();
-
-        for tx_ref in tx_refs {
-            let permit = semaphore.clone().acquire_owned().await.unwrap();
-            let client_clone = self.client.clone();
-            let tx_hash = tx_ref.tx_hash.clone();
-
-            let max_retries = self.max_retries;
-            let retry_delay = self.retry_delay;
-            let cache_clone = self.cache.clone();
-
-            tasks.push(tokio::spawn(async move {
-                let _permit = permit; // Hold permit
-                Self::fetch_full_transaction(
-                    client_clone,
-                    tx_hash,
-                    max_retries,
-                    retry_delay,
-                    cache_clone,
-                )
-                .await
-            }));
-        }
-
+        // Fetch in batches of what is still missing, since slot bounds may filter
+        // out part of each batch
+        let mut tx_hashes = tx_refs.into_iter().map(|r| r.tx_hash);
         let mut transactions = Vec::new();
-        for task in tasks {
-            match task.await {
-                Ok(Ok(tx)) => {
-                    // Apply filters
-                    let passes_filter = if let Some(from_slot) = params.from_slot {
-                        tx.slot >= from_slot
-                    } else {
-                        true
-                    } && if let Some(to_slot) = params.to_slot {
-                        tx.slot <= to_slot
-                    } else {
-                        true
-                    } && if let Some(from_block) = params.from_block {
-                        tx.block >= from_block
-                    } else {
-                        true
-                    } && if let Some(to_block) = params.to_block {
-                        tx.block <= to_block
-                    } else {
-                        true
-                    };
-
-                    if passes_filter {
-                        transactions.push(tx);
-                    }
-                }
-                Ok(Err(e)) => tracing::warn!("Failed to fetch transaction: {}", e),
-                Err(e) => tracing::warn!("Task join error: {}", e),
+        loop {
+            let wanted = params
+                .limit
+                .map_or(usize::MAX, |limit| limit.saturating_sub(transactions.len()));
+            let batch: Vec<String> = tx_hashes.by_ref().take(wanted).collect();
+            if batch.is_empty() {
+                break;
             }
+            let fetched = self.fetch_full_transactions(batch).await?;
+            transactions.extend(fetched.into_iter().filter(|tx| params.matches(tx)));
         }
 
         tracing::info!("Successfully fetched {} transactions", transactions.len());
         Ok(transactions)
     }
 
-    async fn get_script_utxos(&self, address: &str) -> Result<Vec<(UtxoRef, TxOutput)>> {
+    async fn get_script_utxos(
+        &self,
+        address: &str,
+        params: QueryParams,
+    ) -> Result<Vec<(UtxoRef, TxOutput)>> {
         tracing::debug!("Fetching UTXOs for address {} from Blockfrost", address);
 
-        let pagination = Pagination::new(Order::Asc, 1, 100);
+        let page_size = params.page_size.unwrap_or(100).min(100) as usize;
+        let mut page = params.page.unwrap_or(1) as usize;
+        let fetch_all = params.page.is_none();
+        let order = if params.order.as_deref() == Some("desc") {
+            Order::Desc
+        } else {
+            Order::Asc
+        };
+
+        // Keep fetching pages until one comes back short, unless a page was requested
+        let mut utxos = Vec::new();
+        loop {
+            let pagination = Pagination::new(order, page, page_size);
+            let page_utxos = self
+                .execute_with_retry(|| async {
+                    self.client.addresses_utxos(address, pagination).await
+                })
+                .await?;
+            let count = page_utxos.len();
+            utxos.extend(page_utxos);
 
-        let utxos = self
-            .execute_with_retry(|| async { self.client.addresses_utxos(address, pagination).await })
-            .await?;
+            if let Some(limit) = params.limit
+                && utxos.len() >= limit
+            {
+                utxos.truncate(limit);
+                break;
+            }
+            if !fetch_all || count < page_size {
+                break;
+            }
+            page += 1;
+        }
+        tracing::info!("Found {} UTXOs at {}", utxos.len(), address);
 
         // Map Blockfrost UTXOs to our format
-        let result = utxos
+        let mut result: Vec<(UtxoRef, TxOutput)> = utxos
             .iter()
             .map(|utxo| {
                 let utxo_ref = UtxoRef::new(utxo.tx_hash.clone(), utxo.output_index as u64);
@@ -552,31 +1063,351 @@ impl DataSource for BlockfrostDataSource {
                     })
                     .collect();
 
-                // Handle datum
-                let datum = if let Some(ref inline_datum) = utxo.inline_datum {
-                    Some(self.parse_datum_from_hex(inline_datum))
-                } else {
-                    utxo.data_hash.as_ref().map(|data_hash| Datum {
-                        hash: data_hash.clone(),
-                        raw_cbor: vec![],
-                        parsed: None,
-                    })
-                };
+                let datum = Self::datum_from_output(&utxo.inline_datum, &utxo.data_hash);
 
                 let output = TxOutput {
                     address: address.to_string(),
                     amount,
                     datum,
-                    script_ref: utxo.reference_script_hash.as_ref().map(|_hash| Script {
-                        script_type: "Unknown".to_string(),
-                        bytes: vec![],
-                    }),
+                    script_ref: None,
                 };
 
                 (utxo_ref, output)
             })
             .collect();
 
+        // Resolve hash-referenced datums so the UTXO set carries full datums
+        for datum in result.iter_mut().filter_map(|(_, o)| o.datum.as_mut()) {
+            Self::hydrate_datum(
+                &self.client,
+                datum,
+                self.max_retries,
+                self.retry_delay,
+                self.request_timeout,
+                self.cache.as_deref(),
+            )
+            .await;
+        }
+        for ((_, output), raw) in result.iter_mut().zip(&utxos) {
+            if let Some(ref script_hash) = raw.reference_script_hash {
+                output.script_ref = Some(
+                    Self::fetch_reference_script(
+                        &self.client,
+                        script_hash,
+                        self.max_retries,
+                        self.retry_delay,
+                        self.request_timeout,
+                        self.cache.as_deref(),
+                    )
+                    .await,
+                );
+            }
+        }
+
         Ok(result)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blockfrost::ResponseError;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn response_error(status_code: u16) -> BlockfrostError {
+        BlockfrostError::Response {
+            url: "https://example.invalid".to_string(),
+            reason: ResponseError {
+                status_code,
+                error: "error".to_string(),
+                message: "message".to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_metadata_from_blockfrost() {
+        assert!(metadata_from_blockfrost(&[]).is_none());
+
+        let message = TxContentMetadataInner::new(
+            "674".to_string(),
+            TxContentMetadataInnerJsonMetadata::Object(
+                [("msg".to_string(), serde_json::json!(["Vesting unlock"]))].into(),
+            ),
+        );
+        let note = TxContentMetadataInner::new(
+            "1".to_string(),
+            TxContentMetadataInnerJsonMetadata::String("plain".to_string()),
+        );
+
+        let metadata = metadata_from_blockfrost(&[message, note]).unwrap();
+        assert_eq!(
+            metadata.json,
+            serde_json::json!({
+                "674": { "msg": ["Vesting unlock"] },
+                "1": "plain",
+            })
+        );
+        assert_eq!(metadata.labels(), vec!["1", "674"]);
+    }
+
+    #[test]
+    fn test_redeemer_from_blockfrost() {
+        let payload = TxContentRedeemersInner::new(
+            1,
+            Purpose::Mint,
+            "ab".repeat(28),
+            "cd".repeat(32),
+            "cd".repeat(32),
+            "1700".to_string(),
+            "476468".to_string(),
+            "172033".to_string(),
+        );
+        // Constr 1 []
+        let raw_cbor = vec![0xd8, 0x7a, 0x80];
+
+        let redeemer = redeemer_from_blockfrost(&payload, raw_cbor.clone());
+        assert!(matches!(redeemer.tag, RedeemerTag::Mint));
+        assert_eq!(redeemer.index, 1);
+        assert_eq!(redeemer.raw_cbor, raw_cbor);
+        assert_eq!(
+            redeemer.parsed.unwrap().raw,
+            PlutusData::Constr {
+                tag: 1,
+                fields: vec![]
+            }
+        );
+        assert_eq!(
+            redeemer.ex_units,
+            ExUnits {
+                mem: 1700,
+                steps: 476468
+            }
+        );
+
+        let unresolved = redeemer_from_blockfrost(&payload, vec![]);
+        assert!(unresolved.raw_cbor.is_empty());
+        assert!(unresolved.parsed.is_none());
+    }
+
+    #[test]
+    fn test_redeemer_tag_for_each_purpose() {
+        for (purpose, tag) in [
+            (Purpose::Spend, RedeemerTag::Spend),
+            (Purpose::Mint, RedeemerTag::Mint),
+            (Purpose::Cert, RedeemerTag::Cert),
+            (Purpose::Reward, RedeemerTag::Reward),
+        ] {
+            let payload = TxContentRedeemersInner {
+                purpose,
+                ..Default::default()
+            };
+            assert_eq!(redeemer_from_blockfrost(&payload, vec![]).tag, tag);
+        }
+    }
+
+    #[test]
+    fn test_check_project_network() {
+        assert!(check_project_network("mainnetAbC123", Network::Mainnet).is_ok());
+        assert!(check_project_network("preprodAbC123", Network::Preprod).is_ok());
+        assert!(check_project_network("previewAbC123", Network::Preview).is_ok());
+        // Keys without a network prefix are not checked
+        assert!(check_project_network("selfhosted", Network::Mainnet).is_ok());
+
+        for (key, network) in [
+            ("mainnetAbC123", Network::Preprod),
+            ("preprodAbC123", Network::Mainnet),
+            ("previewAbC123", Network::Preprod),
+        ] {
+            assert!(
+                matches!(check_project_network(key, network), Err(Error::Config(_))),
+                "{} on {}",
+                key,
+                network
+            );
+        }
+    }
+
+    #[test]
+    fn test_is_retryable_by_status() {
+        assert!(is_retryable(&response_error(429)));
+        assert!(is_retryable(&response_error(500)));
+        assert!(is_retryable(&response_error(503)));
+        for status in [400, 402, 403, 404, 418] {
+            assert!(!is_retryable(&response_error(status)), "{}", status);
+        }
+        assert!(!is_retryable(&BlockfrostError::Parsing {
+            message: "Invalid hash".to_string()
+        }));
+        assert!(is_retryable(&BlockfrostError::Io(std::io::Error::from(
+            std::io::ErrorKind::ConnectionReset
+        ))));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_with_retry_stops_on_client_errors() {
+        async fn attempts(status_code: u16) -> usize {
+            let calls = AtomicUsize::new(0);
+            let result: Result<()> = BlockfrostDataSource::fetch_with_retry(
+                || {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    async move { Err(response_error(status_code)) }
+                },
+                1,
+                Duration::ZERO,
+                Duration::from_secs(30),
+                "test",
+            )
+            .await;
+            assert!(result.is_err());
+            calls.load(Ordering::SeqCst)
+        }
+
+        assert_eq!(attempts(403).await, 1);
+        assert_eq!(attempts(404).await, 1);
+        assert_eq!(attempts(429).await, 2);
+        assert_eq!(attempts(502).await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_with_retry_reports_rate_limit() {
+        async fn fetch(status_code: u16) -> Result<()> {
+            BlockfrostDataSource::fetch_with_retry(
+                || async move { Err(response_error(status_code)) },
+                1,
+                Duration::ZERO,
+                Duration::from_secs(30),
+                "test",
+            )
+            .await
+        }
+
+        assert!(matches!(fetch(429).await, Err(Error::RateLimitExceeded)));
+        assert!(matches!(fetch(503).await, Err(Error::Blockfrost(_))));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_with_retry_times_out_and_retries() {
+        let calls = AtomicUsize::new(0);
+        let fetch = |max_retries| {
+            BlockfrostDataSource::fetch_with_retry(
+                || {
+                    // Only the first attempt hangs
+                    let hangs = calls.fetch_add(1, Ordering::SeqCst) == 0;
+                    async move {
+                        if hangs {
+                            sleep(Duration::from_secs(60)).await;
+                        }
+                        Ok(42)
+                    }
+                },
+                max_retries,
+                Duration::ZERO,
+                Duration::from_millis(50),
+                "test",
+            )
+        };
+
+        assert_eq!(fetch(1).await.unwrap(), 42);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        calls.store(0, Ordering::SeqCst);
+        let err = fetch(0).await.unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{}", err);
+    }
+
+    fn blockfrost_script(r#type: ScriptType, serialised_size: Option<i32>) -> BlockfrostScript {
+        BlockfrostScript {
+            script_hash: "ab".repeat(28),
+            r#type,
+            serialised_size,
+        }
+    }
+
+    #[test]
+    fn test_script_from_blockfrost_maps_type_and_cbor() {
+        let info = blockfrost_script(ScriptType::PlutusV2, Some(4));
+        let script = script_from_blockfrost(&info, Some("4e4d0100"));
+        assert_eq!(script.script_type, "PlutusV2");
+        assert_eq!(script.bytes, vec![0x4e, 0x4d, 0x01, 0x00]);
+        assert_eq!(script.size, Some(4));
+
+        let info = blockfrost_script(ScriptType::Timelock, None);
+        let script = script_from_blockfrost(&info, Some("8200581c"));
+        assert_eq!(script.script_type, "NativeScript");
+        assert_eq!(script.size, Some(4));
+    }
+
+    #[tokio::test]
+    async fn test_limit_applies_after_slot_bounds() {
+        use crate::data_source::mock::{MOCK_SCRIPT_ADDRESS, MockDataSource};
+
+        // Serve the address listing and the transactions from the cache
+        let dir = tempfile::tempdir().unwrap();
+        let cache = DataSourceCache::new(Duration::from_secs(3600), Some(dir.path().into()));
+        let transactions = MockDataSource::new()
+            .get_transactions_by_address(MOCK_SCRIPT_ADDRESS, QueryParams::default())
+            .await
+            .unwrap();
+        let refs: Vec<_> = transactions
+            .iter()
+            .map(|tx| {
+                serde_json::json!({
+                    "tx_hash": tx.hash,
+                    "tx_index": 0,
+                    "block_height": tx.block,
+                    "block_time": 0,
+                })
+            })
+            .collect();
+        let key = DataSourceCache::cache_key_for(MOCK_SCRIPT_ADDRESS, 1, 100, "asc");
+        cache
+            .save_text(&key, &serde_json::to_string(&refs).unwrap())
+            .await;
+        for tx in &transactions {
+            cache.save_transaction(tx).await;
+        }
+        let ds = BlockfrostDataSource::new("test_key".to_string())
+            .unwrap()
+            .with_cache(cache);
+
+        // The first transactions fall before the bound and must not use up the limit
+        let params = QueryParams {
+            from_slot: Some(transactions[2].slot),
+            limit: Some(2),
+            ..QueryParams::default()
+        };
+        let found = ds
+            .get_transactions_by_address(MOCK_SCRIPT_ADDRESS, params)
+            .await;
+
+        let hashes: Vec<_> = found.unwrap().into_iter().map(|tx| tx.hash).collect();
+        assert_eq!(
+            hashes,
+            [transactions[2].hash.as_str(), transactions[3].hash.as_str()]
+        );
+    }
+
+    #[test]
+    fn test_concurrency_sizes_fetch_semaphore() {
+        let ds = BlockfrostDataSource::new("test_key".to_string()).unwrap();
+        assert_eq!(ds.concurrency(), 3);
+        assert_eq!(ds.fetch_semaphore().available_permits(), 3);
+
+        let ds = ds.with_concurrency(10);
+        assert_eq!(ds.concurrency(), 10);
+        assert_eq!(ds.fetch_semaphore().available_permits(), 10);
+
+        // A semaphore without permits would never let a fetch through
+        assert_eq!(ds.with_concurrency(0).concurrency(), 1);
+    }
+
+    #[test]
+    fn test_script_from_blockfrost_without_cbor() {
+        let info = blockfrost_script(ScriptType::PlutusV3, None);
+        let script = script_from_blockfrost(&info, None);
+        assert_eq!(script.script_type, "PlutusV3");
+        assert!(script.bytes.is_empty());
+        assert_eq!(script.size, None);
+    }
+}
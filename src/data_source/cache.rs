@@ -2,17 +2,96 @@
 //!
 //! This module provides a caching mechanism for storing and retrieving
 //! transaction data and raw text responses.
+//!
+//! Entries are kept one JSON file per key, with an `index.json` recording when each was
+//! written so lookups and pruning don't have to stat every file. The index is kept in
+//! memory and written out every few saves, after pruning and when the cache is dropped.
 
 use crate::{Result, data_source::Transaction};
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::{Duration, SystemTime};
 use tokio::fs;
+use tokio::sync::Mutex;
+
+/// Saves between two opportunistic prunes; the first save always prunes
+const PRUNE_EVERY_SAVES: usize = 50;
+
+/// File in the cache directory holding the [`IndexEntry`] of every cached key
+const INDEX_FILE: &str = "index.json";
+
+/// Index changes kept in memory before [`INDEX_FILE`] is rewritten
+const INDEX_FLUSH_EVERY: usize = 32;
+
+/// When a cache entry was written and how large it is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct IndexEntry {
+    /// Seconds since the Unix epoch
+    mtime: u64,
+    size: u64,
+}
+
+impl IndexEntry {
+    fn new(modified: SystemTime, size: u64) -> Self {
+        let mtime = modified
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        Self { mtime, size }
+    }
+
+    fn modified(&self) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(self.mtime)
+    }
+}
+
+/// In-memory copy of [`INDEX_FILE`]
+#[derive(Debug)]
+struct Index {
+    path: PathBuf,
+    entries: Mutex<HashMap<String, IndexEntry>>,
+    /// Changes not yet written to `path`
+    unsaved: AtomicUsize,
+}
+
+impl Index {
+    /// Write `entries` to `path`, replacing it atomically so a crash never leaves a
+    /// truncated index behind
+    fn write(path: &Path, entries: &HashMap<String, IndexEntry>) -> std::io::Result<()> {
+        let tmp_path = path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, serde_json::to_string(entries)?)?;
+        std::fs::rename(&tmp_path, path)
+    }
+}
+
+impl Drop for Index {
+    fn drop(&mut self) {
+        if *self.unsaved.get_mut() == 0 {
+            return;
+        }
+        if let Err(e) = Self::write(&self.path, self.entries.get_mut()) {
+            tracing::warn!("Failed to write cache index: {}", e);
+        }
+    }
+}
 
 /// Data Source Cache
 #[derive(Debug, Clone)]
 pub struct DataSourceCache {
     cache_dir: PathBuf,
+    /// How long this run trusts an entry when looking it up
     ttl: Duration,
+    /// Age at which [`Self::prune`] deletes an entry; unlike `ttl` it must suit every
+    /// run sharing the directory, so a short-lived lookup TTL doesn't wipe the cache
+    max_age: Duration,
+    /// Size budget for the cache directory, enforced by [`Self::prune`]
+    max_bytes: Option<u64>,
+    saves: Arc<AtomicUsize>,
+    /// Shared by clones, and written out once the last one is dropped
+    index: Arc<Index>,
 }
 
 impl DataSourceCache {
@@ -24,7 +103,72 @@ impl DataSourceCache {
                 .join("cardano-state-viz")
         });
 
-        Self { cache_dir, ttl }
+        let entries = Self::load_index(&cache_dir);
+        Self {
+            index: Arc::new(Index {
+                path: cache_dir.join(INDEX_FILE),
+                entries: Mutex::new(entries),
+                unsaved: AtomicUsize::new(0),
+            }),
+            cache_dir,
+            ttl,
+            max_age: ttl,
+            max_bytes: None,
+            saves: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Read the index left by a previous run, rebuilding it from the cached files if
+    /// it's missing or corrupt
+    fn load_index(cache_dir: &Path) -> HashMap<String, IndexEntry> {
+        let path = cache_dir.join(INDEX_FILE);
+        if let Ok(content) = std::fs::read_to_string(&path) {
+            match serde_json::from_str(&content) {
+                Ok(index) => return index,
+                Err(e) => {
+                    tracing::warn!(
+                        "Rebuilding unreadable cache index {}: {}",
+                        path.display(),
+                        e
+                    )
+                }
+            }
+        }
+        Self::scan_dir(cache_dir)
+    }
+
+    /// Index the files in `cache_dir` from their metadata
+    fn scan_dir(cache_dir: &Path) -> HashMap<String, IndexEntry> {
+        let Ok(entries) = std::fs::read_dir(cache_dir) else {
+            return HashMap::new();
+        };
+        entries
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                if path.extension().is_none_or(|ext| ext != "json")
+                    || entry.file_name() == INDEX_FILE
+                {
+                    return None;
+                }
+                let key = path.file_stem()?.to_str()?.to_string();
+                let metadata = entry.metadata().ok()?;
+                let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                Some((key, IndexEntry::new(modified, metadata.len())))
+            })
+            .collect()
+    }
+
+    /// Prune entries older than `max_age` rather than the lookup TTL
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Limit the total size of cached files
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
     }
 
     pub fn cache_key_for(address: &str, page: usize, page_size: usize, order: &str) -> String {
@@ -37,23 +181,126 @@ impl DataSourceCache {
     }
 
     /// Check if cache is valid
-    async fn is_cache_valid(&self, path: &PathBuf) -> bool {
-        if !path.exists() {
-            return false;
-        }
-
-        match fs::metadata(path).await {
-            Ok(metadata) => match metadata.modified() {
-                Ok(modified) => {
-                    if let Ok(elapsed) = SystemTime::now().duration_since(modified) {
-                        elapsed < self.ttl
-                    } else {
-                        false
-                    }
+    ///
+    /// Consults the index; only keys missing from it (e.g. cached before the index
+    /// existed) are looked up on disk, and then added to it.
+    async fn is_cache_valid(&self, key: &str) -> bool {
+        let entry = self.index.entries.lock().await.get(key).copied();
+        let entry = match entry {
+            Some(entry) => entry,
+            None => {
+                let Ok(metadata) = fs::metadata(self.get_cache_path(key)).await else {
+                    return false;
+                };
+                let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                let entry = IndexEntry::new(modified, metadata.len());
+                self.update_index(|index| {
+                    index.insert(key.to_string(), entry);
+                })
+                .await;
+                entry
+            }
+        };
+
+        SystemTime::now()
+            .duration_since(entry.modified())
+            .is_ok_and(|elapsed| elapsed < self.ttl)
+    }
+
+    /// Apply `update` to the index, writing it to [`INDEX_FILE`] every
+    /// [`INDEX_FLUSH_EVERY`] changes
+    async fn update_index(&self, update: impl FnOnce(&mut HashMap<String, IndexEntry>)) {
+        let mut entries = self.index.entries.lock().await;
+        update(&mut entries);
+        if self.index.unsaved.fetch_add(1, Ordering::Relaxed) + 1 >= INDEX_FLUSH_EVERY {
+            self.write_index(&entries).await;
+        }
+    }
+
+    /// Write `entries`, which the caller holds locked, to [`INDEX_FILE`]
+    async fn write_index(&self, entries: &HashMap<String, IndexEntry>) {
+        if let Err(e) = self.ensure_cache_dir().await {
+            tracing::warn!("Failed to create cache directory: {}", e);
+            return;
+        }
+        match Index::write(&self.index.path, entries) {
+            Ok(()) => self.index.unsaved.store(0, Ordering::Relaxed),
+            Err(e) => tracing::warn!("Failed to write cache index: {}", e),
+        }
+    }
+
+    /// Record a freshly written entry in the index
+    async fn record_save(&self, key: &str, size: usize) {
+        let entry = IndexEntry::new(SystemTime::now(), size as u64);
+        self.update_index(|index| {
+            index.insert(key.to_string(), entry);
+        })
+        .await;
+    }
+
+    /// Drop an entry whose file couldn't be read from the index
+    async fn forget(&self, key: &str) {
+        self.update_index(|index| {
+            index.remove(key);
+        })
+        .await;
+    }
+
+    /// Delete entries older than the maximum age, then the oldest ones until the cache
+    /// fits in its size budget
+    ///
+    /// Ages and sizes come from the index, the same as for lookups. Returns the number
+    /// of entries removed.
+    pub async fn prune(&self) -> Result<usize> {
+        let mut entries = self.index.entries.lock().await;
+        let now = SystemTime::now();
+        let (expired, mut kept): (Vec<_>, Vec<_>) = entries
+            .iter()
+            .map(|(key, entry)| (entry.modified(), entry.size, key.clone()))
+            .partition(|(modified, _, _)| {
+                now.duration_since(*modified)
+                    .is_ok_and(|elapsed| elapsed >= self.max_age)
+            });
+        let mut removed_keys: Vec<String> = expired.into_iter().map(|(_, _, key)| key).collect();
+
+        if let Some(max_bytes) = self.max_bytes {
+            let mut total: u64 = kept.iter().map(|(_, size, _)| size).sum();
+            kept.sort();
+            for (_, size, key) in kept {
+                if total <= max_bytes {
+                    break;
                 }
-                Err(_) => false,
-            },
-            Err(_) => false,
+                removed_keys.push(key);
+                total -= size;
+            }
+        }
+
+        if removed_keys.is_empty() {
+            return Ok(0);
+        }
+        for key in &removed_keys {
+            match fs::remove_file(self.get_cache_path(key)).await {
+                Ok(()) => {}
+                // Already gone, which is what pruning is after
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+            entries.remove(key);
+            self.index.unsaved.fetch_add(1, Ordering::Relaxed);
+        }
+        tracing::debug!("Pruned {} cache entries", removed_keys.len());
+        self.write_index(&entries).await;
+        Ok(removed_keys.len())
+    }
+
+    /// Prune on the first save and every [`PRUNE_EVERY_SAVES`] saves after it
+    async fn prune_after_save(&self) {
+        let saves = self.saves.fetch_add(1, Ordering::Relaxed);
+        if !saves.is_multiple_of(PRUNE_EVERY_SAVES) {
+            return;
+        }
+        if let Err(e) = self.prune().await {
+            tracing::warn!("Failed to prune cache: {}", e);
         }
     }
 
@@ -72,7 +319,7 @@ impl DataSourceCache {
         let key = format!("tx_{}", tx_hash);
         let path = self.get_cache_path(&key);
 
-        if self.is_cache_valid(&path).await {
+        if self.is_cache_valid(&key).await {
             match fs::read_to_string(&path).await {
                 Ok(content) => match serde_json::from_str(&content) {
                     Ok(tx) => {
@@ -81,7 +328,10 @@ impl DataSourceCache {
                     }
                     Err(e) => tracing::warn!("Failed to deserialize cached transaction: {}", e),
                 },
-                Err(e) => tracing::warn!("Failed to read cached transaction: {}", e),
+                Err(e) => {
+                    tracing::warn!("Failed to read cached transaction: {}", e);
+                    self.forget(&key).await;
+                }
             }
         }
         None
@@ -98,25 +348,28 @@ impl DataSourceCache {
         let path = self.get_cache_path(&key);
 
         match serde_json::to_string(tx) {
-            Ok(json) => {
-                if let Err(e) = fs::write(&path, json).await {
-                    tracing::warn!("Failed to write transaction to cache: {}", e);
-                }
-            }
+            Ok(json) => match fs::write(&path, &json).await {
+                Ok(()) => self.record_save(&key, json.len()).await,
+                Err(e) => tracing::warn!("Failed to write transaction to cache: {}", e),
+            },
             Err(e) => tracing::warn!("Failed to serialize transaction: {}", e),
         }
+        self.prune_after_save().await;
     }
 
     /// Get raw text content from cache (useful for pages)
     pub async fn get_text(&self, key: &str) -> Option<String> {
         let path = self.get_cache_path(key);
-        if self.is_cache_valid(&path).await {
+        if self.is_cache_valid(key).await {
             match fs::read_to_string(&path).await {
                 Ok(content) => {
                     tracing::debug!("Cache hit for key {}", key);
                     return Some(content);
                 }
-                Err(e) => tracing::warn!("Failed to read cached text: {}", e),
+                Err(e) => {
+                    tracing::warn!("Failed to read cached text: {}", e);
+                    self.forget(key).await;
+                }
             }
         }
         None
@@ -130,8 +383,117 @@ impl DataSourceCache {
         }
 
         let path = self.get_cache_path(key);
-        if let Err(e) = fs::write(&path, content).await {
-            tracing::warn!("Failed to write text to cache: {}", e);
+        match fs::write(&path, content).await {
+            Ok(()) => self.record_save(key, content.len()).await,
+            Err(e) => tracing::warn!("Failed to write text to cache: {}", e),
         }
+        self.prune_after_save().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Backdate a cache entry in the index
+    async fn set_age(cache: &DataSourceCache, key: &str, age: Duration) {
+        let mut entries = cache.index.entries.lock().await;
+        let modified = SystemTime::now() - age;
+        entries.get_mut(key).unwrap().mtime = IndexEntry::new(modified, 0).mtime;
+    }
+
+    #[tokio::test]
+    async fn test_prune_removes_expired_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = DataSourceCache::new(Duration::from_secs(3600), Some(dir.path().into()));
+        for key in ["fresh", "stale", "ancient"] {
+            cache.save_text(key, "{}").await;
+        }
+        set_age(&cache, "stale", Duration::from_secs(7200)).await;
+        set_age(&cache, "ancient", Duration::from_secs(86_400)).await;
+
+        assert_eq!(cache.prune().await.unwrap(), 2);
+        assert_eq!(cache.get_text("fresh").await.as_deref(), Some("{}"));
+        assert!(!cache.get_cache_path("stale").exists());
+        assert_eq!(cache.prune().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_prune_keeps_entries_within_max_age() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = DataSourceCache::new(Duration::from_secs(30), Some(dir.path().into()))
+            .with_max_age(Duration::from_secs(3600));
+        cache.save_text("page", "{}").await;
+        set_age(&cache, "page", Duration::from_secs(60)).await;
+
+        // Too old for this run's lookups, but other runs may still use it
+        assert_eq!(cache.get_text("page").await, None);
+        assert_eq!(cache.prune().await.unwrap(), 0);
+        assert!(cache.get_cache_path("page").exists());
+    }
+
+    #[tokio::test]
+    async fn test_index_is_written_and_reused() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = DataSourceCache::new(Duration::from_secs(3600), Some(dir.path().into()));
+        cache.save_text("page", "[1,2,3]").await;
+
+        // Index writes are batched, and whatever is left is written on drop
+        assert!(!dir.path().join(INDEX_FILE).exists());
+        drop(cache);
+        let index = DataSourceCache::load_index(dir.path());
+        assert_eq!(index["page"].size, 7);
+
+        // A new instance trusts the index over the file's modification time, for
+        // lookups and pruning alike
+        let file = std::fs::File::options()
+            .write(true)
+            .open(dir.path().join("page.json"))
+            .unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(7200))
+            .unwrap();
+        let reloaded = DataSourceCache::new(Duration::from_secs(3600), Some(dir.path().into()));
+        assert_eq!(reloaded.get_text("page").await.as_deref(), Some("[1,2,3]"));
+        assert_eq!(reloaded.prune().await.unwrap(), 0);
+        assert!(reloaded.get_cache_path("page").exists());
+
+        // Files the index doesn't know about are still found on disk
+        std::fs::write(reloaded.get_cache_path("legacy"), "{}").unwrap();
+        assert_eq!(reloaded.get_text("legacy").await.as_deref(), Some("{}"));
+
+        // Pruning keeps the index in step and leaves the index file alone
+        set_age(&reloaded, "page", Duration::from_secs(7200)).await;
+        assert_eq!(reloaded.prune().await.unwrap(), 1);
+        let index = DataSourceCache::load_index(dir.path());
+        assert!(!index.contains_key("page"));
+        assert!(index.contains_key("legacy"));
+
+        // A full batch of saves is written straight away
+        for i in 0..INDEX_FLUSH_EVERY {
+            reloaded.save_text(&format!("key_{}", i), "{}").await;
+        }
+        let index = DataSourceCache::load_index(dir.path());
+        assert!(index.contains_key("key_0"));
+
+        // Without an index, it is rebuilt from the files
+        std::fs::remove_file(dir.path().join(INDEX_FILE)).unwrap();
+        assert!(DataSourceCache::load_index(dir.path()).contains_key("legacy"));
+    }
+
+    #[tokio::test]
+    async fn test_prune_removes_oldest_entries_over_budget() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = DataSourceCache::new(Duration::from_secs(3600), Some(dir.path().into()))
+            .with_max_bytes(25);
+        for (key, age) in [("oldest", 300), ("older", 200), ("newest", 100)] {
+            cache.save_text(key, "0123456789").await;
+            set_age(&cache, key, Duration::from_secs(age)).await;
+        }
+
+        // 30 bytes cached, so only the oldest entry has to go
+        assert_eq!(cache.prune().await.unwrap(), 1);
+        assert!(!cache.get_cache_path("oldest").exists());
+        assert!(cache.get_cache_path("older").exists());
+        assert!(cache.get_cache_path("newest").exists());
     }
 }
@@ -2,6 +2,7 @@
 //!
 //! Provides sample vesting contract transactions for testing and development.
 //! The mock vesting contract locks tokens that can be unlocked over time.
+//! [`MockScenarioBuilder`] generates other contract shapes (linear, tree, cyclic).
 
 use super::{
     Asset, DataSource, Datum, ExUnits, QueryParams, Redeemer, RedeemerTag, Transaction, TxInput,
@@ -22,8 +23,13 @@ pub struct MockDataSource {
 
 impl MockDataSource {
     pub fn new() -> Self {
+        Self::from_transactions(Self::generate_sample_transactions())
+    }
+
+    /// Serve a custom set of transactions, e.g. from a [`MockScenarioBuilder`]
+    pub fn from_transactions(transactions: Vec<Transaction>) -> Self {
         Self {
-            transactions: Self::generate_sample_transactions(),
+            transactions,
             _script_address: MOCK_SCRIPT_ADDRESS.to_string(),
         }
     }
@@ -139,6 +145,8 @@ impl MockDataSource {
             ],
             witnesses: Witnesses::default(),
             metadata: None,
+            size: None,
+            valid: true,
         }
     }
 
@@ -192,6 +200,8 @@ impl MockDataSource {
                 ..Default::default()
             },
             metadata: None,
+            size: None,
+            valid: true,
         }
     }
 
@@ -233,6 +243,8 @@ impl MockDataSource {
                 ..Default::default()
             },
             metadata: None,
+            size: None,
+            valid: true,
         }
     }
 
@@ -261,12 +273,14 @@ impl MockDataSource {
         let raw_cbor = crate::parser::cbor::encode_plutus_data(&plutus_data).unwrap_or_default();
 
         Datum {
-            hash: format!("datum_hash_{:016x}", total_amount ^ vested_amount),
+            hash: super::blake2b_256(&raw_cbor),
             raw_cbor,
             parsed: Some(crate::parser::ParsedDatum {
                 raw: plutus_data,
                 fields: std::collections::HashMap::new(),
+                schema_mismatch: None,
             }),
+            reencoded: false,
         }
     }
 
@@ -296,6 +310,119 @@ impl MockDataSource {
     }
 }
 
+/// Builds mock transaction histories of a given shape at a script address
+///
+/// Each call adds an independent chain starting from its own lock transaction, so
+/// shapes can be combined. Every state holds 2 ADA and no datum; spends carry a
+/// `Constr 0 []` spend redeemer.
+pub struct MockScenarioBuilder {
+    script_address: String,
+    transactions: Vec<Transaction>,
+}
+
+impl MockScenarioBuilder {
+    pub fn new(script_address: impl Into<String>) -> Self {
+        Self {
+            script_address: script_address.into(),
+            transactions: Vec::new(),
+        }
+    }
+
+    /// A lock followed by `transitions` spends, each consuming the previous state
+    pub fn linear(mut self, transitions: usize) -> Self {
+        let mut state = self.transaction(&[], 1)[0].clone();
+        for _ in 0..transitions {
+            state = self.transaction(&[state], 1)[0].clone();
+        }
+        self
+    }
+
+    /// A lock whose state is split into `branches` states by a single spend
+    pub fn tree(mut self, branches: usize) -> Self {
+        let root = self.transaction(&[], 1);
+        self.transaction(&root, branches);
+        self
+    }
+
+    /// A lock feeding a cycle of `length` states, each spending the previous one and
+    /// the first also spending the last
+    ///
+    /// A ledger can't produce this (the first spend references an output created
+    /// after it), but it exercises cycle handling in the graph and analyzer.
+    pub fn cyclic(mut self, length: usize) -> Self {
+        let lock = self.transaction(&[], 1);
+        let first = self.next_hash(length.saturating_sub(1));
+        let last = UtxoRef::new(first, 0);
+
+        let mut state = self.transaction(&[lock[0].clone(), last], 1)[0].clone();
+        for _ in 1..length {
+            state = self.transaction(&[state], 1)[0].clone();
+        }
+        self
+    }
+
+    /// The transactions generated so far, in slot order
+    pub fn build(self) -> Vec<Transaction> {
+        self.transactions
+    }
+
+    /// A mock data source serving the generated transactions
+    pub fn into_data_source(self) -> MockDataSource {
+        MockDataSource::from_transactions(self.build())
+    }
+
+    /// Hash of the transaction `ahead` transactions after the next one
+    fn next_hash(&self, ahead: usize) -> String {
+        format!("{:064x}", self.transactions.len() + ahead + 1)
+    }
+
+    /// Add a transaction spending `inputs` into `outputs` new states, returning them
+    fn transaction(&mut self, inputs: &[UtxoRef], outputs: usize) -> Vec<UtxoRef> {
+        let hash = self.next_hash(0);
+        let height = self.transactions.len() as u64 + 1;
+        let lovelace = 2_000_000;
+
+        let mut redeemers = Vec::with_capacity(inputs.len());
+        for index in 0..inputs.len() {
+            let mut redeemer = MockDataSource::create_unlock_redeemer();
+            redeemer.index = index as u64;
+            redeemers.push(redeemer);
+        }
+
+        self.transactions.push(Transaction {
+            hash: hash.clone(),
+            block: height,
+            slot: height * 100,
+            inputs: inputs
+                .iter()
+                .map(|utxo_ref| TxInput {
+                    utxo_ref: utxo_ref.clone(),
+                    address: Some(self.script_address.clone()),
+                    amount: Some(vec![Asset::lovelace(lovelace)]),
+                })
+                .collect(),
+            outputs: (0..outputs)
+                .map(|_| TxOutput {
+                    address: self.script_address.clone(),
+                    amount: vec![Asset::lovelace(lovelace)],
+                    datum: None,
+                    script_ref: None,
+                })
+                .collect(),
+            witnesses: Witnesses {
+                redeemers,
+                ..Default::default()
+            },
+            metadata: None,
+            size: None,
+            valid: true,
+        });
+        (0..outputs)
+            .map(|idx| UtxoRef::new(&hash, idx as u64))
+            .collect()
+    }
+}
+
 impl Default for MockDataSource {
     fn default() -> Self {
         Self::new()
@@ -315,10 +442,10 @@ impl DataSource for MockDataSource {
     async fn get_transactions_by_address(
         &self,
         address: &str,
-        _params: QueryParams,
+        params: QueryParams,
     ) -> Result<Vec<Transaction>> {
         // Filter transactions that involve the given address
-        let filtered: Vec<Transaction> = self
+        let mut filtered: Vec<&Transaction> = self
             .transactions
             .iter()
             .filter(|tx| {
@@ -328,35 +455,60 @@ impl DataSource for MockDataSource {
                         .iter()
                         .any(|inp| inp.address.as_deref() == Some(address))
             })
+            .collect();
+
+        // Same ordering, paging and limit semantics as the Blockfrost source: a page is
+        // taken from the address history before the slot/block bounds are applied,
+        // and without a page every transaction is returned
+        if params.order.as_deref() == Some("desc") {
+            filtered.reverse();
+        }
+        if let Some(page) = params.page {
+            let page_size = params.page_size.unwrap_or(100).min(100) as usize;
+            let start = (page.max(1) as usize - 1) * page_size;
+            filtered = filtered.into_iter().skip(start).take(page_size).collect();
+        }
+        let mut filtered: Vec<Transaction> = filtered
+            .into_iter()
+            .filter(|tx| params.matches(tx))
             .cloned()
             .collect();
+        if let Some(limit) = params.limit {
+            filtered.truncate(limit);
+        }
 
         Ok(filtered)
     }
 
-    async fn get_script_utxos(&self, address: &str) -> Result<Vec<(UtxoRef, TxOutput)>> {
-        let mut utxos = std::collections::HashMap::new();
-
-        // Add all outputs at script address
-        for tx in &self.transactions {
-            for (idx, output) in tx.outputs.iter().enumerate() {
-                if output.address == address {
-                    let utxo_ref = UtxoRef::new(&tx.hash, idx as u64);
-                    utxos.insert(utxo_ref, output.clone());
-                }
-            }
-        }
+    async fn get_script_utxos(
+        &self,
+        address: &str,
+        params: QueryParams,
+    ) -> Result<Vec<(UtxoRef, TxOutput)>> {
+        // Spent outputs at the script address
+        let spent: std::collections::HashSet<&UtxoRef> = self
+            .transactions
+            .iter()
+            .flat_map(|tx| &tx.inputs)
+            .filter(|input| input.address.as_deref() == Some(address))
+            .map(|input| &input.utxo_ref)
+            .collect();
 
-        // Remove spent outputs
-        for tx in &self.transactions {
-            for input in &tx.inputs {
-                if input.address.as_deref() == Some(address) {
-                    utxos.remove(&input.utxo_ref);
-                }
-            }
-        }
+        // Unspent outputs at the script address, in creation order
+        let utxos: Vec<(UtxoRef, TxOutput)> = self
+            .transactions
+            .iter()
+            .flat_map(|tx| {
+                tx.outputs
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, output)| (UtxoRef::new(&tx.hash, idx as u64), output))
+            })
+            .filter(|(utxo_ref, output)| output.address == address && !spent.contains(utxo_ref))
+            .map(|(utxo_ref, output)| (utxo_ref, output.clone()))
+            .collect();
 
-        Ok(utxos.into_iter().collect())
+        Ok(params.paginate(utxos))
     }
 }
 
@@ -383,6 +535,75 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_transactions_truncated_to_limit() {
+        let source = MockDataSource::new();
+        let txs = source
+            .get_transactions_by_address(MOCK_SCRIPT_ADDRESS, QueryParams::new().limit(2))
+            .await
+            .unwrap();
+
+        assert_eq!(txs.len(), 2);
+        assert_eq!(
+            txs[1].hash,
+            "0000000000000000000000000000000000000000000000000000000000000002"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transactions_paged() {
+        let source = MockDataSource::new();
+        let hashes = |txs: Vec<Transaction>| -> Vec<u64> {
+            txs.iter()
+                .map(|tx| u64::from_str_radix(&tx.hash, 16).unwrap())
+                .collect()
+        };
+
+        let page = |page, order: &str| {
+            let mut params = QueryParams::new().page(page).page_size(2);
+            params.order = Some(order.to_string());
+            source.get_transactions_by_address(MOCK_SCRIPT_ADDRESS, params)
+        };
+        assert_eq!(hashes(page(1, "asc").await.unwrap()), vec![1, 2]);
+        assert_eq!(hashes(page(2, "asc").await.unwrap()), vec![3, 4]);
+        assert_eq!(hashes(page(3, "asc").await.unwrap()), vec![5]);
+        assert!(page(4, "asc").await.unwrap().is_empty());
+        assert_eq!(hashes(page(1, "desc").await.unwrap()), vec![5, 4]);
+
+        // Page size alone does not limit the result
+        let params = QueryParams::new().page_size(2);
+        let txs = source
+            .get_transactions_by_address(MOCK_SCRIPT_ADDRESS, params)
+            .await
+            .unwrap();
+        assert_eq!(txs.len(), 5);
+
+        // Bounds and limit apply within the page
+        let params = QueryParams::new()
+            .page(1)
+            .page_size(4)
+            .from_slot(2000)
+            .limit(2);
+        let txs = source
+            .get_transactions_by_address(MOCK_SCRIPT_ADDRESS, params)
+            .await
+            .unwrap();
+        assert_eq!(hashes(txs), vec![2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_transactions_within_slot_bounds() {
+        let source = MockDataSource::new();
+        let params = QueryParams::new().from_slot(2000).to_slot(4000);
+        let txs = source
+            .get_transactions_by_address(MOCK_SCRIPT_ADDRESS, params)
+            .await
+            .unwrap();
+
+        let slots: Vec<u64> = txs.iter().map(|tx| tx.slot).collect();
+        assert_eq!(slots, vec![2000, 3000, 4000]);
+    }
+
     #[tokio::test]
     async fn test_get_transaction_by_hash() {
         let source = MockDataSource::new();
@@ -402,12 +623,97 @@ mod tests {
     #[tokio::test]
     async fn test_get_script_utxos() {
         let source = MockDataSource::new();
-        let utxos = source.get_script_utxos(MOCK_SCRIPT_ADDRESS).await.unwrap();
+        let utxos = source
+            .get_script_utxos(MOCK_SCRIPT_ADDRESS, QueryParams::default())
+            .await
+            .unwrap();
 
         // After all transactions, no UTXOs should remain (all unlocked)
         assert_eq!(utxos.len(), 0, "All tokens should be unlocked");
     }
 
+    #[tokio::test]
+    async fn test_script_utxos_beyond_one_page() {
+        // The spend of the lock leaves 250 unspent states
+        let source = MockScenarioBuilder::new("addr_script")
+            .tree(250)
+            .into_data_source();
+        let utxos = |params| source.get_script_utxos("addr_script", params);
+        let indexes = |utxos: Vec<(UtxoRef, TxOutput)>| -> Vec<u64> {
+            utxos
+                .iter()
+                .map(|(utxo_ref, _)| utxo_ref.output_index)
+                .collect()
+        };
+
+        // Without a page, every page is returned
+        let all = utxos(QueryParams::default()).await.unwrap();
+        assert_eq!(indexes(all), (0..250).collect::<Vec<_>>());
+
+        // Page size is capped at 100 like Blockfrost
+        let page = utxos(QueryParams::new().page(3).page_size(500))
+            .await
+            .unwrap();
+        assert_eq!(indexes(page), (200..250).collect::<Vec<_>>());
+        assert!(utxos(QueryParams::new().page(4)).await.unwrap().is_empty());
+
+        let limited = utxos(QueryParams::new().limit(120)).await.unwrap();
+        assert_eq!(indexes(limited), (0..120).collect::<Vec<_>>());
+
+        let mut params = QueryParams::new().limit(2);
+        params.order = Some("desc".to_string());
+        assert_eq!(indexes(utxos(params).await.unwrap()), vec![249, 248]);
+    }
+
+    /// Fetch a builder's transactions and detect the resulting contract pattern
+    async fn scenario_pattern(
+        builder: MockScenarioBuilder,
+    ) -> crate::state_machine::analyzer::ContractPattern {
+        let transactions = builder
+            .into_data_source()
+            .get_transactions_by_address("addr_script", QueryParams::default())
+            .await
+            .unwrap();
+        let graph =
+            crate::state_machine::build_state_graph(&transactions, "addr_script", None).unwrap();
+        crate::state_machine::analyzer::detect_pattern(&graph).pattern
+    }
+
+    #[tokio::test]
+    async fn test_scenario_builder_tree() {
+        use crate::state_machine::analyzer::ContractPattern;
+
+        let transactions = MockScenarioBuilder::new("addr_script").tree(2).build();
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(transactions[1].outputs.len(), 2);
+
+        let graph =
+            crate::state_machine::build_state_graph(&transactions, "addr_script", None).unwrap();
+        assert_eq!(graph.stats().total_states, 3);
+        assert_eq!(
+            graph.outgoing_transitions(&format!("{:064x}#0", 1)).len(),
+            2
+        );
+        assert_eq!(
+            scenario_pattern(MockScenarioBuilder::new("addr_script").tree(2)).await,
+            ContractPattern::Tree
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scenario_builder_linear_and_cyclic() {
+        use crate::state_machine::analyzer::ContractPattern;
+
+        assert_eq!(
+            scenario_pattern(MockScenarioBuilder::new("addr_script").linear(3)).await,
+            ContractPattern::Linear
+        );
+        assert_eq!(
+            scenario_pattern(MockScenarioBuilder::new("addr_script").cyclic(3)).await,
+            ContractPattern::Cyclic
+        );
+    }
+
     #[test]
     fn test_vesting_datum_structure() {
         let beneficiary = vec![0xaa; 28];
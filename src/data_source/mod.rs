@@ -3,7 +3,7 @@
 //! This module provides a trait-based abstraction for fetching transaction data
 //! from multiple sources (Blockfrost API, Cardano node, mock data).
 
-use crate::{Config, Result};
+use crate::{Config, Network, Result};
 use async_trait::async_trait;
 use std::time::Duration;
 
@@ -16,8 +16,8 @@ pub mod node;
 // Re-export models
 use crate::cli::DataSourceType;
 pub use models::{
-    Asset, Datum, ExUnits, Metadata, QueryParams, Redeemer, RedeemerTag, Script, Transaction,
-    TxInput, TxOutput, UtxoRef, Witnesses,
+    Asset, Datum, ExUnits, Metadata, PolicyAssets, QueryParams, Redeemer, RedeemerTag, Script,
+    Transaction, TxInput, TxOutput, UtxoRef, Witnesses,
 };
 
 /// Data source trait for fetching Cardano blockchain data
@@ -38,36 +38,154 @@ pub trait DataSource: Send + Sync {
         params: QueryParams,
     ) -> Result<Vec<Transaction>>;
 
-    /// Fetch current UTXOs at a script address
-    async fn get_script_utxos(&self, address: &str) -> Result<Vec<(UtxoRef, TxOutput)>>;
+    /// Fetch current UTXOs at a script address, oldest first
+    ///
+    /// `params` orders, pages and limits the result like transactions; slot and
+    /// block bounds don't apply to UTXOs. Without a page every UTXO is returned.
+    async fn get_script_utxos(
+        &self,
+        address: &str,
+        params: QueryParams,
+    ) -> Result<Vec<(UtxoRef, TxOutput)>>;
 }
 
-/// Create a data source instance based on type and configuration
+/// Create a data source instance for `network` based on type and configuration
 pub async fn create_data_source(
     source_type: DataSourceType,
+    network: Network,
     config: &Config,
     cache_enabled: bool,
     cache_ttl: Duration,
 ) -> Result<Box<dyn DataSource>> {
     match source_type {
         DataSourceType::Mock => Ok(Box::new(mock::MockDataSource::new())),
-        DataSourceType::Blockfrost => {
-            let mut ds = blockfrost::BlockfrostDataSource::new(config.blockfrost_api_key()?)?
-                .with_max_retries(config.blockfrost.max_retries)
-                .with_retry_delay(config.blockfrost.retry_delay_ms);
-            if cache_enabled {
-                ds = ds.with_cache(cache::DataSourceCache::new(cache_ttl, None));
-            }
-            Ok(Box::new(ds))
-        }
+        DataSourceType::Blockfrost => Ok(Box::new(blockfrost_data_source(
+            config,
+            network,
+            cache_enabled,
+            cache_ttl,
+        )?)),
         DataSourceType::Node => {
-            // let socket_path = config.node_socket_path()?;
-            // let network_magic = config.node.network_magic;
-            // Ok(Box::new(node::NodeDataSource::new(
-            //     socket_path,
-            //     network_magic,
-            // )))
-            unimplemented!("Node data source not implemented");
+            let network_magic = config.node.network_magic.unwrap_or(network.magic());
+            Ok(Box::new(node::NodeDataSource::new(
+                config.node_socket_path()?,
+                network_magic,
+            )?))
+        }
+    }
+}
+
+/// Calculate Blake2b-256 hash of data
+pub(crate) fn blake2b_256(data: &[u8]) -> String {
+    use blake2::digest::consts::U32;
+    use blake2::{Blake2b, Digest};
+
+    let mut hasher = Blake2b::<U32>::new();
+    hasher.update(data);
+    hex::encode(hasher.finalize())
+}
+
+/// Create a Blockfrost data source, attaching a cache only when enabled
+///
+/// Fails if the API key belongs to a project for a different network.
+fn blockfrost_data_source(
+    config: &Config,
+    network: Network,
+    cache_enabled: bool,
+    cache_ttl: Duration,
+) -> Result<blockfrost::BlockfrostDataSource> {
+    let api_key = config.blockfrost_api_key()?;
+    blockfrost::check_project_network(&api_key, network)?;
+    let mut ds =
+        blockfrost::BlockfrostDataSource::with_base_url(api_key, config.blockfrost_base_url())?
+            .with_max_retries(config.blockfrost.max_retries)
+            .with_retry_delay(config.blockfrost.retry_delay_ms)
+            .with_request_timeout(config.blockfrost.request_timeout)
+            .with_concurrency(config.blockfrost.concurrency);
+    if cache_enabled {
+        // Pruning goes by the configured TTL, which other runs share, so a short
+        // per-run TTL (e.g. watch's interval) doesn't delete their entries
+        let mut cache = cache::DataSourceCache::new(cache_ttl, Some(config.cache_directory()))
+            .with_max_age(cache_ttl.max(config.cache.ttl));
+        if let Some(max_bytes) = config.cache.max_bytes {
+            cache = cache.with_max_bytes(max_bytes);
+        }
+        ds = ds.with_cache(cache);
+    }
+    Ok(ds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{Cli, Commands};
+    use clap::Parser;
+
+    fn cache_flags(args: &[&str]) -> (bool, Duration) {
+        let cli = Cli::try_parse_from(
+            ["cardano-state-viz", "watch", "--address", "addr_test1..."]
+                .iter()
+                .chain(args),
+        )
+        .unwrap();
+        match cli.command {
+            Commands::Watch {
+                no_cache,
+                cache_ttl,
+                ..
+            } => (!no_cache, cache_ttl),
+            _ => unreachable!(),
         }
     }
+
+    #[test]
+    fn test_no_cache_flag_disables_cache() {
+        let config = Config::default().with_cli_api_key(Some("test_key".to_string()));
+
+        let (cache_enabled, cache_ttl) = cache_flags(&["--no-cache"]);
+        let ds =
+            blockfrost_data_source(&config, Network::default(), cache_enabled, cache_ttl).unwrap();
+        assert!(!ds.has_cache());
+
+        let (cache_enabled, cache_ttl) = cache_flags(&["--cache-ttl", "10m"]);
+        assert_eq!(cache_ttl, Duration::from_secs(600));
+        let ds =
+            blockfrost_data_source(&config, Network::default(), cache_enabled, cache_ttl).unwrap();
+        assert!(ds.has_cache());
+    }
+
+    #[test]
+    fn test_blockfrost_base_url_from_config() {
+        let mut config = Config::default().with_cli_api_key(Some("test_key".to_string()));
+        let ds = blockfrost_data_source(&config, Network::default(), false, Duration::from_secs(0))
+            .unwrap();
+        assert_eq!(ds.base_url(), None);
+
+        config.blockfrost.base_url = Some("http://localhost:3000/api/v0".to_string());
+        let ds = blockfrost_data_source(&config, Network::default(), false, Duration::from_secs(0))
+            .unwrap();
+        assert_eq!(ds.base_url(), Some("http://localhost:3000/api/v0"));
+    }
+
+    #[test]
+    fn test_blockfrost_key_must_match_network() {
+        let config = Config::default().with_cli_api_key(Some("mainnetAbC123".to_string()));
+        let no_cache = Duration::from_secs(0);
+
+        assert!(blockfrost_data_source(&config, Network::Mainnet, false, no_cache).is_ok());
+        assert!(matches!(
+            blockfrost_data_source(&config, Network::Preprod, false, no_cache),
+            Err(crate::Error::Config(_))
+        ));
+    }
+
+    #[test]
+    fn test_blockfrost_concurrency_from_config() {
+        let mut config = Config::default().with_cli_api_key(Some("test_key".to_string()));
+        config.blockfrost.concurrency = 8;
+
+        let ds = blockfrost_data_source(&config, Network::default(), false, Duration::from_secs(0))
+            .unwrap();
+        assert_eq!(ds.concurrency(), 8);
+    }
 }
@@ -4,10 +4,11 @@
 //! UTXOs, datums, redeemers, and related types.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 
 use crate::parser::{ParsedDatum, ParsedRedeemer};
+use crate::{Error, Result};
 
 /// Unique reference to a UTXO
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -54,6 +55,19 @@ pub struct Transaction {
 
     /// Transaction metadata
     pub metadata: Option<Metadata>,
+
+    /// Serialized transaction size in bytes, if known
+    #[serde(default)]
+    pub size: Option<u64>,
+
+    /// Whether phase-2 script validation succeeded; when it failed, only the
+    /// collateral was consumed
+    #[serde(default = "default_valid")]
+    pub valid: bool,
+}
+
+fn default_valid() -> bool {
+    true
 }
 
 /// Transaction input
@@ -91,11 +105,17 @@ pub struct TxOutput {
     pub script_ref: Option<Script>,
 }
 
+/// Length of a hex-encoded minting policy id (a 28-byte script hash)
+const POLICY_ID_HEX_LEN: usize = 56;
+
 /// Asset (ADA or native token)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Asset {
     /// Asset unit (e.g. "lovelace" or "policy_id.asset_name")
     pub unit: String,
+
+    /// Quantity as sent by the data source; native asset quantities can exceed `u64`,
+    /// so use [`Asset::amount`] to read it
     pub quantity: String,
 }
 
@@ -113,6 +133,82 @@ impl Asset {
             quantity: quantity.to_string(),
         }
     }
+
+    pub fn is_lovelace(&self) -> bool {
+        self.unit == "lovelace"
+    }
+
+    /// Split a native asset unit into its policy id and hex asset name
+    ///
+    /// Accepts both our `policy_id.asset_name` form and Blockfrost's concatenated
+    /// form, where the policy id is the first 56 hex characters.
+    fn split_unit(&self) -> Option<(&str, &str)> {
+        if self.is_lovelace() {
+            return None;
+        }
+        if let Some(split) = self.unit.split_once('.') {
+            return Some(split);
+        }
+        let is_hex = self.unit.bytes().all(|b| b.is_ascii_hexdigit());
+        (is_hex && self.unit.len() >= POLICY_ID_HEX_LEN)
+            .then(|| self.unit.split_at(POLICY_ID_HEX_LEN))
+    }
+
+    /// Minting policy id of a native asset (`None` for lovelace)
+    pub fn policy_id(&self) -> Option<&str> {
+        self.split_unit().map(|(policy_id, _)| policy_id)
+    }
+
+    /// Hex-encoded asset name of a native asset (empty for the policy's nameless token)
+    pub fn asset_name_hex(&self) -> Option<&str> {
+        self.split_unit().map(|(_, asset_name)| asset_name)
+    }
+
+    /// Asset name as text, when it decodes to printable UTF-8
+    pub fn asset_name_utf8(&self) -> Option<String> {
+        let bytes = hex::decode(self.asset_name_hex()?).ok()?;
+        let name = String::from_utf8(bytes).ok()?;
+        (!name.is_empty() && !name.chars().any(char::is_control)).then_some(name)
+    }
+
+    /// Parse the quantity as an unsigned integer
+    pub fn amount(&self) -> Result<u128> {
+        self.quantity.parse().map_err(|e| {
+            Error::parser(format!(
+                "Invalid quantity '{}' for asset {}: {}",
+                self.quantity, self.unit, e
+            ))
+        })
+    }
+
+    /// Group the native assets of a value by policy id, ordered by policy id
+    ///
+    /// Lovelace is left out.
+    pub fn group_by_policy(amount: &[Asset]) -> Vec<PolicyAssets<'_>> {
+        let mut groups: BTreeMap<&str, Vec<&Asset>> = BTreeMap::new();
+        for asset in amount {
+            if let Some(policy_id) = asset.policy_id() {
+                groups.entry(policy_id).or_default().push(asset);
+            }
+        }
+        groups
+            .into_iter()
+            .map(|(policy_id, assets)| PolicyAssets {
+                policy_id,
+                total: assets.iter().filter_map(|asset| asset.amount().ok()).sum(),
+                assets,
+            })
+            .collect()
+    }
+}
+
+/// Native assets sharing a minting policy
+#[derive(Debug, Clone)]
+pub struct PolicyAssets<'a> {
+    pub policy_id: &'a str,
+    pub assets: Vec<&'a Asset>,
+    /// Summed quantity of the assets; invalid quantities are not counted
+    pub total: u128,
 }
 
 /// Datum (data attached to UTXO)
@@ -127,6 +223,11 @@ pub struct Datum {
     /// Parsed PlutusData
     #[serde(skip)]
     pub parsed: Option<ParsedDatum>,
+
+    /// CBOR was rebuilt from the provider's JSON rather than taken from chain,
+    /// so it may not hash back to `hash`
+    #[serde(default)]
+    pub reencoded: bool,
 }
 
 /// Script reference
@@ -136,6 +237,10 @@ pub struct Script {
     pub script_type: String,
 
     pub bytes: Vec<u8>,
+
+    /// Serialized script size in bytes, if known
+    #[serde(default)]
+    pub size: Option<u64>,
 }
 
 /// Transaction witnesses
@@ -175,16 +280,20 @@ pub struct Redeemer {
 }
 
 /// Redeemer tag
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum RedeemerTag {
     Spend,
     Mint,
     Cert,
     Reward,
+    /// Conway-era governance vote
+    Voting,
+    /// Conway-era governance proposal
+    Proposing,
 }
 
 /// Execution units (memory and CPU steps)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ExUnits {
     pub mem: u64,
     pub steps: u64,
@@ -197,6 +306,16 @@ pub struct Metadata {
     pub json: serde_json::Value,
 }
 
+impl Metadata {
+    /// Top-level metadata labels (e.g. "674" for messages, "721" for NFTs)
+    pub fn labels(&self) -> Vec<&str> {
+        self.json
+            .as_object()
+            .map(|labels| labels.keys().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+}
+
 /// Query parameters for fetching transactions
 #[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
 pub struct QueryParams {
@@ -250,16 +369,85 @@ impl QueryParams {
         self
     }
 
+    pub fn from_block(mut self, block: u64) -> Self {
+        self.from_block = Some(block);
+        self
+    }
+
+    pub fn to_block(mut self, block: u64) -> Self {
+        self.to_block = Some(block);
+        self
+    }
+
     pub fn limit(mut self, limit: usize) -> Self {
         self.limit = Some(limit);
         self
     }
+
+    /// Whether a transaction falls within the slot and block bounds
+    pub fn matches(&self, tx: &Transaction) -> bool {
+        self.from_slot.is_none_or(|slot| tx.slot >= slot)
+            && self.to_slot.is_none_or(|slot| tx.slot <= slot)
+            && self.from_block.is_none_or(|block| tx.block >= block)
+            && self.to_block.is_none_or(|block| tx.block <= block)
+    }
+
+    /// Apply the order, page and limit to `items` listed oldest first, with the same
+    /// semantics as the Blockfrost source: without a page every item is kept
+    ///
+    /// For sources that hold the whole result in memory; slot and block bounds are
+    /// left to the caller.
+    pub(crate) fn paginate<T>(&self, mut items: Vec<T>) -> Vec<T> {
+        if self.order.as_deref() == Some("desc") {
+            items.reverse();
+        }
+        if let Some(page) = self.page {
+            let page_size = self.page_size.unwrap_or(100).min(100) as usize;
+            let start = (page.max(1) as usize - 1) * page_size;
+            items = items.into_iter().skip(start).take(page_size).collect();
+        }
+        if let Some(limit) = self.limit {
+            items.truncate(limit);
+        }
+        items
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_group_by_policy() {
+        let policy_a = "aa".repeat(28);
+        let policy_b = "bb".repeat(28);
+        let amount = vec![
+            Asset::lovelace(2_000_000),
+            Asset::native(&policy_b, &hex::encode("gold"), 5),
+            Asset::native(&policy_a, &hex::encode("one"), 1),
+            Asset::native(&policy_b, &hex::encode("silver"), 10),
+            Asset::native(&policy_a, &hex::encode("two"), 2),
+            Asset::native(&policy_a, &hex::encode("three"), 3),
+        ];
+
+        let groups = Asset::group_by_policy(&amount);
+        assert_eq!(groups.len(), 2);
+
+        assert_eq!(groups[0].policy_id, policy_a);
+        assert_eq!(groups[0].assets.len(), 3);
+        assert_eq!(groups[0].total, 6);
+        assert_eq!(groups[1].policy_id, policy_b);
+        let names: Vec<_> = groups[1]
+            .assets
+            .iter()
+            .filter_map(|asset| asset.asset_name_utf8())
+            .collect();
+        assert_eq!(names, vec!["gold", "silver"]);
+        assert_eq!(groups[1].total, 15);
+
+        assert!(Asset::group_by_policy(&[Asset::lovelace(1)]).is_empty());
+    }
+
     #[test]
     fn test_utxo_ref_display() {
         let utxo = UtxoRef::new("abc123def", 5);
@@ -294,12 +482,68 @@ mod tests {
         assert_eq!(set.len(), 2);
     }
 
+    #[test]
+    fn test_asset_amount() {
+        assert_eq!(Asset::lovelace(5_000_000).amount().unwrap(), 5_000_000);
+
+        let large = Asset {
+            unit: "policy.token".to_string(),
+            quantity: "18446744073709551616".to_string(), // u64::MAX + 1
+        };
+        assert_eq!(large.amount().unwrap(), u64::MAX as u128 + 1);
+
+        for quantity in ["", "-1", "1.5", "abc"] {
+            let asset = Asset {
+                unit: "policy.token".to_string(),
+                quantity: quantity.to_string(),
+            };
+            assert!(asset.amount().is_err(), "{:?} should not parse", quantity);
+        }
+    }
+
+    #[test]
+    fn test_asset_name_lovelace() {
+        let asset = Asset::lovelace(1_000_000);
+        assert_eq!(asset.policy_id(), None);
+        assert_eq!(asset.asset_name_hex(), None);
+        assert_eq!(asset.asset_name_utf8(), None);
+    }
+
+    #[test]
+    fn test_asset_name_printable() {
+        let policy_id = "ab".repeat(28);
+        let asset = Asset::native(&policy_id, &hex::encode("HOSKY"), 5);
+        assert_eq!(asset.policy_id(), Some(policy_id.as_str()));
+        assert_eq!(asset.asset_name_hex(), Some("484f534b59"));
+        assert_eq!(asset.asset_name_utf8().as_deref(), Some("HOSKY"));
+
+        // Blockfrost concatenates the policy id and asset name
+        let asset = Asset {
+            unit: format!("{}484f534b59", policy_id),
+            quantity: "5".to_string(),
+        };
+        assert_eq!(asset.policy_id(), Some(policy_id.as_str()));
+        assert_eq!(asset.asset_name_utf8().as_deref(), Some("HOSKY"));
+    }
+
+    #[test]
+    fn test_asset_name_not_utf8() {
+        let asset = Asset::native(&"ab".repeat(28), "ff00fe", 1);
+        assert_eq!(asset.asset_name_hex(), Some("ff00fe"));
+        assert_eq!(asset.asset_name_utf8(), None);
+
+        // Control characters are valid UTF-8 but not printable
+        let asset = Asset::native(&"ab".repeat(28), "0001", 1);
+        assert_eq!(asset.asset_name_utf8(), None);
+    }
+
     #[test]
     fn test_datum_serialization() {
         let datum = Datum {
             hash: "test_hash".to_string(),
             raw_cbor: vec![0xd8, 0x79],
             parsed: None,
+            reencoded: false,
         };
 
         let json = serde_json::to_string(&datum).unwrap();
@@ -325,6 +569,8 @@ mod tests {
             }],
             witnesses: Witnesses::default(),
             metadata: None,
+            size: None,
+            valid: true,
         };
 
         let json = serde_json::to_string(&tx).unwrap();
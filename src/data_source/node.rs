@@ -1,16 +1,29 @@
 //! Cardano node data source implementation
+//!
+//! Talks to a local node over its Unix socket using the node-to-client mini-protocols.
+//! The local state query protocol only exposes the current ledger state, so the UTXO
+//! set at an address is available but transaction history is not: that needs a chain
+//! indexer (e.g. Blockfrost) rather than a bare node.
 
-use crate::Result;
+use super::{Asset, DataSource, Datum, QueryParams, Transaction, TxOutput, UtxoRef, blake2b_256};
+use crate::parser::{GenericParser, Parser};
+use crate::{Error, Result};
+use async_trait::async_trait;
+use pallas_addresses::Address;
+use pallas_network::facades::NodeClient;
+use pallas_network::miniprotocols::localstate::queries_v16::{
+    self, DatumOption, TransactionOutput, UTxO, Value,
+};
 use std::path::PathBuf;
 
 /// Cardano node client
 pub struct NodeDataSource {
-    _socket_path: PathBuf,
-    _network_magic: Option<u32>,
+    socket_path: PathBuf,
+    network_magic: u32,
 }
 
 impl NodeDataSource {
-    pub fn new(socket_path: PathBuf, network_magic: Option<u32>) -> Result<Self> {
+    pub fn new(socket_path: PathBuf, network_magic: u32) -> Result<Self> {
         if !socket_path.exists() {
             return Err(crate::Error::Config(format!(
                 "Node socket path does not exist: {:?}",
@@ -18,10 +31,246 @@ impl NodeDataSource {
             )));
         }
         Ok(Self {
-            _socket_path: socket_path,
-            _network_magic: network_magic,
+            socket_path,
+            network_magic,
         })
     }
+
+    /// Open a connection and complete the node-to-client handshake
+    async fn connect(&self) -> Result<NodeClient> {
+        NodeClient::connect(&self.socket_path, self.network_magic as u64)
+            .await
+            .map_err(|e| {
+                Error::Node(format!(
+                    "Failed to connect to {:?} (network magic {}): {}",
+                    self.socket_path, self.network_magic, e
+                ))
+            })
+    }
+}
+
+/// Map a UTXO returned by the local state query to our model
+fn utxo_from_node(utxo: &UTxO, output: &TransactionOutput) -> Result<(UtxoRef, TxOutput)> {
+    let (address, amount, datum) = match output {
+        TransactionOutput::Current(output) => (
+            &output.address,
+            &output.amount,
+            output
+                .inline_datum
+                .as_ref()
+                .map(datum_from_node)
+                .transpose()?,
+        ),
+        TransactionOutput::Legacy(output) => (
+            &output.address,
+            &output.amount,
+            output.datum_hash.map(|hash| Datum {
+                hash: hash.to_string(),
+                raw_cbor: vec![],
+                parsed: None,
+                reencoded: false,
+            }),
+        ),
+    };
+
+    let address = Address::from_bytes(address)
+        .and_then(|address| address.to_bech32())
+        .map_err(|e| Error::Node(format!("Invalid output address: {}", e)))?;
+
+    let utxo_ref = UtxoRef::new(utxo.transaction_id.to_string(), u64::from(&utxo.index));
+    let output = TxOutput {
+        address,
+        amount: assets_from_node(amount),
+        datum,
+        // Reference scripts are not decoded from the node's output format
+        script_ref: None,
+    };
+    Ok((utxo_ref, output))
+}
+
+fn assets_from_node(value: &Value) -> Vec<Asset> {
+    match value {
+        Value::Coin(coin) => vec![Asset::lovelace(u64::from(coin))],
+        Value::Multiasset(coin, multiasset) => {
+            let mut assets = vec![Asset::lovelace(u64::from(coin))];
+            for (policy_id, tokens) in multiasset.iter() {
+                for (asset_name, quantity) in tokens.iter() {
+                    assets.push(Asset::native(
+                        &policy_id.to_string(),
+                        &hex::encode(asset_name.as_slice()),
+                        u64::from(quantity),
+                    ));
+                }
+            }
+            assets
+        }
+    }
 }
 
-// TODO: implement
+fn datum_from_node(datum: &DatumOption) -> Result<Datum> {
+    match datum {
+        DatumOption::Hash(hash) => Ok(Datum {
+            hash: hash.to_string(),
+            raw_cbor: vec![],
+            parsed: None,
+            reencoded: false,
+        }),
+        DatumOption::Data(data) => {
+            let raw_cbor = pallas_codec::minicbor::to_vec(&data.0)
+                .map_err(|e| Error::CborDecode(format!("Failed to encode inline datum: {}", e)))?;
+            Ok(Datum {
+                hash: blake2b_256(&raw_cbor),
+                parsed: GenericParser.parse_datum(&raw_cbor).ok(),
+                raw_cbor,
+                reencoded: false,
+            })
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for NodeDataSource {
+    async fn get_transaction(&self, tx_hash: &str) -> Result<Transaction> {
+        Err(Error::NotImplemented(format!(
+            "Fetching transaction {} from a node requires a chain indexer; use the blockfrost source",
+            tx_hash
+        )))
+    }
+
+    async fn get_transactions_by_address(
+        &self,
+        address: &str,
+        _params: QueryParams,
+    ) -> Result<Vec<Transaction>> {
+        Err(Error::NotImplemented(format!(
+            "Transaction history for {} is not available from a node's local state; \
+             use the blockfrost source",
+            address
+        )))
+    }
+
+    async fn get_script_utxos(
+        &self,
+        address: &str,
+        params: QueryParams,
+    ) -> Result<Vec<(UtxoRef, TxOutput)>> {
+        tracing::debug!("Fetching UTXOs for address {} from local node", address);
+
+        let addr = Address::from_bech32(address)
+            .map_err(|e| Error::InvalidAddress(format!("{}: {}", address, e)))?;
+
+        let mut client = self.connect().await?;
+        let statequery = client.statequery();
+        let node_err = |e: pallas_network::miniprotocols::localstate::ClientError| {
+            Error::Node(format!("Local state query failed: {}", e))
+        };
+
+        statequery.acquire(None).await.map_err(node_err)?;
+        let era = queries_v16::get_current_era(statequery)
+            .await
+            .map_err(node_err)?;
+        let utxos = queries_v16::get_utxo_by_address(statequery, era, vec![addr.to_vec().into()])
+            .await
+            .map_err(node_err)?;
+        statequery.send_release().await.map_err(node_err)?;
+        client.abort().await;
+
+        let mut result = utxos
+            .iter()
+            .map(|(utxo, output)| utxo_from_node(utxo, output))
+            .collect::<Result<Vec<_>>>()?;
+        result.sort_by(|(a, _), (b, _)| {
+            (&a.tx_hash, a.output_index).cmp(&(&b.tx_hash, b.output_index))
+        });
+
+        tracing::info!("Found {} UTXOs at {}", result.len(), address);
+        Ok(params.paginate(result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pallas_codec::utils::{AnyUInt, Bytes, CborWrap, MaybeIndefArray, NonEmptyKeyValuePairs};
+    use pallas_network::miniprotocols::localstate::queries_v16::{
+        BigInt, Constr, PlutusData, PostAlonsoTransactionOutput,
+    };
+
+    /// Testnet enterprise address paying to script hash 0x11..11
+    fn script_address_bytes() -> Vec<u8> {
+        let mut bytes = vec![0x70];
+        bytes.extend([0x11; 28]);
+        bytes
+    }
+
+    #[test]
+    fn test_utxo_from_node_maps_value_and_inline_datum() {
+        let datum = PlutusData::Constr(Constr {
+            tag: 121,
+            any_constructor: None,
+            fields: MaybeIndefArray::Def(vec![PlutusData::BigInt(BigInt::from(42))]),
+        });
+        let output = TransactionOutput::Current(PostAlonsoTransactionOutput {
+            address: Bytes::from(script_address_bytes()),
+            amount: Value::Multiasset(
+                AnyUInt::U64(2_000_000),
+                NonEmptyKeyValuePairs::Def(vec![(
+                    [0xab; 28].into(),
+                    NonEmptyKeyValuePairs::Def(vec![(
+                        Bytes::from(b"token".to_vec()),
+                        AnyUInt::U8(5),
+                    )]),
+                )]),
+            ),
+            inline_datum: Some(DatumOption::Data(CborWrap(datum))),
+            script_ref: None,
+        });
+        let utxo = UTxO {
+            transaction_id: [0xcd; 32].into(),
+            index: AnyUInt::U8(1),
+        };
+
+        let (utxo_ref, output) = utxo_from_node(&utxo, &output).unwrap();
+
+        assert_eq!(utxo_ref, UtxoRef::new("cd".repeat(32), 1));
+        assert!(output.address.starts_with("addr_test1w"));
+        let amount: Vec<_> = output
+            .amount
+            .iter()
+            .map(|a| (a.unit.as_str(), a.quantity.as_str()))
+            .collect();
+        let token_unit = format!("{}.{}", "ab".repeat(28), hex::encode("token"));
+        assert_eq!(
+            amount,
+            vec![("lovelace", "2000000"), (token_unit.as_str(), "5")]
+        );
+
+        let datum = output.datum.unwrap();
+        assert_eq!(datum.raw_cbor, vec![0xd8, 0x79, 0x81, 0x18, 0x2a]);
+        assert_eq!(datum.hash, blake2b_256(&datum.raw_cbor));
+        assert!(datum.parsed.is_some());
+    }
+
+    /// Queries a live node; set `CARDANO_NODE_SOCKET_PATH`, `CARDANO_NODE_TEST_ADDRESS`
+    /// and optionally `CARDANO_NODE_NETWORK_MAGIC` (defaults to preprod) to run it
+    #[tokio::test]
+    async fn test_node_script_utxos() {
+        let (Ok(socket_path), Ok(address)) = (
+            std::env::var("CARDANO_NODE_SOCKET_PATH"),
+            std::env::var("CARDANO_NODE_TEST_ADDRESS"),
+        ) else {
+            return;
+        };
+        let network_magic = std::env::var("CARDANO_NODE_NETWORK_MAGIC")
+            .ok()
+            .and_then(|magic| magic.parse().ok())
+            .unwrap_or(crate::Network::Preprod.magic());
+
+        let ds = NodeDataSource::new(socket_path.into(), network_magic).unwrap();
+        let utxos = ds
+            .get_script_utxos(&address, QueryParams::default())
+            .await
+            .unwrap();
+        assert!(utxos.iter().all(|(_, output)| output.address == address));
+    }
+}
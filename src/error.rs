@@ -55,6 +55,13 @@ pub enum Error {
     #[error("Invalid Cardano address: {0}")]
     InvalidAddress(String),
 
+    /// The address has no transactions (e.g. a typo, or the wrong network)
+    #[error(
+        "No transactions found at {address} on {network}; check the address and network, \
+         or pass --allow-empty to continue anyway"
+    )]
+    NoTransactions { address: String, network: String },
+
     /// Invalid transaction hash
     #[error("Invalid transaction hash: {0}")]
     InvalidTxHash(String),
@@ -113,6 +120,14 @@ impl Error {
         Self::StateMachine(msg.into())
     }
 
+    /// Create a schema parse error for a TOML file, locating the failure by line and column
+    pub fn schema_parse(file: impl Into<PathBuf>, contents: &str, err: &toml::de::Error) -> Self {
+        Self::SchemaParse {
+            file: file.into(),
+            message: describe_toml_error(contents, err),
+        }
+    }
+
     /// Check if error is a user quit
     pub fn is_user_quit(&self) -> bool {
         matches!(self, Error::UserQuit)
@@ -130,6 +145,20 @@ impl From<toml::de::Error> for Error {
     }
 }
 
+/// Format a TOML error as "line L, column C: message" when its location is known
+pub(crate) fn describe_toml_error(contents: &str, err: &toml::de::Error) -> String {
+    let message = err.message().trim_end();
+    match err.span() {
+        Some(span) => {
+            let before = &contents[..span.start.min(contents.len())];
+            let line = before.matches('\n').count() + 1;
+            let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+            format!("line {}, column {}: {}", line, column, message)
+        }
+        None => message.to_string(),
+    }
+}
+
 impl From<serde_json::Error> for Error {
     fn from(err: serde_json::Error) -> Self {
         Error::Parser(format!("JSON error: {}", err))
@@ -177,6 +206,17 @@ mod tests {
         assert_eq!(err.to_string(), "Data source error: connection failed");
     }
 
+    #[test]
+    fn test_schema_parse_error_location() {
+        let contents = "[contract]\nname = \"Test\"\nscript_address = \n";
+        let err = toml::from_str::<toml::Table>(contents).unwrap_err();
+
+        let err = Error::schema_parse("schemas/broken.toml", contents, &err);
+        let message = err.to_string();
+        assert!(message.contains("schemas/broken.toml"), "{}", message);
+        assert!(message.contains("line 3"), "{}", message);
+    }
+
     #[test]
     fn test_user_quit() {
         let err = Error::UserQuit;
@@ -8,32 +8,71 @@
 //! - Building state transition graphs from eUTXO chains
 //! - Visualizing state machines in an interactive TUI
 //! - Supporting user-defined schemas for contract-specific visualization
+//!
+//! [`api::analyze`] runs the whole pipeline and returns the graph for programmatic use.
 //! ```
 
+pub mod api;
 pub mod cli;
 pub mod config;
 pub mod data_source;
 pub mod error;
+pub mod network;
 pub mod parser;
 pub mod state_machine;
 pub mod tui;
 
 pub use config::Config;
 pub use error::{Error, Result};
+pub use network::Network;
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 pub const NAME: &str = env!("CARGO_PKG_NAME");
 
-/// Initialize logging with the given log level
-pub fn init_logging(level: &str) {
+/// Initialize logging with the given log level, writing to stderr and, if set, to `file`
+///
+/// `RUST_LOG` takes precedence over `level`.
+pub fn init_logging(level: &str, file: Option<&std::path::Path>) -> Result<()> {
+    use tracing_appender::rolling::{RollingFileAppender, Rotation};
     use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
 
-    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(level));
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| log_filter(level));
+
+    let file_layer = match file {
+        Some(path) => {
+            let file_name = path.file_name().ok_or_else(|| {
+                Error::Config(format!("Log file path has no file name: {:?}", path))
+            })?;
+            let directory = path
+                .parent()
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .unwrap_or(std::path::Path::new("."));
+            let appender = RollingFileAppender::builder()
+                .rotation(Rotation::NEVER)
+                .filename_prefix(file_name.to_string_lossy())
+                .build(directory)
+                .map_err(|e| Error::Config(format!("Cannot open log file {:?}: {}", path, e)))?;
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .with_ansi(false)
+                    .with_writer(appender),
+            )
+        }
+        None => None,
+    };
 
     tracing_subscriber::registry()
         .with(filter)
         .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .with(file_layer)
         .init();
+    Ok(())
+}
+
+/// Log filter for a configured level, falling back to `info` if it does not parse
+fn log_filter(level: &str) -> tracing_subscriber::EnvFilter {
+    tracing_subscriber::EnvFilter::try_new(level)
+        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info"))
 }
 
 #[cfg(test)]
@@ -45,4 +84,21 @@ mod tests {
         assert!(!VERSION.is_empty());
         assert_eq!(NAME, "cardano-state-viz");
     }
+
+    #[test]
+    fn test_log_filter_from_level() {
+        use tracing::level_filters::LevelFilter;
+
+        assert_eq!(
+            log_filter("debug").max_level_hint(),
+            Some(LevelFilter::DEBUG)
+        );
+        assert_eq!(log_filter("WARN").max_level_hint(), Some(LevelFilter::WARN));
+        assert_eq!(
+            log_filter("warn,cardano_state_viz=trace").to_string(),
+            "cardano_state_viz=trace,warn"
+        );
+        // Unparseable levels fall back to info
+        assert_eq!(log_filter("=").max_level_hint(), Some(LevelFilter::INFO));
+    }
 }
@@ -7,18 +7,17 @@ use clap::Parser;
 async fn main() -> Result<()> {
     let args = cli::Cli::parse();
 
-    // Use default level, can be overridden by config
-    init_logging("info");
-
-    tracing::info!("Cardano State Machine Visualizer v{}", VERSION);
-    tracing::debug!("Parsed arguments: {:?}", args);
-
+    // Load the config first so its logging settings apply from the start
     let config = if let Some(config_path) = &args.config {
         Config::from_file(config_path)?
     } else {
         Config::load()?
     };
 
+    init_logging(&config.logging.level, config.logging.file.as_deref())?;
+
+    tracing::info!("Cardano State Machine Visualizer v{}", VERSION);
+    tracing::debug!("Parsed arguments: {:?}", args);
     tracing::debug!("Loaded configuration: {:?}", config);
 
     cli::execute(args, config).await?;
@@ -0,0 +1,298 @@
+//! Cardano network parameters
+//!
+//! This module defines the supported networks and the era-aware slot arithmetic
+//! needed to convert between absolute slot numbers and wall-clock time.
+
+use crate::error::{Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Slot length during the Byron era, in seconds
+const BYRON_SLOT_LENGTH_SECS: i64 = 20;
+
+/// Slot length from the Shelley era onwards, in seconds
+const SHELLEY_SLOT_LENGTH_SECS: i64 = 1;
+
+/// Supported Cardano networks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    Mainnet,
+    #[default]
+    Preprod,
+    Preview,
+}
+
+/// Era boundary parameters used for slot <-> time conversion
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct EraParams {
+    /// Unix timestamp (seconds) of slot 0
+    system_start: i64,
+
+    /// First slot of the Shelley era (0 for networks without a Byron era)
+    shelley_start_slot: u64,
+}
+
+impl EraParams {
+    /// Unix timestamp (seconds) of the first Shelley slot
+    fn shelley_start_time(&self) -> i64 {
+        self.system_start + self.shelley_start_slot as i64 * BYRON_SLOT_LENGTH_SECS
+    }
+}
+
+impl Network {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "mainnet",
+            Network::Preprod => "preprod",
+            Network::Preview => "preview",
+        }
+    }
+
+    /// Network magic used in the node-to-client handshake
+    pub fn magic(&self) -> u32 {
+        match self {
+            Network::Mainnet => 764_824_073,
+            Network::Preprod => 1,
+            Network::Preview => 2,
+        }
+    }
+
+    /// Era parameters for this network
+    ///
+    /// - Mainnet: Byron started 2017-09-23T21:44:51Z, Shelley hard fork at slot 4492800 (epoch 208)
+    /// - Preprod: Byron started 2022-06-01T00:00:00Z, Shelley hard fork at slot 86400 (epoch 4)
+    /// - Preview: Shelley from genesis, started 2022-10-25T00:00:00Z
+    fn era_params(&self) -> EraParams {
+        match self {
+            Network::Mainnet => EraParams {
+                system_start: 1_506_203_091,
+                shelley_start_slot: 4_492_800,
+            },
+            Network::Preprod => EraParams {
+                system_start: 1_654_041_600,
+                shelley_start_slot: 86_400,
+            },
+            Network::Preview => EraParams {
+                system_start: 1_666_656_000,
+                shelley_start_slot: 0,
+            },
+        }
+    }
+
+    /// Convert an absolute slot number to a Unix timestamp (seconds)
+    pub fn slot_to_unix_time(&self, slot: u64) -> i64 {
+        let params = self.era_params();
+        if slot < params.shelley_start_slot {
+            params.system_start + slot as i64 * BYRON_SLOT_LENGTH_SECS
+        } else {
+            params.shelley_start_time()
+                + (slot - params.shelley_start_slot) as i64 * SHELLEY_SLOT_LENGTH_SECS
+        }
+    }
+
+    /// Convert an absolute slot number to a UTC datetime
+    pub fn slot_to_datetime(&self, slot: u64) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp(self.slot_to_unix_time(slot), 0)
+    }
+
+    /// Convert a Unix timestamp (seconds) to the slot in progress at that time
+    ///
+    /// Returns `None` for times before the network's system start.
+    pub fn unix_time_to_slot(&self, unix_time: i64) -> Option<u64> {
+        let params = self.era_params();
+        if unix_time < params.system_start {
+            return None;
+        }
+
+        let shelley_start_time = params.shelley_start_time();
+        if unix_time < shelley_start_time {
+            Some(((unix_time - params.system_start) / BYRON_SLOT_LENGTH_SECS) as u64)
+        } else {
+            Some(
+                params.shelley_start_slot
+                    + ((unix_time - shelley_start_time) / SHELLEY_SLOT_LENGTH_SECS) as u64,
+            )
+        }
+    }
+
+    /// First slot of a trailing time window ending at `now`
+    ///
+    /// Windows reaching back before the system start are clamped to slot 0.
+    pub fn window_start_slot(&self, window: Duration, now: DateTime<Utc>) -> u64 {
+        let cutoff = now.timestamp().saturating_sub(window.as_secs() as i64);
+        self.unix_time_to_slot(cutoff).unwrap_or(0)
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Network {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "mainnet" => Ok(Network::Mainnet),
+            "preprod" => Ok(Network::Preprod),
+            "preview" => Ok(Network::Preview),
+            other => Err(Error::Config(format!(
+                "Unknown network '{}' (expected mainnet, preprod or preview)",
+                other
+            ))),
+        }
+    }
+}
+
+/// Check that `address` is a well-formed bech32 Cardano address before querying a source
+///
+/// The mock source's placeholder address is accepted as is. An address for a different
+/// network than `expected_network` only logs a warning, since the source decides
+/// whether it can serve it.
+pub fn validate_address(address: &str, expected_network: Option<&str>) -> Result<()> {
+    if address == crate::data_source::mock::MOCK_SCRIPT_ADDRESS {
+        return Ok(());
+    }
+
+    let parsed = pallas_addresses::Address::from_bech32(address)
+        .map_err(|e| Error::InvalidAddress(format!("{}: {}", address, e)))?;
+
+    let Some(expected) = expected_network.and_then(|name| name.parse::<Network>().ok()) else {
+        return Ok(());
+    };
+    let address_is_mainnet = match parsed.network() {
+        Some(pallas_addresses::Network::Mainnet) => true,
+        Some(pallas_addresses::Network::Testnet) => false,
+        _ => return Ok(()),
+    };
+    if address_is_mainnet != (expected == Network::Mainnet) {
+        tracing::warn!(
+            "Address {} is for {}, but the selected network is {}",
+            address,
+            if address_is_mainnet {
+                "mainnet"
+            } else {
+                "a testnet"
+            },
+            expected
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// CIP-19 test vectors for a script-hash enterprise address
+    const MAINNET_ADDRESS: &str = "addr1w8phkx6acpnf78fuvxn0mkew3l0fd058hzquvz7w36x4gtcyjy7wx";
+    const TESTNET_ADDRESS: &str = "addr_test1wrphkx6acpnf78fuvxn0mkew3l0fd058hzquvz7w36x4gtcl6szpr";
+
+    #[test]
+    fn test_validate_address_mainnet() {
+        assert!(validate_address(MAINNET_ADDRESS, Some("mainnet")).is_ok());
+        // A network mismatch only warns
+        assert!(validate_address(MAINNET_ADDRESS, Some("preprod")).is_ok());
+    }
+
+    #[test]
+    fn test_validate_address_testnet() {
+        assert!(validate_address(TESTNET_ADDRESS, Some("preprod")).is_ok());
+        assert!(validate_address(TESTNET_ADDRESS, None).is_ok());
+    }
+
+    #[test]
+    fn test_validate_address_rejects_garbage() {
+        for address in ["", "not-an-address", "addr1qqqqqqq", &MAINNET_ADDRESS[..40]] {
+            assert!(
+                matches!(
+                    validate_address(address, Some("mainnet")),
+                    Err(Error::InvalidAddress(_))
+                ),
+                "{}",
+                address
+            );
+        }
+        assert!(validate_address("mock", Some("mainnet")).is_ok());
+    }
+
+    #[test]
+    fn test_mainnet_byron_slots() {
+        // Genesis: 2017-09-23T21:44:51Z
+        assert_eq!(Network::Mainnet.slot_to_unix_time(0), 1_506_203_091);
+        // Last Byron slot, 20s per slot
+        assert_eq!(Network::Mainnet.slot_to_unix_time(4_492_799), 1_596_059_071);
+    }
+
+    #[test]
+    fn test_mainnet_shelley_slots() {
+        // Shelley hard fork: 2020-07-29T21:44:51Z
+        assert_eq!(Network::Mainnet.slot_to_unix_time(4_492_800), 1_596_059_091);
+        // Start of epoch 209: 2020-08-03T21:44:51Z
+        assert_eq!(Network::Mainnet.slot_to_unix_time(4_924_800), 1_596_491_091);
+        // Well-known Shelley offset: unix = slot + 1591566291
+        assert_eq!(
+            Network::Mainnet.slot_to_unix_time(100_000_000),
+            100_000_000 + 1_591_566_291
+        );
+    }
+
+    #[test]
+    fn test_testnet_slots() {
+        // Preprod Shelley hard fork: 2022-06-21T00:00:00Z
+        assert_eq!(Network::Preprod.slot_to_unix_time(86_400), 1_655_769_600);
+        // Preview genesis: 2022-10-25T00:00:00Z
+        assert_eq!(Network::Preview.slot_to_unix_time(0), 1_666_656_000);
+        assert_eq!(Network::Preview.slot_to_unix_time(3600), 1_666_659_600);
+    }
+
+    #[test]
+    fn test_unix_time_to_slot_round_trip() {
+        for network in [Network::Mainnet, Network::Preprod, Network::Preview] {
+            for slot in [0, 1, 86_399, 86_400, 4_492_799, 4_492_800, 120_000_000] {
+                let time = network.slot_to_unix_time(slot);
+                assert_eq!(network.unix_time_to_slot(time), Some(slot), "{}", network);
+            }
+        }
+
+        // Mid-Byron-slot times round down to the slot in progress
+        assert_eq!(
+            Network::Mainnet.unix_time_to_slot(1_506_203_091 + 25),
+            Some(1)
+        );
+        assert_eq!(Network::Mainnet.unix_time_to_slot(1_506_203_090), None);
+    }
+
+    #[test]
+    fn test_slot_to_datetime() {
+        let datetime = Network::Mainnet.slot_to_datetime(4_492_800).unwrap();
+        assert_eq!(
+            datetime.format("%Y-%m-%d %H:%M:%S").to_string(),
+            "2020-07-29 21:44:51"
+        );
+    }
+
+    #[test]
+    fn test_window_start_slot() {
+        let now = DateTime::from_timestamp(1_666_656_000 + 10 * 86_400, 0).unwrap();
+        let week = Duration::from_secs(7 * 86_400);
+        assert_eq!(Network::Preview.window_start_slot(week, now), 3 * 86_400);
+
+        // Windows older than the network are clamped to genesis
+        let year = Duration::from_secs(365 * 86_400);
+        assert_eq!(Network::Preview.window_start_slot(year, now), 0);
+    }
+
+    #[test]
+    fn test_network_from_str() {
+        assert_eq!("mainnet".parse::<Network>().unwrap(), Network::Mainnet);
+        assert_eq!("Preprod".parse::<Network>().unwrap(), Network::Preprod);
+        assert!("testnet".parse::<Network>().is_err());
+    }
+}
@@ -28,6 +28,21 @@ pub enum PlutusData {
 
 use minicbor::encode::Encoder;
 
+/// CBOR tag for an unsigned bignum (RFC 8949, section 3.4.3)
+const BIGNUM_POSITIVE_TAG: u64 = 2;
+
+/// CBOR tag for a negative bignum, encoding `-1 - n`
+const BIGNUM_NEGATIVE_TAG: u64 = 3;
+
+/// CBOR tag for a byte string holding encoded CBOR (RFC 8949, section 3.4.5.1)
+const ENCODED_CBOR_TAG: u64 = 24;
+
+/// CBOR tag for a constructor of any index, encoded as `[index, [fields...]]`
+const GENERAL_CONSTR_TAG: u64 = 102;
+
+/// CBOR tag for a set, used by Conway-era encodings
+const SET_TAG: u64 = 258;
+
 /// Encode PlutusData to CBOR bytes
 pub fn encode_plutus_data(data: &PlutusData) -> Result<Vec<u8>> {
     let mut buffer = Vec::new();
@@ -45,9 +60,23 @@ fn encode_plutus_data_recursive<W: minicbor::encode::Write>(
     data: &PlutusData,
 ) -> std::result::Result<(), minicbor::encode::Error<W::Error>> {
     match data {
-        PlutusData::Integer(n) => {
-            encoder.i64(*n as i64)?;
-        }
+        PlutusData::Integer(n) => match minicbor::data::Int::try_from(*n) {
+            Ok(int) => {
+                encoder.int(int)?;
+            }
+            Err(_) => {
+                // Outside the CBOR major type 0/1 range: encode as a bignum (tags 2 and 3)
+                let (tag, magnitude) = if *n >= 0 {
+                    (BIGNUM_POSITIVE_TAG, *n as u128)
+                } else {
+                    (BIGNUM_NEGATIVE_TAG, (-1 - *n) as u128)
+                };
+                let bytes = magnitude.to_be_bytes();
+                let first = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+                encoder.tag(minicbor::data::Tag::new(tag))?;
+                encoder.bytes(&bytes[first..])?;
+            }
+        },
         PlutusData::Bytes(b) => {
             encoder.bytes(b)?;
         }
@@ -72,11 +101,17 @@ fn encode_plutus_data_recursive<W: minicbor::encode::Write>(
             } else if *tag <= 127 {
                 1280 + (tag - 7)
             } else {
-                102
+                GENERAL_CONSTR_TAG
             };
 
             encoder.tag(minicbor::data::Tag::new(cbor_tag))?;
 
+            // The general form carries the constructor index next to the fields
+            if cbor_tag == GENERAL_CONSTR_TAG {
+                encoder.array(2)?;
+                encoder.u64(*tag)?;
+            }
+
             encoder.begin_array()?;
             for field in fields {
                 encode_plutus_data_recursive(encoder, field)?;
@@ -114,22 +149,19 @@ fn decode_plutus_data_recursive(decoder: &mut Decoder) -> Result<PlutusData> {
         | Type::I8
         | Type::I16
         | Type::I32
-        | Type::I64 => {
-            let value: i128 = decoder
-                .i64()
-                .map_err(|e| crate::Error::CborDecode(format!("Failed to decode integer: {}", e)))?
-                as i128;
-            Ok(PlutusData::Integer(value))
+        | Type::I64
+        | Type::Int => {
+            let value = decoder.int().map_err(|e| {
+                crate::Error::CborDecode(format!("Failed to decode integer: {}", e))
+            })?;
+            Ok(PlutusData::Integer(i128::from(value)))
         }
 
-        // Byte string
-        Type::Bytes => {
-            let bytes: Vec<u8> = decoder
-                .bytes()
-                .map_err(|e| crate::Error::CborDecode(format!("Failed to decode bytes: {}", e)))?
-                .to_vec();
-            Ok(PlutusData::Bytes(bytes))
-        }
+        // Byte string (chunked when longer than 64 bytes)
+        Type::Bytes | Type::BytesIndef => Ok(PlutusData::Bytes(decode_bytes(decoder)?)),
+
+        // Text string, not valid PlutusData but found in metadata-adjacent datums
+        Type::String | Type::StringIndef => Ok(PlutusData::Bytes(decode_text(decoder)?)),
 
         // Array (List or Constr alternative 0-6)
         Type::Array | Type::ArrayIndef => {
@@ -193,16 +225,36 @@ fn decode_plutus_data_recursive(decoder: &mut Decoder) -> Result<PlutusData> {
             Ok(PlutusData::Map(pairs))
         }
 
-        // Tag (Constructor with alternative >= 7)
+        // Tag (Constructor, bignum or wrapper)
         Type::Tag => {
             let tag = decoder
                 .tag()
                 .map_err(|e| crate::Error::CborDecode(format!("Failed to decode tag: {}", e)))?;
 
             // Tag 121-127 are used for Constr 0-6 (compact encoding)
-            // Tag 1280-1400 are used for Constr 7-127 (general encoding)
+            // Tag 1280-1400 are used for Constr 7-127
+            // Tag 102 wraps [index, fields] for any constructor (general encoding)
             let tag_value = tag.as_u64();
-            if (121..=127).contains(&tag_value) {
+            if tag_value == BIGNUM_POSITIVE_TAG || tag_value == BIGNUM_NEGATIVE_TAG {
+                let magnitude = decode_bignum_magnitude(decoder)?;
+                let value = if tag_value == BIGNUM_POSITIVE_TAG {
+                    magnitude
+                } else {
+                    -1 - magnitude
+                };
+                Ok(PlutusData::Integer(value))
+            } else if tag_value == ENCODED_CBOR_TAG {
+                // Inline datums may arrive wrapped as CBOR-in-CBOR
+                let inner = decode_bytes(decoder)?;
+                decode_plutus_data_recursive(&mut Decoder::new(&inner))
+            } else if tag_value == SET_TAG {
+                match decode_plutus_data_recursive(decoder)? {
+                    items @ PlutusData::List(_) => Ok(items),
+                    _ => Err(crate::Error::CborDecode(
+                        "Expected array for tagged set".to_string(),
+                    )),
+                }
+            } else if (121..=127).contains(&tag_value) {
                 let constr_tag = tag_value - 121;
                 let fields =
                     if let PlutusData::List(fields) = decode_plutus_data_recursive(decoder)? {
@@ -230,6 +282,29 @@ fn decode_plutus_data_recursive(decoder: &mut Decoder) -> Result<PlutusData> {
                     tag: constr_tag,
                     fields,
                 })
+            } else if tag_value == GENERAL_CONSTR_TAG {
+                match decode_plutus_data_recursive(decoder)? {
+                    PlutusData::List(items) => match items.as_slice() {
+                        [PlutusData::Integer(index), PlutusData::List(fields)] => {
+                            let tag = u64::try_from(*index).map_err(|_| {
+                                crate::Error::CborDecode(format!(
+                                    "Invalid constructor index: {}",
+                                    index
+                                ))
+                            })?;
+                            Ok(PlutusData::Constr {
+                                tag,
+                                fields: fields.clone(),
+                            })
+                        }
+                        _ => Err(crate::Error::CborDecode(
+                            "Expected [index, fields] for tag 102 constructor".to_string(),
+                        )),
+                    },
+                    _ => Err(crate::Error::CborDecode(
+                        "Expected array for tag 102 constructor".to_string(),
+                    )),
+                }
             } else {
                 Err(crate::Error::CborDecode(format!(
                     "Unsupported CBOR tag for PlutusData: {}",
@@ -245,23 +320,90 @@ fn decode_plutus_data_recursive(decoder: &mut Decoder) -> Result<PlutusData> {
     }
 }
 
+/// Decode a definite or indefinite (chunked) byte string
+fn decode_bytes(decoder: &mut Decoder) -> Result<Vec<u8>> {
+    let map_err = |e: minicbor::decode::Error| {
+        crate::Error::CborDecode(format!("Failed to decode bytes: {}", e))
+    };
+
+    let mut bytes = Vec::new();
+    for chunk in decoder.bytes_iter().map_err(map_err)? {
+        bytes.extend_from_slice(chunk.map_err(map_err)?);
+    }
+    Ok(bytes)
+}
+
+/// Decode a definite or indefinite (chunked) UTF-8 text string into its bytes
+fn decode_text(decoder: &mut Decoder) -> Result<Vec<u8>> {
+    let map_err = |e: minicbor::decode::Error| {
+        crate::Error::CborDecode(format!("Failed to decode text string: {}", e))
+    };
+
+    let mut text = String::new();
+    for chunk in decoder.str_iter().map_err(map_err)? {
+        text.push_str(chunk.map_err(map_err)?);
+    }
+    Ok(text.into_bytes())
+}
+
+/// Decode the big-endian magnitude of a bignum, which must fit in an `i128`
+fn decode_bignum_magnitude(decoder: &mut Decoder) -> Result<i128> {
+    let bytes = decode_bytes(decoder)?;
+    let first = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    let significant = &bytes[first..];
+
+    let too_large = || {
+        crate::Error::CborDecode(format!(
+            "Bignum 0x{} exceeds the supported integer range (i128)",
+            hex::encode(significant)
+        ))
+    };
+    if significant.len() > 16 {
+        return Err(too_large());
+    }
+
+    let mut buf = [0u8; 16];
+    buf[16 - significant.len()..].copy_from_slice(significant);
+    i128::try_from(u128::from_be_bytes(buf)).map_err(|_| too_large())
+}
+
 impl PlutusData {
     /// Convert to human-readable string with heuristics
     ///
     /// Attempts to interpret common patterns:
     /// - 28-byte bytestrings as public key hashes
-    /// - Large integers as POSIX timestamps
+    /// - Large integers as POSIX timestamps, in seconds or milliseconds
     /// - Small integers as booleans (0 = false, 1 = true)
     pub fn to_human_readable(&self) -> String {
         self.to_string()
     }
 
-    fn format_recursive(&self, f: &mut std::fmt::Formatter<'_>, depth: usize) -> std::fmt::Result {
+    /// Like [`PlutusData::to_human_readable`], with the rendering tweaked by `options`
+    pub fn to_human_readable_with(&self, options: HumanReadableOptions) -> String {
+        HumanReadable {
+            data: self,
+            options,
+        }
+        .to_string()
+    }
+
+    fn format_recursive(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        depth: usize,
+        options: HumanReadableOptions,
+    ) -> std::fmt::Result {
         if depth > 10 {
             return write!(f, "...");
         }
 
         match self {
+            PlutusData::Bytes(b) if b.len() == 28 && options.bech32_keys => {
+                match bech32::encode::<bech32::Bech32>(VKEY_HASH_HRP, b) {
+                    Ok(encoded) => write!(f, "{}", encoded),
+                    Err(_) => write!(f, "PubKeyHash({})", hex::encode(b)),
+                }
+            }
             PlutusData::Bytes(b) if b.len() == 28 => {
                 write!(f, "PubKeyHash({})", hex::encode(b))
             }
@@ -281,24 +423,17 @@ impl PlutusData {
             PlutusData::Integer(n) if *n == 0 || *n == 1 => {
                 write!(f, "{}", if *n == 1 { "true" } else { "false" })
             }
-            PlutusData::Integer(n) => {
-                // Try to parse as timestamp (seconds since epoch)
-                // Timestamps in Cardano are usually > 1,500,000,000
-                if *n > 1_500_000_000
-                    && *n < 2_500_000_000
-                    && let Some(datetime) = DateTime::from_timestamp(*n as i64, 0)
-                {
-                    return write!(f, "{}", datetime.format("%Y-%m-%d %H:%M:%S UTC"));
-                }
-                write!(f, "{}", n)
-            }
+            PlutusData::Integer(n) => match posix_time(*n) {
+                Some(datetime) => write!(f, "{}", datetime.format(POSIX_TIME_FORMAT)),
+                None => write!(f, "{}", n),
+            },
             PlutusData::List(items) => {
                 write!(f, "[")?;
                 for (i, item) in items.iter().enumerate() {
                     if i > 0 {
                         write!(f, ", ")?;
                     }
-                    item.format_recursive(f, depth + 1)?;
+                    item.format_recursive(f, depth + 1, options)?;
                 }
                 write!(f, "]")
             }
@@ -308,9 +443,9 @@ impl PlutusData {
                     if i > 0 {
                         write!(f, ", ")?;
                     }
-                    k.format_recursive(f, depth + 1)?;
+                    k.format_recursive(f, depth + 1, options)?;
                     write!(f, ": ")?;
-                    v.format_recursive(f, depth + 1)?;
+                    v.format_recursive(f, depth + 1, options)?;
                 }
                 write!(f, "}}")
             }
@@ -322,7 +457,7 @@ impl PlutusData {
                         if i > 0 {
                             write!(f, ", ")?;
                         }
-                        field.format_recursive(f, depth + 1)?;
+                        field.format_recursive(f, depth + 1, options)?;
                     }
                     write!(f, "]")?;
                 }
@@ -331,6 +466,17 @@ impl PlutusData {
         }
     }
 
+    /// Short name of the variant, for diagnostics
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            PlutusData::Constr { .. } => "constr",
+            PlutusData::Map(_) => "map",
+            PlutusData::List(_) => "list",
+            PlutusData::Integer(_) => "int",
+            PlutusData::Bytes(_) => "bytes",
+        }
+    }
+
     /// Get the constructor tag if this is a Constr
     pub fn as_constr_tag(&self) -> Option<u64> {
         match self {
@@ -378,11 +524,139 @@ impl PlutusData {
             _ => None,
         }
     }
+
+    /// Convert from the "detailed schema" JSON representation of PlutusData
+    ///
+    /// This is the format returned by Blockfrost's datum endpoints and cardano-cli:
+    /// `{"constructor": 0, "fields": [...]}`, `{"map": [{"k": ..., "v": ...}]}`,
+    /// `{"list": [...]}`, `{"int": 42}` and `{"bytes": "cafe"}`.
+    ///
+    /// JSON numbers are only read exactly within the 64-bit integer range, since
+    /// anything larger has already been rounded to an `f64` by the JSON parser; such
+    /// integers are rejected. Larger integers can be given as strings
+    /// (`{"int": "340282366920938463463374607431768211455"}`), up to the `i128` range.
+    pub fn from_detailed_json(value: &serde_json::Value) -> Result<Self> {
+        let invalid = || crate::Error::parser(format!("Invalid detailed schema JSON: {}", value));
+
+        let obj = value.as_object().ok_or_else(invalid)?;
+
+        if let Some(constructor) = obj.get("constructor") {
+            let tag = constructor.as_u64().ok_or_else(invalid)?;
+            let fields = obj
+                .get("fields")
+                .and_then(|f| f.as_array())
+                .ok_or_else(invalid)?
+                .iter()
+                .map(Self::from_detailed_json)
+                .collect::<Result<Vec<_>>>()?;
+            Ok(PlutusData::Constr { tag, fields })
+        } else if let Some(entries) = obj.get("map") {
+            let pairs = entries
+                .as_array()
+                .ok_or_else(invalid)?
+                .iter()
+                .map(|entry| {
+                    let key = entry.get("k").ok_or_else(invalid)?;
+                    let val = entry.get("v").ok_or_else(invalid)?;
+                    Ok((
+                        Self::from_detailed_json(key)?,
+                        Self::from_detailed_json(val)?,
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(PlutusData::Map(pairs))
+        } else if let Some(items) = obj.get("list") {
+            let items = items
+                .as_array()
+                .ok_or_else(invalid)?
+                .iter()
+                .map(Self::from_detailed_json)
+                .collect::<Result<Vec<_>>>()?;
+            Ok(PlutusData::List(items))
+        } else if let Some(int) = obj.get("int") {
+            let n = if let Some(n) = int.as_i64() {
+                i128::from(n)
+            } else if let Some(n) = int.as_u64() {
+                i128::from(n)
+            } else if let Some(text) = int.as_str() {
+                text.parse::<i128>().map_err(|_| invalid())?
+            } else if int.is_f64() {
+                return Err(crate::Error::parser(format!(
+                    "Integer {} in detailed schema JSON is outside the 64-bit range and \
+                     can't be read exactly",
+                    int
+                )));
+            } else {
+                return Err(invalid());
+            };
+            Ok(PlutusData::Integer(n))
+        } else if let Some(bytes) = obj.get("bytes") {
+            let bytes = hex::decode(bytes.as_str().ok_or_else(invalid)?).map_err(|_| invalid())?;
+            Ok(PlutusData::Bytes(bytes))
+        } else {
+            Err(invalid())
+        }
+    }
 }
 
 impl std::fmt::Display for PlutusData {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.format_recursive(f, 0)
+        self.format_recursive(f, 0, HumanReadableOptions::default())
+    }
+}
+
+/// Integers rendered as POSIX times when they fall in this window of seconds since
+/// the epoch (mid 2017 to early 2049), or the same window in milliseconds
+const POSIX_TIME_WINDOW_SECS: std::ops::Range<i128> = 1_500_000_000..2_500_000_000;
+
+const POSIX_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S UTC";
+
+/// Format an integer known to be a POSIX time, e.g. from a schema field
+///
+/// Unlike the guess made for untyped integers any value is accepted; it is read as
+/// milliseconds when it would otherwise be past the year 5000. `None` when out of
+/// the representable range.
+pub(crate) fn format_posix_time(n: i128) -> Option<String> {
+    let n = i64::try_from(n).ok()?;
+    let datetime = if n.unsigned_abs() >= 100_000_000_000 {
+        DateTime::from_timestamp_millis(n)
+    } else {
+        DateTime::from_timestamp(n, 0)
+    }?;
+    Some(datetime.format(POSIX_TIME_FORMAT).to_string())
+}
+
+/// Interpret an integer as a POSIX time in seconds or, as Plutus `POSIXTime` is,
+/// in milliseconds
+fn posix_time(n: i128) -> Option<DateTime<chrono::Utc>> {
+    if POSIX_TIME_WINDOW_SECS.contains(&n) {
+        DateTime::from_timestamp(n as i64, 0)
+    } else if POSIX_TIME_WINDOW_SECS.contains(&(n / 1000)) {
+        DateTime::from_timestamp_millis(n as i64)
+    } else {
+        None
+    }
+}
+
+/// Rendering options for [`PlutusData::to_human_readable_with`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HumanReadableOptions {
+    /// Show 28-byte bytestrings as CIP-5 `addr_vkh` bech32 instead of `PubKeyHash(hex)`
+    pub bech32_keys: bool,
+}
+
+/// CIP-5 prefix for a payment verification key hash
+const VKEY_HASH_HRP: bech32::Hrp = bech32::Hrp::parse_unchecked("addr_vkh");
+
+/// [`PlutusData`] paired with the options to display it with
+struct HumanReadable<'a> {
+    data: &'a PlutusData,
+    options: HumanReadableOptions,
+}
+
+impl std::fmt::Display for HumanReadable<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.data.format_recursive(f, 0, self.options)
     }
 }
 
@@ -398,6 +672,83 @@ mod tests {
         assert_eq!(result, PlutusData::Integer(42));
     }
 
+    #[test]
+    fn test_integer_round_trip() {
+        let values = [
+            0,
+            -1,
+            u64::MAX as i128,
+            -(1i128 << 63),
+            -(1i128 << 64), // smallest CBOR major type 1 integer
+            1i128 << 64,    // first value that needs a bignum
+            -(1i128 << 64) - 1,
+            1i128 << 100,
+            i128::MAX,
+            i128::MIN,
+        ];
+        for value in values {
+            let data = PlutusData::Integer(value);
+            let cbor = encode_plutus_data(&data).unwrap();
+            assert_eq!(decode_plutus_data(&cbor).unwrap(), data, "{}", value);
+        }
+
+        // u64::MAX stays a plain major type 0 integer
+        let cbor = encode_plutus_data(&PlutusData::Integer(u64::MAX as i128)).unwrap();
+        assert_eq!(cbor, [0x1b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+    }
+
+    /// Encode `data`, check it decodes back unchanged and return the encoding
+    fn assert_round_trip(data: &PlutusData) -> Vec<u8> {
+        let cbor = encode_plutus_data(data).unwrap();
+        assert_eq!(
+            &decode_plutus_data(&cbor).unwrap(),
+            data,
+            "{}",
+            hex::encode(&cbor)
+        );
+        cbor
+    }
+
+    #[test]
+    fn test_constructor_round_trip() {
+        for tag in [0, 6, 7, 127, 128, 200, u64::MAX] {
+            assert_round_trip(&PlutusData::Constr {
+                tag,
+                fields: vec![PlutusData::Integer(1), PlutusData::Bytes(vec![0xab])],
+            });
+        }
+
+        // Tag 102 wrapping [200, [42]]
+        let cbor = assert_round_trip(&PlutusData::Constr {
+            tag: 200,
+            fields: vec![PlutusData::Integer(42)],
+        });
+        assert_eq!(cbor, [0xd8, 0x66, 0x82, 0x18, 0xc8, 0x9f, 0x18, 0x2a, 0xff]);
+    }
+
+    #[test]
+    fn test_decode_bignum() {
+        // Tag 2 with 2^64 as a 9-byte big-endian magnitude
+        let cbor = [0xc2, 0x49, 0x01, 0, 0, 0, 0, 0, 0, 0, 0];
+        assert_eq!(
+            decode_plutus_data(&cbor).unwrap(),
+            PlutusData::Integer(1i128 << 64)
+        );
+
+        // Tag 3 encodes -1 - n
+        let cbor = [0xc3, 0x49, 0x01, 0, 0, 0, 0, 0, 0, 0, 0];
+        assert_eq!(
+            decode_plutus_data(&cbor).unwrap(),
+            PlutusData::Integer(-(1i128 << 64) - 1)
+        );
+
+        // 2^128 does not fit in an i128
+        let mut cbor = vec![0xc2, 0x51, 0x01];
+        cbor.extend([0u8; 16]);
+        let err = decode_plutus_data(&cbor).unwrap_err();
+        assert!(err.to_string().contains("exceeds"), "{}", err);
+    }
+
     #[test]
     fn test_decode_bytes() {
         // CBOR encoding of byte string "hello"
@@ -406,6 +757,36 @@ mod tests {
         assert_eq!(result, PlutusData::Bytes(b"hello".to_vec()));
     }
 
+    #[test]
+    fn test_decode_chunked_bytes() {
+        // Indefinite byte string: "hel" and "lo" chunks, then break
+        let cbor = [0x5f, 0x43, 0x68, 0x65, 0x6c, 0x42, 0x6c, 0x6f, 0xff];
+        assert_eq!(
+            decode_plutus_data(&cbor).unwrap(),
+            PlutusData::Bytes(b"hello".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_decode_text_string() {
+        // Text string "hi"
+        let cbor = [0x62, 0x68, 0x69];
+        assert_eq!(
+            decode_plutus_data(&cbor).unwrap(),
+            PlutusData::Bytes(b"hi".to_vec())
+        );
+
+        // Indefinite text string: "h" and "i" chunks, then break
+        let cbor = [0x7f, 0x61, 0x68, 0x61, 0x69, 0xff];
+        assert_eq!(
+            decode_plutus_data(&cbor).unwrap(),
+            PlutusData::Bytes(b"hi".to_vec())
+        );
+
+        // Invalid UTF-8 is rejected
+        assert!(decode_plutus_data(&[0x61, 0xff]).is_err());
+    }
+
     #[test]
     fn test_decode_list() {
         // CBOR encoding of [1, 2, 3]
@@ -421,6 +802,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_decode_encoded_cbor_tag() {
+        // Tag 24 wrapping the bytestring 0x182a (the integer 42)
+        let cbor = [0xd8, 0x18, 0x42, 0x18, 0x2a];
+        assert_eq!(decode_plutus_data(&cbor).unwrap(), PlutusData::Integer(42));
+    }
+
+    #[test]
+    fn test_decode_set_tag() {
+        // Tag 258 wrapping [1, 2]
+        let cbor = [0xd9, 0x01, 0x02, 0x82, 0x01, 0x02];
+        assert_eq!(
+            decode_plutus_data(&cbor).unwrap(),
+            PlutusData::List(vec![PlutusData::Integer(1), PlutusData::Integer(2)])
+        );
+
+        // Sets must wrap an array
+        let cbor = [0xd9, 0x01, 0x02, 0x01];
+        assert!(decode_plutus_data(&cbor).is_err());
+    }
+
     #[test]
     fn test_to_human_readable_pubkeyhash() {
         let bytes = vec![0xaa; 28];
@@ -429,12 +831,41 @@ mod tests {
         assert!(readable.starts_with("PubKeyHash("));
     }
 
+    #[test]
+    fn test_to_human_readable_bech32_key_hash() {
+        let hash = hex::decode("1c12f03c1ef2e935acc35ec2e6f96c650fd3bfba3e96550504d53361").unwrap();
+        let data = PlutusData::List(vec![PlutusData::Bytes(hash)]);
+        let options = HumanReadableOptions { bech32_keys: true };
+
+        assert_eq!(
+            data.to_human_readable_with(options),
+            "[addr_vkh1rsf0q0q77t5nttxrtmpwd7tvv58a80a686t92pgy65ekz280xdc]"
+        );
+        // The default rendering is unchanged
+        assert_eq!(
+            data.to_human_readable_with(HumanReadableOptions::default()),
+            data.to_human_readable()
+        );
+        assert!(data.to_human_readable().starts_with("[PubKeyHash(1c12f03c"));
+    }
+
     #[test]
     fn test_to_human_readable_timestamp() {
-        let timestamp = 1705334400i128; // 2024-01-15 12:00:00 UTC
+        let timestamp = 1705334400i128; // 2024-01-15 16:00:00 UTC
         let data = PlutusData::Integer(timestamp);
         let readable = data.to_human_readable();
         assert!(readable.contains("2024-01-15"));
+
+        // Plutus POSIXTime is in milliseconds
+        let data = PlutusData::Integer(1_705_334_400_000);
+        assert_eq!(data.to_human_readable(), "2024-01-15 16:00:00 UTC");
+
+        // Neither window
+        assert_eq!(PlutusData::Integer(42).to_human_readable(), "42");
+        assert_eq!(
+            PlutusData::Integer(100_000_000_000).to_human_readable(),
+            "100000000000"
+        );
     }
 
     #[test]
@@ -481,4 +912,54 @@ mod tests {
             Err(e) => panic!("Failed to decode indefinite array: {}", e),
         }
     }
+
+    #[test]
+    fn test_from_detailed_json() {
+        let json = serde_json::json!({
+            "constructor": 0,
+            "fields": [
+                { "bytes": "aabb" },
+                { "int": 42 },
+                { "list": [{ "int": -1 }] },
+                { "map": [{ "k": { "bytes": "" }, "v": { "int": 7 } }] }
+            ]
+        });
+
+        let data = PlutusData::from_detailed_json(&json).unwrap();
+        assert_eq!(
+            data,
+            PlutusData::Constr {
+                tag: 0,
+                fields: vec![
+                    PlutusData::Bytes(vec![0xaa, 0xbb]),
+                    PlutusData::Integer(42),
+                    PlutusData::List(vec![PlutusData::Integer(-1)]),
+                    PlutusData::Map(vec![(PlutusData::Bytes(vec![]), PlutusData::Integer(7))]),
+                ],
+            }
+        );
+
+        assert!(PlutusData::from_detailed_json(&serde_json::json!({ "unknown": 1 })).is_err());
+        assert!(PlutusData::from_detailed_json(&serde_json::json!({ "bytes": "zz" })).is_err());
+
+        // Beyond u64 the number has been rounded by the JSON parser, so it's refused
+        let json: serde_json::Value =
+            serde_json::from_str(r#"{"int": 100000000000000000000}"#).unwrap();
+        let err = PlutusData::from_detailed_json(&json).unwrap_err();
+        assert!(
+            err.to_string().contains("outside the 64-bit range"),
+            "{}",
+            err
+        );
+        let json = serde_json::json!({ "int": "100000000000000000000" });
+        assert_eq!(
+            PlutusData::from_detailed_json(&json).unwrap(),
+            PlutusData::Integer(100_000_000_000_000_000_000)
+        );
+        let json = serde_json::json!({ "int": u64::MAX });
+        assert_eq!(
+            PlutusData::from_detailed_json(&json).unwrap(),
+            PlutusData::Integer(u64::MAX as i128)
+        );
+    }
 }
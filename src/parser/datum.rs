@@ -3,8 +3,8 @@
 //! This module handles extracting and parsing datums from transaction outputs.
 
 use crate::{
-    Result,
-    data_source::{Datum, Transaction, TxOutput, Witnesses},
+    Error, Result,
+    data_source::{Datum, Transaction, TxOutput, Witnesses, blake2b_256},
 };
 
 /// Datum extractor for extracting datums from transaction outputs
@@ -63,9 +63,8 @@ impl DatumExtractor {
     ) -> Result<Option<Datum>> {
         for datum in &witnesses.datums {
             if datum.hash == datum_hash {
-                if self.validate_hashes && !datum.raw_cbor.is_empty() {
-                    // TODO: Validate that hash matches CBOR content
-                    //  For now, trust the provided hash
+                if self.validate_hashes {
+                    validate_datum_hash(datum)?;
                 }
                 return Ok(Some(datum.clone()));
             }
@@ -105,6 +104,7 @@ impl DatumExtractor {
             let parsed_datum = crate::parser::ParsedDatum {
                 raw: plutus_data,
                 fields: std::collections::HashMap::new(),
+                schema_mismatch: None,
             };
 
             // Store parsed data in datum
@@ -131,12 +131,21 @@ pub fn extract_datum(output: &TxOutput, witnesses: Option<&Witnesses>) -> Result
 }
 
 /// Validate that a datum hash matches its CBOR content
+///
+/// The hash is the Blake2b-256 digest of the CBOR bytes. Returns `Ok(false)` when
+/// there is no CBOR to check and an error naming both hashes on a mismatch.
 pub fn validate_datum_hash(datum: &Datum) -> Result<bool> {
     if datum.raw_cbor.is_empty() {
         return Ok(false);
     }
 
-    // TODO: Implement proper hash validation
+    let actual = blake2b_256(&datum.raw_cbor);
+    if !actual.eq_ignore_ascii_case(&datum.hash) {
+        return Err(Error::parser(format!(
+            "Datum hash mismatch: declared {}, CBOR hashes to {}",
+            datum.hash, actual
+        )));
+    }
 
     Ok(true)
 }
@@ -152,6 +161,7 @@ mod tests {
             hash: "test_hash_123".to_string(),
             raw_cbor: vec![0x18, 0x2a], // CBOR encoding of integer 42
             parsed: None,
+            reencoded: false,
         };
 
         TxOutput {
@@ -162,11 +172,17 @@ mod tests {
         }
     }
 
+    /// Blake2b-256 of the witness datum's CBOR (integer 1)
+    fn witness_datum_hash() -> String {
+        blake2b_256(&[0x01])
+    }
+
     fn create_test_output_with_datum_hash() -> TxOutput {
         let datum = Datum {
-            hash: "datum_hash_456".to_string(),
+            hash: witness_datum_hash(),
             raw_cbor: vec![], // Empty - needs witness lookup
             parsed: None,
+            reencoded: false,
         };
 
         TxOutput {
@@ -179,9 +195,10 @@ mod tests {
 
     fn create_test_witnesses() -> Witnesses {
         let datum = Datum {
-            hash: "datum_hash_456".to_string(),
+            hash: witness_datum_hash(),
             raw_cbor: vec![0x01], // CBOR encoding of integer 1
             parsed: None,
+            reencoded: false,
         };
 
         Witnesses {
@@ -215,7 +232,7 @@ mod tests {
         assert!(result.is_some());
 
         let datum = result.unwrap();
-        assert_eq!(datum.hash, "datum_hash_456");
+        assert_eq!(datum.hash, witness_datum_hash());
         assert_eq!(datum.raw_cbor, vec![0x01]);
     }
 
@@ -275,6 +292,8 @@ mod tests {
             ],
             witnesses: create_test_witnesses(),
             metadata: None,
+            size: None,
+            valid: true,
         };
 
         let datums = extractor.extract_all_datums(&tx).unwrap();
@@ -283,6 +302,46 @@ mod tests {
         assert_eq!(datums[1].0, 2); // Third output
     }
 
+    #[test]
+    fn test_validate_datum_hash() {
+        let datum = Datum {
+            hash: witness_datum_hash(),
+            raw_cbor: vec![0x01],
+            parsed: None,
+            reencoded: false,
+        };
+        assert!(validate_datum_hash(&datum).unwrap());
+
+        let wrong = Datum {
+            hash: "00".repeat(32),
+            ..datum.clone()
+        };
+        let err = validate_datum_hash(&wrong).unwrap_err().to_string();
+        assert!(err.contains(&"00".repeat(32)));
+        assert!(err.contains(&witness_datum_hash()));
+
+        let empty = Datum {
+            raw_cbor: vec![],
+            ..datum
+        };
+        assert!(!validate_datum_hash(&empty).unwrap());
+    }
+
+    #[test]
+    fn test_witness_datum_hash_mismatch() {
+        let output = create_test_output_with_datum_hash();
+        let mut witnesses = create_test_witnesses();
+        witnesses.datums[0].raw_cbor = vec![0x02];
+
+        let result = DatumExtractor::new().extract_datum(&output, Some(&witnesses));
+        assert!(result.unwrap_err().to_string().contains("mismatch"));
+
+        let result = DatumExtractor::without_validation()
+            .extract_datum(&output, Some(&witnesses))
+            .unwrap();
+        assert_eq!(result.unwrap().raw_cbor, vec![0x02]);
+    }
+
     #[test]
     fn test_output_without_datum() {
         let extractor = DatumExtractor::new();
@@ -17,6 +17,9 @@ pub use cbor::PlutusData;
 pub struct ParsedDatum {
     pub raw: PlutusData,
     pub fields: HashMap<String, String>,
+    /// Why the datum did not match the active schema, if it didn't
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_mismatch: Option<String>,
 }
 
 /// Parsed redeemer
@@ -32,16 +35,38 @@ pub trait Parser {
     fn parse_redeemer(&self, raw: &[u8]) -> Result<ParsedRedeemer>;
 }
 
+/// Label the elements of a constructor or list as `field_0`, `field_1`, ...
+///
+/// Nested constructors and lists are labeled too, with dotted names such as
+/// `field_1.field_0`.
+fn generic_fields(data: &PlutusData, prefix: Option<&str>, fields: &mut HashMap<String, String>) {
+    let items = match data {
+        PlutusData::Constr { fields, .. } => fields,
+        PlutusData::List(items) => items,
+        _ => return,
+    };
+    for (i, item) in items.iter().enumerate() {
+        let name = match prefix {
+            Some(prefix) => format!("{}.field_{}", prefix, i),
+            None => format!("field_{}", i),
+        };
+        fields.insert(name.clone(), item.to_human_readable());
+        generic_fields(item, Some(&name), fields);
+    }
+}
+
 /// Generic parser (no schema)
 pub struct GenericParser;
 
 impl Parser for GenericParser {
     fn parse_datum(&self, raw: &[u8]) -> Result<ParsedDatum> {
         let plutus_data = cbor::decode_plutus_data(raw)?;
-        // TODO: Generate generic field names (field_0, field_1, ...)
+        let mut fields = HashMap::new();
+        generic_fields(&plutus_data, None, &mut fields);
         Ok(ParsedDatum {
             raw: plutus_data,
-            fields: HashMap::new(),
+            fields,
+            schema_mismatch: None,
         })
     }
 
@@ -53,3 +78,63 @@ impl Parser for GenericParser {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cbor::encode_plutus_data;
+
+    #[test]
+    fn test_generic_fields_flat_constructor() {
+        let datum = PlutusData::Constr {
+            tag: 0,
+            fields: vec![PlutusData::Integer(42), PlutusData::Bytes(vec![0xAA])],
+        };
+        let parsed = GenericParser
+            .parse_datum(&encode_plutus_data(&datum).unwrap())
+            .unwrap();
+
+        assert_eq!(parsed.fields.len(), 2);
+        assert_eq!(parsed.fields["field_0"], "42");
+        assert_eq!(
+            parsed.fields["field_1"],
+            PlutusData::Bytes(vec![0xAA]).to_human_readable()
+        );
+    }
+
+    #[test]
+    fn test_generic_fields_nested_constructor() {
+        let inner = PlutusData::Constr {
+            tag: 1,
+            fields: vec![PlutusData::Integer(7)],
+        };
+        let datum = PlutusData::Constr {
+            tag: 0,
+            fields: vec![
+                PlutusData::Integer(1),
+                inner.clone(),
+                PlutusData::List(vec![PlutusData::Integer(2), PlutusData::Integer(3)]),
+            ],
+        };
+        let parsed = GenericParser
+            .parse_datum(&encode_plutus_data(&datum).unwrap())
+            .unwrap();
+
+        let mut names: Vec<_> = parsed.fields.keys().map(String::as_str).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                "field_0",
+                "field_1",
+                "field_1.field_0",
+                "field_2",
+                "field_2.field_0",
+                "field_2.field_1"
+            ]
+        );
+        assert_eq!(parsed.fields["field_1"], inner.to_human_readable());
+        assert_eq!(parsed.fields["field_1.field_0"], "7");
+        assert_eq!(parsed.fields["field_2.field_1"], "3");
+    }
+}
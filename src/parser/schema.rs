@@ -13,11 +13,11 @@ use std::collections::HashMap;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContractSchema {
     pub contract: ContractInfo,
-    pub datum: DatumSchema,
+    pub datum: DatumDefinition,
     pub redeemer: Vec<RedeemerSchema>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub states: HashMap<String, StateRule>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub transitions: HashMap<String, TransitionStyle>,
 }
 
@@ -40,6 +40,15 @@ pub struct ContractInfo {
     pub script_address: String,
 }
 
+/// Datum layout: a single `[datum]` table, or a `[[datum]]` entry per constructor
+/// of a sum type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DatumDefinition {
+    Single(DatumSchema),
+    Variants(Vec<DatumVariant>),
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatumSchema {
     #[serde(rename = "type")]
@@ -49,6 +58,32 @@ pub struct DatumSchema {
     pub fields: Vec<FieldDef>,
 }
 
+/// One constructor of a sum-type datum
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatumVariant {
+    pub name: String,
+    #[serde(default)]
+    pub constructor_index: u64,
+    #[serde(default)]
+    pub fields: Vec<FieldDef>,
+}
+
+impl DatumDefinition {
+    /// Fields expected for a datum with constructor `tag`, and the variant's name
+    /// when the datum is a sum type
+    pub fn constructor(&self, tag: u64) -> Option<(&[FieldDef], Option<&str>)> {
+        match self {
+            DatumDefinition::Single(datum) => (datum.datum_type == "constr"
+                && datum.constructor_index == tag)
+                .then_some((datum.fields.as_slice(), None)),
+            DatumDefinition::Variants(variants) => variants
+                .iter()
+                .find(|variant| variant.constructor_index == tag)
+                .map(|variant| (variant.fields.as_slice(), Some(variant.name.as_str()))),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FieldDef {
     pub name: String,
@@ -67,7 +102,8 @@ impl ContractSchema {
     pub fn from_file(path: impl Into<PathBuf>) -> Result<Self> {
         let path = path.into();
         let contents = std::fs::read_to_string(&path)?;
-        let schema: ContractSchema = toml::from_str(&contents)?;
+        let schema: ContractSchema = toml::from_str(&contents)
+            .map_err(|e| crate::Error::schema_parse(&path, &contents, &e))?;
         Ok(schema)
     }
 }
@@ -197,38 +233,80 @@ impl SchemaParser {
     }
 }
 
+impl SchemaParser {
+    /// Explain where a decoded datum diverges from the datum schema
+    ///
+    /// Returns `None` when the datum's shape matches the schema.
+    fn datum_mismatch(&self, data: &crate::parser::PlutusData) -> Option<String> {
+        let definition = &self.schema.datum;
+        if let DatumDefinition::Single(expected) = definition
+            && expected.datum_type != "constr"
+        {
+            return Some(format!(
+                "schema datum type '{}' is not supported (only 'constr')",
+                expected.datum_type
+            ));
+        }
+
+        let crate::parser::PlutusData::Constr { tag, fields } = data else {
+            return Some(format!("expected constr, datum is {}", data.kind_name()));
+        };
+        let Some((expected_fields, variant)) = definition.constructor(*tag) else {
+            return Some(match definition {
+                DatumDefinition::Single(expected) => format!(
+                    "expected constr index {}, datum has index {}",
+                    expected.constructor_index, tag
+                ),
+                DatumDefinition::Variants(_) => {
+                    format!("no datum variant has constr index {}", tag)
+                }
+            });
+        };
+
+        if fields.len() == expected_fields.len() {
+            return None;
+        }
+        Some(match variant {
+            Some(name) => format!(
+                "variant '{}' expects {} fields, datum has {}",
+                name,
+                expected_fields.len(),
+                fields.len()
+            ),
+            None => format!(
+                "schema expects {} fields, datum has {}",
+                expected_fields.len(),
+                fields.len()
+            ),
+        })
+    }
+}
+
 impl Parser for SchemaParser {
     fn parse_datum(&self, raw: &[u8]) -> Result<ParsedDatum> {
         let raw_data = crate::parser::cbor::decode_plutus_data(raw)?;
         let mut fields = HashMap::new();
 
-        // Check if schema matches
+        let schema_mismatch = self.datum_mismatch(&raw_data);
+
         // Currently only supporting top-level Constr matches
-        if self.schema.datum.datum_type == "constr"
-            && let crate::parser::PlutusData::Constr {
-                tag,
-                fields: data_fields,
-            } = &raw_data
+        if let crate::parser::PlutusData::Constr {
+            tag,
+            fields: data_fields,
+        } = &raw_data
+            && let Some((field_defs, variant)) = self.schema.datum.constructor(*tag)
         {
-            // Check constructor tag (index)
-            if *tag == self.schema.datum.constructor_index {
-                // Match fields
-                for (i, field_def) in self.schema.datum.fields.iter().enumerate() {
-                    if let Some(val) = data_fields.get(i) {
-                        let val_str = match field_def.field_type.as_str() {
-                            "int" => val
-                                .as_integer()
-                                .map(|v| v.to_string())
-                                .unwrap_or_else(|| val.to_human_readable()),
-                            "bytes" => val
-                                .as_bytes()
-                                .map(hex::encode)
-                                .unwrap_or_else(|| val.to_human_readable()),
-                            // Fallback for other types
-                            _ => val.to_human_readable(),
-                        };
-                        fields.insert(field_def.name.clone(), val_str);
-                    }
+            if let Some(variant) = variant {
+                fields.insert("_variant".to_string(), variant.to_string());
+            }
+
+            // Match fields
+            for (i, field_def) in field_defs.iter().enumerate() {
+                if let Some(val) = data_fields.get(i) {
+                    fields.insert(
+                        field_def.name.clone(),
+                        format_field(&field_def.field_type, val),
+                    );
                 }
             }
         }
@@ -236,6 +314,7 @@ impl Parser for SchemaParser {
         Ok(ParsedDatum {
             raw: raw_data,
             fields,
+            schema_mismatch,
         })
     }
 
@@ -260,6 +339,30 @@ impl Parser for SchemaParser {
     }
 }
 
+/// Render a datum field as its schema type, falling back to the generic
+/// human-readable form when the value doesn't have that type
+fn format_field(field_type: &str, val: &crate::parser::PlutusData) -> String {
+    use crate::parser::PlutusData;
+
+    let typed = match (field_type, val) {
+        ("int", PlutusData::Integer(n)) => Some(n.to_string()),
+        ("bytes", PlutusData::Bytes(b)) => Some(hex::encode(b)),
+        ("posix_time", PlutusData::Integer(n)) => crate::parser::cbor::format_posix_time(*n),
+        ("pubkeyhash", PlutusData::Bytes(b)) => Some(format!("PubKeyHash({})", hex::encode(b))),
+        // Plutus encodes Bool as a nullary constructor: False = 0, True = 1
+        ("bool", PlutusData::Integer(n @ (0 | 1))) => Some((*n == 1).to_string()),
+        (
+            "bool",
+            PlutusData::Constr {
+                tag: n @ (0 | 1),
+                fields,
+            },
+        ) if fields.is_empty() => Some((*n == 1).to_string()),
+        _ => None,
+    };
+    typed.unwrap_or_else(|| val.to_human_readable())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -273,7 +376,7 @@ mod tests {
                 description: None,
                 script_address: "addr_test1".to_string(),
             },
-            datum: DatumSchema {
+            datum: DatumDefinition::Single(DatumSchema {
                 datum_type: "constr".to_string(),
                 constructor_index: 0,
                 fields: vec![
@@ -288,7 +391,7 @@ mod tests {
                         desc: None,
                     },
                 ],
-            },
+            }),
             redeemer: vec![
                 RedeemerSchema {
                     name: "Unlock".to_string(),
@@ -334,6 +437,242 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_schema_parse_typed_fields() {
+        use crate::parser::PlutusData;
+        use crate::parser::cbor::encode_plutus_data;
+
+        let field = |name: &str, field_type: &str| FieldDef {
+            name: name.to_string(),
+            field_type: field_type.to_string(),
+            desc: None,
+        };
+        let mut schema = create_test_schema();
+        schema.datum = DatumDefinition::Single(DatumSchema {
+            datum_type: "constr".to_string(),
+            constructor_index: 0,
+            fields: vec![
+                field("unlock_time", "posix_time"),
+                field("created_at", "posix_time"),
+                field("beneficiary", "pubkeyhash"),
+                field("cancelled", "bool"),
+                field("claimed", "bool"),
+                field("not_a_key", "pubkeyhash"),
+            ],
+        });
+        let parser = SchemaParser::new(schema);
+
+        let datum = PlutusData::Constr {
+            tag: 0,
+            fields: vec![
+                PlutusData::Integer(1_705_334_400_000),
+                PlutusData::Integer(1_705_334_400),
+                PlutusData::Bytes(vec![0xab; 28]),
+                PlutusData::Integer(0),
+                PlutusData::Constr {
+                    tag: 1,
+                    fields: vec![],
+                },
+                PlutusData::Integer(5),
+            ],
+        };
+        let parsed = parser
+            .parse_datum(&encode_plutus_data(&datum).unwrap())
+            .unwrap();
+
+        let field = |name: &str| parsed.fields.get(name).map(String::as_str);
+        assert_eq!(field("unlock_time"), Some("2024-01-15 16:00:00 UTC"));
+        assert_eq!(field("created_at"), Some("2024-01-15 16:00:00 UTC"));
+        let key_hash = format!("PubKeyHash({})", "ab".repeat(28));
+        assert_eq!(field("beneficiary"), Some(key_hash.as_str()));
+        assert_eq!(field("cancelled"), Some("false"));
+        assert_eq!(field("claimed"), Some("true"));
+        // Values that don't match the declared type render generically
+        assert_eq!(field("not_a_key"), Some("5"));
+    }
+
+    #[test]
+    fn test_schema_parse_datum_mismatch() {
+        use crate::parser::PlutusData;
+        use crate::parser::cbor::encode_plutus_data;
+
+        let parser = SchemaParser::new(create_test_schema());
+
+        let matching = PlutusData::Constr {
+            tag: 0,
+            fields: vec![PlutusData::Integer(42), PlutusData::Bytes(vec![0xAA])],
+        };
+        let parsed = parser
+            .parse_datum(&encode_plutus_data(&matching).unwrap())
+            .unwrap();
+        assert_eq!(parsed.schema_mismatch, None);
+
+        let wrong_tag = PlutusData::Constr {
+            tag: 2,
+            fields: vec![],
+        };
+        let parsed = parser
+            .parse_datum(&encode_plutus_data(&wrong_tag).unwrap())
+            .unwrap();
+        assert!(parsed.fields.is_empty());
+        assert_eq!(
+            parsed.schema_mismatch.as_deref(),
+            Some("expected constr index 0, datum has index 2")
+        );
+
+        let wrong_arity = PlutusData::Constr {
+            tag: 0,
+            fields: vec![PlutusData::Integer(42)],
+        };
+        let parsed = parser
+            .parse_datum(&encode_plutus_data(&wrong_arity).unwrap())
+            .unwrap();
+        assert_eq!(
+            parsed.schema_mismatch.as_deref(),
+            Some("schema expects 2 fields, datum has 1")
+        );
+
+        let not_constr = PlutusData::Integer(7);
+        let parsed = parser
+            .parse_datum(&encode_plutus_data(&not_constr).unwrap())
+            .unwrap();
+        assert_eq!(
+            parsed.schema_mismatch.as_deref(),
+            Some("expected constr, datum is int")
+        );
+    }
+
+    const SUM_TYPE_SCHEMA: &str = r#"
+[contract]
+name = "Channel"
+script_address = "addr_test1"
+
+[[datum]]
+name = "Active"
+constructor_index = 0
+fields = [
+    { name = "owner", type = "bytes" },
+    { name = "balance", type = "int" },
+]
+
+[[datum]]
+name = "Closed"
+constructor_index = 1
+fields = [{ name = "closed_at", type = "int" }]
+
+[[redeemer]]
+name = "Close"
+constructor_index = 0
+"#;
+
+    #[test]
+    fn test_schema_parse_active_variant() {
+        use crate::parser::PlutusData;
+        use crate::parser::cbor::encode_plutus_data;
+
+        let schema: ContractSchema = toml::from_str(SUM_TYPE_SCHEMA).unwrap();
+        assert!(matches!(schema.datum, DatumDefinition::Variants(ref v) if v.len() == 2));
+        let parser = SchemaParser::new(schema);
+
+        let active = PlutusData::Constr {
+            tag: 0,
+            fields: vec![PlutusData::Bytes(vec![0xAA]), PlutusData::Integer(100)],
+        };
+        let parsed = parser
+            .parse_datum(&encode_plutus_data(&active).unwrap())
+            .unwrap();
+        assert_eq!(parsed.schema_mismatch, None);
+        assert_eq!(parsed.fields["_variant"], "Active");
+        assert_eq!(parsed.fields["owner"], "aa");
+        assert_eq!(parsed.fields["balance"], "100");
+    }
+
+    #[test]
+    fn test_schema_parse_closed_variant() {
+        use crate::parser::PlutusData;
+        use crate::parser::cbor::encode_plutus_data;
+
+        let parser = SchemaParser::new(toml::from_str(SUM_TYPE_SCHEMA).unwrap());
+
+        let closed = PlutusData::Constr {
+            tag: 1,
+            fields: vec![PlutusData::Integer(1_700_000)],
+        };
+        let parsed = parser
+            .parse_datum(&encode_plutus_data(&closed).unwrap())
+            .unwrap();
+        assert_eq!(parsed.schema_mismatch, None);
+        assert_eq!(parsed.fields["_variant"], "Closed");
+        assert_eq!(parsed.fields["closed_at"], "1700000");
+        assert!(!parsed.fields.contains_key("owner"));
+
+        let wrong_arity = PlutusData::Constr {
+            tag: 1,
+            fields: vec![],
+        };
+        let parsed = parser
+            .parse_datum(&encode_plutus_data(&wrong_arity).unwrap())
+            .unwrap();
+        assert_eq!(
+            parsed.schema_mismatch.as_deref(),
+            Some("variant 'Closed' expects 1 fields, datum has 0")
+        );
+
+        let unknown = PlutusData::Constr {
+            tag: 2,
+            fields: vec![],
+        };
+        let parsed = parser
+            .parse_datum(&encode_plutus_data(&unknown).unwrap())
+            .unwrap();
+        assert!(parsed.fields.is_empty());
+        assert_eq!(
+            parsed.schema_mismatch.as_deref(),
+            Some("no datum variant has constr index 2")
+        );
+    }
+
+    #[test]
+    fn test_schema_single_datum_table_still_parses() {
+        let schema: ContractSchema = toml::from_str(
+            r#"
+[contract]
+name = "Vesting"
+script_address = "addr_test1"
+
+[datum]
+type = "constr"
+fields = [{ name = "beneficiary", type = "bytes" }]
+
+[[redeemer]]
+name = "Unlock"
+constructor_index = 0
+"#,
+        )
+        .unwrap();
+        let DatumDefinition::Single(datum) = schema.datum else {
+            panic!("expected a single datum constructor");
+        };
+        assert_eq!(datum.fields.len(), 1);
+    }
+
+    #[test]
+    fn test_schema_from_file_reports_location() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("broken-schema.toml");
+        std::fs::write(
+            &path,
+            "[contract]\nname = \"Broken\"\nscript_address = \"addr_test1\"\n\n[datum]\ntype = constr\n",
+        )
+        .unwrap();
+
+        let err = ContractSchema::from_file(&path).unwrap_err();
+
+        assert!(matches!(err, crate::Error::SchemaParse { ref file, .. } if file == &path));
+        let message = err.to_string();
+        assert!(message.contains("line 6"), "{}", message);
+    }
+
     #[test]
     fn test_schema_parse_redeemer() {
         let schema = create_test_schema();
@@ -3,14 +3,22 @@
 //! Analyzes the state machine graph to detect common smart contract patterns
 //! such as linear vesting, branching marketplaces, or cyclic AMMs.
 
-use super::StateGraph;
+use super::{StateGraph, StateId};
 use petgraph::Direction;
+use petgraph::graph::Graph;
+use petgraph::stable_graph::NodeIndex;
+use serde::Serialize;
+use std::collections::HashSet;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum ContractPattern {
     /// A -> B -> C -> D
     Linear,
 
+    /// A linear chain whose locked ADA only ever decreases, e.g. vesting or escrow
+    /// releasing funds in installments
+    Vesting,
+
     /// A -> B
     ///   -> C
     Tree,
@@ -26,6 +34,7 @@ impl ContractPattern {
     pub fn display_name(&self) -> &'static str {
         match self {
             ContractPattern::Linear => "Linear",
+            ContractPattern::Vesting => "Vesting",
             ContractPattern::Tree => "Branching",
             ContractPattern::Cyclic => "Cyclic",
             ContractPattern::Unknown => "Complex/Unknown",
@@ -34,12 +43,15 @@ impl ContractPattern {
 }
 
 /// Analysis report containing pattern and metrics
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct AnalysisReport {
     pub pattern: ContractPattern,
     pub branching_factor: f64,
     pub max_depth: usize,
     pub has_cycles: bool,
+    /// States on each cycle, in the order the cycle is walked from its earliest
+    /// state; cycles are ordered by that state too
+    pub cycles: Vec<Vec<StateId>>,
 }
 
 /// Detect the pattern of a state graph
@@ -52,6 +64,7 @@ pub fn detect_pattern(graph: &StateGraph) -> AnalysisReport {
             branching_factor: 0.0,
             max_depth: 0,
             has_cycles: false,
+            cycles: Vec::new(),
         };
     }
 
@@ -84,7 +97,9 @@ pub fn detect_pattern(graph: &StateGraph) -> AnalysisReport {
             .max()
             .unwrap_or(0);
 
-        if max_out <= 1 {
+        if max_out <= 1 && is_vesting(graph) {
+            ContractPattern::Vesting
+        } else if max_out <= 1 {
             ContractPattern::Linear
         } else {
             ContractPattern::Tree
@@ -96,7 +111,214 @@ pub fn detect_pattern(graph: &StateGraph) -> AnalysisReport {
     AnalysisReport {
         pattern,
         branching_factor,
-        max_depth: 0, // TODO: Calculate depth if needed
+        max_depth: max_depth(graph),
         has_cycles,
+        cycles: if has_cycles {
+            cycles(graph)
+        } else {
+            Vec::new()
+        },
+    }
+}
+
+/// Strongly connected components with more than one state, or a state
+/// transitioning to itself
+///
+/// Each component is walked from its earliest state (by block, slot and id) along
+/// transitions that stay inside it, so a simple cycle comes out in path order; any
+/// states the walk misses are appended in the same earliest-first order.
+fn cycles(graph: &StateGraph) -> Vec<Vec<StateId>> {
+    let position = |node: NodeIndex| {
+        let state = &graph.graph[node];
+        (state.block, state.slot, state.id.clone())
+    };
+
+    let mut cycles: Vec<Vec<NodeIndex>> = petgraph::algo::tarjan_scc(&graph.graph)
+        .into_iter()
+        .filter(|component| {
+            component.len() > 1 || graph.graph.find_edge(component[0], component[0]).is_some()
+        })
+        .map(|mut component| {
+            component.sort_by_key(|&node| position(node));
+            let members: HashSet<NodeIndex> = component.iter().copied().collect();
+            let mut path = vec![component[0]];
+            let mut visited = HashSet::from([component[0]]);
+            while let Some(next) = graph
+                .graph
+                .neighbors_directed(path[path.len() - 1], Direction::Outgoing)
+                .filter(|next| members.contains(next) && !visited.contains(next))
+                .min_by_key(|&next| position(next))
+            {
+                visited.insert(next);
+                path.push(next);
+            }
+            path.extend(component.into_iter().filter(|node| !visited.contains(node)));
+            path
+        })
+        .collect();
+    cycles.sort_by_key(|cycle| position(cycle[0]));
+
+    cycles
+        .into_iter()
+        .map(|cycle| {
+            cycle
+                .into_iter()
+                .map(|node| graph.graph[node].id.clone())
+                .collect()
+        })
+        .collect()
+}
+
+/// Whether the ADA held by the script never increases across a transition and
+/// drops at least once, so a chain of constant-value updates is not reported
+fn is_vesting(graph: &StateGraph) -> bool {
+    let steps: Vec<(u64, u64)> = graph
+        .graph
+        .edge_indices()
+        .filter_map(|edge| graph.graph.edge_endpoints(edge))
+        .map(|(from, to)| (graph.graph[from].ada_value(), graph.graph[to].ada_value()))
+        .collect();
+    steps.iter().all(|(from, to)| to <= from) && steps.iter().any(|(from, to)| to < from)
+}
+
+/// Length (in transitions) of the longest path starting at an initial state
+///
+/// Strongly connected components are collapsed first, so for cyclic graphs this is
+/// the longest path in the condensation.
+fn max_depth(graph: &StateGraph) -> usize {
+    let topology: Graph<(), ()> = graph.graph.map(|_, _| (), |_, _| ()).into();
+    let condensed = petgraph::algo::condensation(topology, true);
+    let Ok(order) = petgraph::algo::toposort(&condensed, None) else {
+        return 0;
+    };
+
+    // Initial states start at depth 0; follow the topological order to push depths forward
+    let mut depth = vec![0usize; condensed.node_count()];
+    for node in order {
+        for next in condensed.neighbors_directed(node, Direction::Outgoing) {
+            depth[next.index()] = depth[next.index()].max(depth[node.index()] + 1);
+        }
+    }
+    depth.into_iter().max().unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_source::{Asset, TxOutput, UtxoRef};
+    use crate::state_machine::{State, Transition};
+
+    fn graph_from_edges(states: usize, edges: &[(usize, usize)]) -> StateGraph {
+        graph_with_values(&vec![1_000_000; states], edges)
+    }
+
+    /// Build a graph whose `i`th state holds `lovelace[i]`
+    fn graph_with_values(lovelace: &[u64], edges: &[(usize, usize)]) -> StateGraph {
+        let mut graph = StateGraph::new("addr_script".to_string());
+        for (i, &lovelace) in lovelace.iter().enumerate() {
+            let output = TxOutput {
+                address: "addr_script".to_string(),
+                amount: vec![Asset::lovelace(lovelace)],
+                datum: None,
+                script_ref: None,
+            };
+            let tx_hash = format!("tx{}", i);
+            graph.add_state(State::new(
+                UtxoRef::new(&tx_hash, 0),
+                None,
+                output,
+                i as u64,
+                i as u64,
+                tx_hash,
+            ));
+        }
+        for (from, to) in edges {
+            graph.add_transition(Transition::new(
+                format!("tx{}#0", from),
+                format!("tx{}#0", to),
+                format!("tx{}", to),
+                None,
+            ));
+        }
+        graph
+    }
+
+    #[test]
+    fn test_max_depth_linear_chain() {
+        let graph = graph_from_edges(4, &[(0, 1), (1, 2), (2, 3)]);
+        let report = detect_pattern(&graph);
+        assert_eq!(report.pattern, ContractPattern::Linear);
+        assert_eq!(report.max_depth, 3);
+    }
+
+    #[tokio::test]
+    async fn test_mock_vesting_flow_detected() {
+        use crate::data_source::mock::{MOCK_SCRIPT_ADDRESS, MockDataSource};
+        use crate::data_source::{DataSource, QueryParams};
+
+        let transactions = MockDataSource::new()
+            .get_transactions_by_address(MOCK_SCRIPT_ADDRESS, QueryParams::default())
+            .await
+            .unwrap();
+        let graph =
+            crate::state_machine::build_state_graph(&transactions, MOCK_SCRIPT_ADDRESS, None)
+                .unwrap();
+
+        assert_eq!(detect_pattern(&graph).pattern, ContractPattern::Vesting);
+    }
+
+    #[test]
+    fn test_increasing_linear_flow_is_not_vesting() {
+        let edges = [(0, 1), (1, 2)];
+        let graph = graph_with_values(&[1_000_000, 2_000_000, 3_000_000], &edges);
+        assert_eq!(detect_pattern(&graph).pattern, ContractPattern::Linear);
+
+        // A single top-up breaks the pattern
+        let graph = graph_with_values(&[3_000_000, 1_000_000, 2_000_000], &edges);
+        assert_eq!(detect_pattern(&graph).pattern, ContractPattern::Linear);
+
+        let graph = graph_with_values(&[3_000_000, 2_000_000, 2_000_000], &edges);
+        assert_eq!(detect_pattern(&graph).pattern, ContractPattern::Vesting);
+    }
+
+    #[test]
+    fn test_max_depth_tree() {
+        // 0 -> 1 -> 3 -> 4
+        //   -> 2
+        let graph = graph_from_edges(5, &[(0, 1), (0, 2), (1, 3), (3, 4)]);
+        let report = detect_pattern(&graph);
+        assert_eq!(report.pattern, ContractPattern::Tree);
+        assert_eq!(report.max_depth, 3);
+    }
+
+    #[test]
+    fn test_max_depth_with_cycle() {
+        // 0 -> 1 -> 2 -> 1 collapses to 0 -> {1, 2} -> 3
+        let graph = graph_from_edges(4, &[(0, 1), (1, 2), (2, 1), (2, 3)]);
+        let report = detect_pattern(&graph);
+        assert!(report.has_cycles);
+        assert_eq!(report.max_depth, 2);
+    }
+
+    #[test]
+    fn test_cycle_paths() {
+        // 0 -> 1 -> 3 -> 2 -> 1, and 4 -> 4
+        let graph = graph_from_edges(5, &[(0, 1), (1, 3), (3, 2), (2, 1), (4, 4)]);
+        let report = detect_pattern(&graph);
+        assert_eq!(report.pattern, ContractPattern::Cyclic);
+        assert_eq!(
+            report.cycles,
+            vec![
+                vec![
+                    "tx1#0".to_string(),
+                    "tx3#0".to_string(),
+                    "tx2#0".to_string()
+                ],
+                vec!["tx4#0".to_string()],
+            ]
+        );
+
+        let graph = graph_from_edges(3, &[(0, 1), (1, 2)]);
+        assert!(detect_pattern(&graph).cycles.is_empty());
     }
 }
@@ -1,17 +1,99 @@
 use crate::Result;
-use crate::data_source::{Transaction, UtxoRef};
+use crate::data_source::{Redeemer, RedeemerTag, Transaction, UtxoRef};
 use crate::parser::schema::SchemaParser;
-use crate::state_machine::{State, StateClass, StateId, Transition};
+use crate::state_machine::{State, StateClass, StateId, Transition, TransitionKind};
 use petgraph::Direction;
 use petgraph::prelude::EdgeRef;
 use petgraph::stable_graph::{EdgeIndex, NodeIndex, StableGraph};
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::path::Path;
+
+/// Extra state classifier supplied by library consumers.
+///
+/// It runs after the topological and schema rules, so the state it receives already
+/// carries their classification. Returning `None` keeps that classification.
+pub type ClassifierFn = dyn Fn(&State, &StateGraph) -> Option<StateClass>;
+
+/// Options for the DOT and Mermaid exports
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DotOptions {
+    /// Route the transitions of a transaction that consumed or created several states
+    /// through one junction node, instead of one edge per (consumed, created) pair
+    ///
+    /// Each consumed state gets an edge into the junction and each created state one
+    /// out of it, so every state stays connected with `n + m` edges instead of `n * m`.
+    pub merge_parallel_edges: bool,
+}
+
+/// One end of an edge in the DOT and Mermaid exports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EdgeEnd<'a> {
+    State(&'a StateId),
+    /// Junction standing for a transaction, see [`DotOptions::merge_parallel_edges`]
+    Junction(&'a str),
+}
+
+/// An edge to draw in the DOT and Mermaid exports
+struct ExportEdge<'a> {
+    from: EdgeEnd<'a>,
+    to: EdgeEnd<'a>,
+    tx_hash: &'a str,
+    /// Empty for the edges leaving a junction
+    label: String,
+}
+
+/// Find the spend redeemer for an input of `tx`
+///
+/// The ledger indexes spend redeemers by the input's position in the transaction's
+/// inputs sorted by tx hash, then output index.
+fn spend_redeemer<'a>(tx: &'a Transaction, utxo_ref: &UtxoRef) -> Option<&'a Redeemer> {
+    let mut sorted_inputs: Vec<&UtxoRef> = tx.inputs.iter().map(|i| &i.utxo_ref).collect();
+    sorted_inputs.sort_by(|a, b| (&a.tx_hash, a.output_index).cmp(&(&b.tx_hash, b.output_index)));
+    let position = sorted_inputs.iter().position(|r| *r == utxo_ref)? as u64;
+
+    tx.witnesses
+        .redeemers
+        .iter()
+        .find(|r| r.tag == RedeemerTag::Spend && r.index == position)
+}
+
+/// Mermaid state id for a state: ids must be plain identifiers, so `tx#0` becomes `s_tx_0`
+fn mermaid_id(state_id: &StateId) -> String {
+    format!("s_{}", mermaid_sanitize(state_id))
+}
+
+fn mermaid_sanitize(id: &str) -> String {
+    id.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Junctions among the ends of `edges`, each once, in order of first appearance
+fn junctions<'a>(edges: &[ExportEdge<'a>]) -> Vec<EdgeEnd<'a>> {
+    let mut junctions = Vec::new();
+    for edge in edges {
+        if matches!(edge.to, EdgeEnd::Junction(_)) && !junctions.contains(&edge.to) {
+            junctions.push(edge.to);
+        }
+    }
+    junctions
+}
+
+/// Mermaid id for an edge end; junctions use a `j_` prefix so they can't clash with states
+fn mermaid_end(end: EdgeEnd) -> String {
+    match end {
+        EdgeEnd::State(id) => mermaid_id(id),
+        EdgeEnd::Junction(tx_hash) => format!("j_{}", mermaid_sanitize(tx_hash)),
+    }
+}
 
 /// A directed graph representing the evolution of UTXO states for a specific Cardano script.
 ///
 /// The `StateGraph` captures how UTXOs are transformed into new UTXOs through transactions.
 /// It provides a high-level view of a smart contract's state machine by linking inputs and
 /// outputs across the blockchain's history.
+#[derive(Clone)]
 pub struct StateGraph {
     /// The underlying graph structure.
     /// Nodes represent UTXO states, and edges represent the transactions that move between them.
@@ -28,6 +110,28 @@ pub struct StateGraph {
 
     /// The Bech32 address of the script whose states are being tracked.
     pub script_address: String,
+
+    /// Every tracked script address, starting with [`Self::script_address`].
+    ///
+    /// Holds more than one address when interacting contracts (e.g. a vault and its
+    /// minting policy) are visualized together.
+    pub script_addresses: Vec<String>,
+
+    /// The script's unspent UTXOs as of the chain tip, if they were fetched.
+    ///
+    /// Used to tell terminal states that are still held by the contract ("live")
+    /// apart from terminal states whose value left the script address.
+    pub unspent_utxos: Option<HashSet<StateId>>,
+
+    /// Hashes of transactions whose phase-2 script validation failed.
+    ///
+    /// States created by these transactions are classified as failed.
+    pub failed_transactions: HashSet<String>,
+
+    /// Incoming and outgoing transition counts per state, kept in step with the
+    /// graph by [`Self::add_state`] and [`Self::add_transition`] so renders don't
+    /// have to scan edges.
+    degrees: HashMap<StateId, (usize, usize)>,
 }
 
 impl StateGraph {
@@ -35,13 +139,23 @@ impl StateGraph {
         Self {
             graph: StableGraph::new(),
             state_index: HashMap::new(),
+            script_addresses: vec![script_address.clone()],
             script_address,
+            unspent_utxos: None,
+            failed_transactions: HashSet::new(),
+            degrees: HashMap::new(),
         }
     }
 
+    /// Record the script's current unspent UTXOs (e.g. from `DataSource::get_script_utxos`)
+    pub fn set_unspent_utxos(&mut self, utxos: impl IntoIterator<Item = UtxoRef>) {
+        self.unspent_utxos = Some(utxos.into_iter().map(|utxo| utxo.to_string()).collect());
+    }
+
     /// Adds a state to the graph and updates the state index.
     pub fn add_state(&mut self, state: State) -> NodeIndex {
         let node_index = self.graph.add_node(state.clone());
+        self.degrees.entry(state.id.clone()).or_default();
         self.state_index.insert(state.id, node_index);
         node_index
     }
@@ -52,24 +166,120 @@ impl StateGraph {
             self.state_index.get(&transition.from_state),
             self.state_index.get(&transition.to_state),
         ) {
+            if let Some((_, outgoing)) = self.degrees.get_mut(&transition.from_state) {
+                *outgoing += 1;
+            }
+            if let Some((incoming, _)) = self.degrees.get_mut(&transition.to_state) {
+                *incoming += 1;
+            }
             Some(self.graph.add_edge(from_idx, to_idx, transition))
         } else {
             None
         }
     }
 
+    /// Whether outputs at `address` become states of this graph
+    pub fn tracks_address(&self, address: &str) -> bool {
+        self.script_addresses
+            .iter()
+            .any(|tracked| tracked == address)
+    }
+
     pub fn build_from_transactions(
         transactions: &[Transaction],
         script_address: &str,
         parser: Option<&SchemaParser>,
     ) -> Result<Self> {
-        let mut graph = Self::new(script_address.to_string());
+        Self::build(transactions, &[script_address.to_string()], parser, None)
+    }
+
+    /// Build one graph tracking the outputs of several script addresses
+    ///
+    /// A transaction spending a state at one address and creating a state at another
+    /// links the two contracts with a transition. The first address becomes
+    /// [`Self::script_address`].
+    pub fn build_from_transactions_multi(
+        transactions: &[Transaction],
+        script_addresses: &[String],
+        parser: Option<&SchemaParser>,
+    ) -> Result<Self> {
+        Self::build(transactions, script_addresses, parser, None)
+    }
+
+    /// Build the graph like [`StateGraph::build_from_transactions`], then let `classifier`
+    /// override the classification of any state
+    pub fn build_with_classifier(
+        transactions: &[Transaction],
+        script_address: &str,
+        parser: Option<&SchemaParser>,
+        classifier: &ClassifierFn,
+    ) -> Result<Self> {
+        Self::build(
+            transactions,
+            &[script_address.to_string()],
+            parser,
+            Some(classifier),
+        )
+    }
+
+    fn build(
+        transactions: &[Transaction],
+        script_addresses: &[String],
+        parser: Option<&SchemaParser>,
+        classifier: Option<&ClassifierFn>,
+    ) -> Result<Self> {
+        let Some(script_address) = script_addresses.first() else {
+            return Err(crate::Error::state_machine(
+                "At least one script address is required",
+            ));
+        };
+        let mut graph = Self::new(script_address.clone());
+        graph.script_addresses = script_addresses.to_vec();
+        graph.add_transactions(transactions, parser);
+
+        graph.classify_states(parser);
+        if let Some(classifier) = classifier {
+            graph.apply_classifier(classifier);
+        }
+        Ok(graph)
+    }
+
+    /// Add transactions observed after the graph was built, e.g. by a watcher
+    ///
+    /// Only their new states and transitions are added; transactions already in the
+    /// graph are skipped. All states are then reclassified, since a new transition can
+    /// turn a terminal state into an active one.
+    pub fn merge(&mut self, new_txs: &[Transaction], parser: Option<&SchemaParser>) {
+        let known: HashSet<&str> = self
+            .graph
+            .node_weights()
+            .map(|state| state.tx_hash.as_str())
+            .chain(self.graph.edge_weights().map(|t| t.tx_hash.as_str()))
+            .collect();
+        let new_txs: Vec<Transaction> = new_txs
+            .iter()
+            .filter(|tx| !known.contains(tx.hash.as_str()))
+            .cloned()
+            .collect();
+
+        self.add_transactions(&new_txs, parser);
+        self.classify_states(parser);
+    }
+
+    /// Add a state for each script output and a transition for each script input spent
+    fn add_transactions(&mut self, transactions: &[Transaction], parser: Option<&SchemaParser>) {
+        self.failed_transactions.extend(
+            transactions
+                .iter()
+                .filter(|tx| !tx.valid)
+                .map(|tx| tx.hash.clone()),
+        );
 
-        // Collect all outputs at the script address
+        // Collect all outputs at the script addresses
         let mut all_outputs: Vec<(String, usize, &Transaction)> = Vec::new();
         for tx in transactions {
             for (output_idx, output) in tx.outputs.iter().enumerate() {
-                if output.address == script_address {
+                if self.tracks_address(&output.address) {
                     all_outputs.push((tx.hash.clone(), output_idx, tx));
                 }
             }
@@ -79,7 +289,7 @@ impl StateGraph {
         for (tx_hash, output_idx, tx) in &all_outputs {
             let utxo_ref = UtxoRef::new(tx_hash, *output_idx as u64);
             let output = &tx.outputs[*output_idx];
-            graph.add_state(State::new(
+            self.add_state(State::new(
                 utxo_ref,
                 output.datum.clone(),
                 output.clone(),
@@ -95,14 +305,17 @@ impl StateGraph {
         //  - Identify outputs that create UTXOs at the script address
         //  - Create transitions between consumed and created states
         for tx in transactions {
-            // Find inputs that consume UTXOs
-            let consumed_states: Vec<StateId> = tx
+            // Find inputs that consume UTXOs, with the spend redeemer that unlocked each.
+            // A transaction failing phase 2 only takes its collateral, so its regular
+            // inputs stay unspent.
+            let consumed_states: Vec<(StateId, Option<Redeemer>)> = tx
                 .inputs
                 .iter()
+                .filter(|_| tx.valid)
                 .filter_map(|input| {
                     let state_id = input.utxo_ref.to_string();
-                    if graph.state_index.contains_key(&state_id) {
-                        Some(state_id)
+                    if self.state_index.contains_key(&state_id) {
+                        Some((state_id, spend_redeemer(tx, &input.utxo_ref).cloned()))
                     } else {
                         None
                     }
@@ -115,8 +328,8 @@ impl StateGraph {
                 .iter()
                 .enumerate()
                 .filter_map(|(idx, output)| {
-                    // Only creates states for outputs sent to THIS script address
-                    if output.address == script_address {
+                    // Only creates states for outputs sent to a tracked script address
+                    if self.tracks_address(&output.address) {
                         let utxo_ref = UtxoRef::new(&tx.hash, idx as u64);
                         Some(utxo_ref.to_string())
                     } else {
@@ -126,20 +339,8 @@ impl StateGraph {
                 .collect();
 
             // Create transitions: each consumed state -> each created state
-            for from_state in &consumed_states {
+            for (from_state, redeemer) in &consumed_states {
                 for to_state in &created_states {
-                    // Find redeemer for this input if available
-                    let redeemer = tx
-                        .witnesses
-                        .redeemers
-                        .iter()
-                        .find(|r| {
-                            // Match redeemer by index
-                            // TODO: would need to properly match spend redeemers to inputs
-                            r.index < tx.inputs.len() as u64
-                        })
-                        .cloned();
-
                     let mut transition = Transition::new(
                         from_state.clone(),
                         to_state.clone(),
@@ -147,9 +348,17 @@ impl StateGraph {
                         redeemer.clone(),
                     );
 
+                    // ADA moved from the consumed state into the created one
+                    if let (Some(from), Some(to)) =
+                        (self.get_state(from_state), self.get_state(to_state))
+                    {
+                        transition = transition
+                            .with_value_delta(to.ada_value() as i64 - from.ada_value() as i64);
+                    }
+
                     // Apply schema-based label if available
                     if let Some(parser) = parser
-                        && let Some(r) = &redeemer
+                        && let Some(r) = redeemer
                         && let Some(parsed) = &r.parsed
                         && let Some(name) = &parsed.name
                         && let Some(label) = parser.label_transition(name)
@@ -157,33 +366,24 @@ impl StateGraph {
                         transition = transition.with_label(label);
                     }
 
-                    graph.add_transition(transition);
+                    self.add_transition(transition);
                 }
             }
         }
-
-        graph.classify_states(parser);
-        Ok(graph)
     }
 
     /// Classify states based on their position in the graph and schema rules
     fn classify_states(&mut self, parser: Option<&SchemaParser>) {
+        let cyclic = self.cyclic_nodes();
+
         // Collect node indices and their classifications first
         let classifications: Vec<(NodeIndex, StateClass)> = self
             .graph
             .node_indices()
             .map(|node_idx| {
                 // Default topological classification
-                let has_incoming = self
-                    .graph
-                    .edges_directed(node_idx, Direction::Incoming)
-                    .count()
-                    > 0;
-                let has_outgoing = self
-                    .graph
-                    .edges_directed(node_idx, Direction::Outgoing)
-                    .count()
-                    > 0;
+                let (incoming, outgoing) = self.degree(&self.graph[node_idx].id);
+                let (has_incoming, has_outgoing) = (incoming > 0, outgoing > 0);
                 let mut classification = match (has_incoming, has_outgoing) {
                     (false, true) => StateClass::Initial,  // No inputs, has outputs
                     (false, false) => StateClass::Initial, // No inputs, no outputs (initial isolated)
@@ -191,6 +391,12 @@ impl StateGraph {
                     (true, true) => StateClass::Active,    // Has both (active transition)
                 };
 
+                // Pool-like contracts keep returning to earlier states; every state on a
+                // cycle is a live continuation rather than a start or end
+                if cyclic.contains(&node_idx) {
+                    classification = StateClass::Active;
+                }
+
                 // Override with schema-based classification if available
                 if let Some(parser) = parser
                     && let Some(state) = self.graph.node_weight(node_idx)
@@ -198,11 +404,47 @@ impl StateGraph {
                 {
                     classification = schema_class;
                 }
+
+                // A failed script phase is terminal regardless of the datum
+                if let Some(state) = self.graph.node_weight(node_idx)
+                    && self.failed_transactions.contains(&state.tx_hash)
+                {
+                    classification = StateClass::Failed;
+                }
                 (node_idx, classification)
             })
             .collect();
 
-        // Apply classifications to states
+        self.set_classifications(classifications);
+    }
+
+    /// Nodes on a cycle: members of a strongly connected component with more than one
+    /// node, or with an edge to themselves
+    fn cyclic_nodes(&self) -> HashSet<NodeIndex> {
+        petgraph::algo::tarjan_scc(&self.graph)
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1 || self.graph.find_edge(component[0], component[0]).is_some()
+            })
+            .flatten()
+            .collect()
+    }
+
+    /// Override classifications with a consumer-supplied classifier
+    fn apply_classifier(&mut self, classifier: &ClassifierFn) {
+        let classifications: Vec<(NodeIndex, StateClass)> = self
+            .graph
+            .node_indices()
+            .filter_map(|node_idx| {
+                let state = self.graph.node_weight(node_idx)?;
+                classifier(state, self).map(|class| (node_idx, class))
+            })
+            .collect();
+
+        self.set_classifications(classifications);
+    }
+
+    fn set_classifications(&mut self, classifications: Vec<(NodeIndex, StateClass)>) {
         for (node_idx, classification) in classifications {
             if let Some(state) = self.graph.node_weight_mut(node_idx) {
                 state.metadata.classification = classification;
@@ -229,183 +471,959 @@ impl StateGraph {
             .collect()
     }
 
-    /// Export to DOT format for Graphviz
-    pub fn to_dot(&self) -> String {
-        let mut dot = "digraph StateGraph {\n".to_string();
-        dot.push_str("  rankdir=LR;\n");
-        dot.push_str("  node [shape=box, style=filled];\n\n");
+    /// Find terminal states that are still unspent at the script address
+    ///
+    /// Empty when the unspent UTXO set has not been recorded.
+    pub fn live_states(&self) -> Vec<&State> {
+        self.find_terminal_states()
+            .into_iter()
+            .filter(|state| self.is_live(&state.id))
+            .collect()
+    }
 
-        // Add nodes
-        for (state_id, &node_idx) in &self.state_index {
-            if let Some(state) = self.graph.node_weight(node_idx) {
-                let color = state.metadata.color.as_deref().unwrap_or("gray");
-                let label = state.display_short();
-                let safe_id = state_id.replace(['#', '-'], "_");
+    /// Whether a state is still unspent at the script address
+    pub fn is_live(&self, id: &StateId) -> bool {
+        self.unspent_utxos
+            .as_ref()
+            .is_some_and(|unspent| unspent.contains(id))
+    }
 
-                dot.push_str(&format!(
-                    "  \"{}\" [label=\"{}\", fillcolor=\"{}\"];\n",
-                    safe_id, label, color
-                ));
-            }
-        }
+    /// Find all states reachable from the given state (its future), in BFS order.
+    ///
+    /// The starting state itself is not included.
+    pub fn reachable_from(&self, id: &StateId) -> Vec<StateId> {
+        self.traverse(id, Direction::Outgoing)
+    }
 
-        dot.push('\n');
+    /// Find all states that led to the given state (its history), in BFS order.
+    ///
+    /// The starting state itself is not included.
+    pub fn ancestors_of(&self, id: &StateId) -> Vec<StateId> {
+        self.traverse(id, Direction::Incoming)
+    }
 
-        // Add edges
-        for edge_idx in self.graph.edge_indices() {
-            if let Some((from_idx, to_idx)) = self.graph.edge_endpoints(edge_idx)
-                && let (Some(from_state), Some(to_state), Some(transition)) = (
-                    self.graph.node_weight(from_idx),
-                    self.graph.node_weight(to_idx),
-                    self.graph.edge_weight(edge_idx),
-                )
-            {
-                let from_id = from_state.id.replace(['#', '-'], "_");
-                let to_id = to_state.id.replace(['#', '-'], "_");
-                let label = transition.display_label();
+    /// Find a shortest route between two states, as the transitions taken in order.
+    ///
+    /// Returns `None` if either state is unknown or `to` can't be reached from `from`;
+    /// the route from a state to itself is empty.
+    pub fn find_path(&self, from: &StateId, to: &StateId) -> Option<Vec<Transition>> {
+        let &start = self.state_index.get(from)?;
+        let &goal = self.state_index.get(to)?;
 
-                dot.push_str(&format!(
-                    "  \"{}\" -> \"{}\" [label=\"{}\"];\n",
-                    from_id, to_id, label
-                ));
+        // Edge used to first reach each node, for walking the route back from the goal
+        let mut reached_by: HashMap<NodeIndex, EdgeIndex> = HashMap::new();
+        let mut visited = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+
+        while let Some(node_idx) = queue.pop_front() {
+            if node_idx == goal {
+                let mut path = Vec::new();
+                let mut current = goal;
+                while let Some(&edge_idx) = reached_by.get(&current) {
+                    path.push(self.graph[edge_idx].clone());
+                    current = self.graph.edge_endpoints(edge_idx)?.0;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for edge in self.graph.edges_directed(node_idx, Direction::Outgoing) {
+                if visited.insert(edge.target()) {
+                    reached_by.insert(edge.target(), edge.id());
+                    queue.push_back(edge.target());
+                }
             }
         }
 
-        dot.push_str("}\n");
+        None
+    }
 
-        // Export to {date}.graph.dot file and generate the svg if graphviz is installed
-        let dot_filename = format!("{}.graph.dot", chrono::Utc::now().format("%Y%m%d%H%M%S"));
-        if let Ok(mut file) = std::fs::File::create(&dot_filename) {
-            use std::io::Write;
-            let _ = file.write_all(dot.as_bytes());
-
-            // Try to generate SVG using dot command
-            let svg_filename = dot_filename.replace(".dot", ".svg");
-            if let Ok(output) = std::process::Command::new("dot")
-                .args(["-Tsvg", &dot_filename, "-o", &svg_filename])
-                .output()
-            {
-                if output.status.success() {
-                    format!("Graph exported to {}", svg_filename)
-                } else {
-                    format!(
-                        "Graph exported to {}, but failed to generate SVG",
-                        dot_filename
-                    )
+    /// Breadth-first traversal from a state following edges in the given direction
+    fn traverse(&self, id: &StateId, direction: Direction) -> Vec<StateId> {
+        let Some(&start) = self.state_index.get(id) else {
+            return Vec::new();
+        };
+
+        let mut visited = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+        let mut result = Vec::new();
+
+        while let Some(node_idx) = queue.pop_front() {
+            for neighbor in self.graph.neighbors_directed(node_idx, direction) {
+                if visited.insert(neighbor) {
+                    if let Some(state) = self.graph.node_weight(neighbor) {
+                        result.push(state.id.clone());
+                    }
+                    queue.push_back(neighbor);
                 }
-            } else {
-                format!("Graph exported to {}", dot_filename)
             }
-        } else {
-            "Failed to export graph to file".to_string()
         }
+
+        result
     }
 
-    /// Get a state by its ID
-    pub fn get_state(&self, state_id: &StateId) -> Option<&State> {
-        self.state_index
-            .get(state_id)
-            .and_then(|&idx| self.graph.node_weight(idx))
+    /// Build a new graph containing a state together with its full history and future.
+    ///
+    /// States keep the classification computed on the full graph, and only transitions
+    /// between retained states are copied over.
+    pub fn focus_subgraph(&self, id: &StateId) -> StateGraph {
+        let mut retained: HashSet<StateId> = self.ancestors_of(id).into_iter().collect();
+        retained.extend(self.reachable_from(id));
+        if self.state_index.contains_key(id) {
+            retained.insert(id.clone());
+        }
+        self.subgraph(&retained)
     }
 
-    /// Get all states
-    pub fn all_states(&self) -> Vec<&State> {
-        self.graph
+    /// Restrict the graph to states within `depth` transitions of an initial state
+    /// (one without incoming transitions), to keep very large contracts displayable
+    ///
+    /// States keep the classification computed on the full graph.
+    pub fn subgraph_within_depth(&self, depth: usize) -> StateGraph {
+        let mut queue: VecDeque<(NodeIndex, usize)> = self
+            .graph
             .node_indices()
-            .filter_map(|idx| self.graph.node_weight(idx))
-            .collect()
-    }
+            .filter(|&idx| self.degree(&self.graph[idx].id).0 == 0)
+            .map(|idx| (idx, 0))
+            .collect();
 
-    /// Get outgoing transitions from a state
-    pub fn outgoing_transitions(&self, state_id: &StateId) -> Vec<&Transition> {
-        if let Some(&node_idx) = self.state_index.get(state_id) {
-            self.graph
-                .edges_directed(node_idx, Direction::Outgoing)
-                .filter_map(|edge| self.graph.edge_weight(edge.id()))
-                .collect()
-        } else {
-            Vec::new()
+        // Every search starts at depth 0, so a state is first reached by a shortest path
+        let mut retained = HashSet::new();
+        while let Some((idx, distance)) = queue.pop_front() {
+            if !retained.insert(self.graph[idx].id.clone()) {
+                continue;
+            }
+            if distance < depth {
+                queue.extend(
+                    self.graph
+                        .neighbors_directed(idx, Direction::Outgoing)
+                        .map(|next| (next, distance + 1)),
+                );
+            }
         }
+        self.subgraph(&retained)
     }
 
-    /// Get incoming transitions to a state
-    pub fn incoming_transitions(&self, state_id: &StateId) -> Vec<&Transition> {
-        if let Some(&node_idx) = self.state_index.get(state_id) {
-            self.graph
-                .edges_directed(node_idx, Direction::Incoming)
-                .filter_map(|edge| self.graph.edge_weight(edge.id()))
-                .collect()
-        } else {
-            Vec::new()
+    /// Build a new graph restricted to the given states, preserving node order
+    fn subgraph(&self, retained: &HashSet<StateId>) -> StateGraph {
+        let mut subgraph = StateGraph::new(self.script_address.clone());
+        subgraph.script_addresses = self.script_addresses.clone();
+        subgraph.unspent_utxos = self.unspent_utxos.clone();
+
+        for node_idx in self.graph.node_indices() {
+            if let Some(state) = self.graph.node_weight(node_idx)
+                && retained.contains(&state.id)
+            {
+                subgraph.add_state(state.clone());
+            }
+        }
+
+        for transition in self.graph.edge_weights() {
+            if retained.contains(&transition.from_state) && retained.contains(&transition.to_state)
+            {
+                subgraph.add_transition(transition.clone());
+            }
         }
+
+        subgraph
     }
 
-    /// Get graph statistics
-    pub fn stats(&self) -> GraphStats {
-        GraphStats {
-            total_states: self.graph.node_count(),
-            total_transitions: self.graph.edge_count(),
-            initial_states: self.find_initial_states().len(),
-            terminal_states: self.find_terminal_states().len(),
+    /// Classify each transaction's transitions as simple, merge, split or many-to-many
+    ///
+    /// Keyed by transaction hash, since every edge of a transaction shares its shape.
+    pub fn transition_kinds(&self) -> HashMap<String, TransitionKind> {
+        let mut endpoints: HashMap<&str, (HashSet<&StateId>, HashSet<&StateId>)> = HashMap::new();
+        for transition in self.graph.edge_weights() {
+            let (from, to) = endpoints.entry(&transition.tx_hash).or_default();
+            from.insert(&transition.from_state);
+            to.insert(&transition.to_state);
         }
+
+        endpoints
+            .into_iter()
+            .map(|(tx_hash, (from, to))| {
+                (
+                    tx_hash.to_string(),
+                    TransitionKind::from_counts(from.len(), to.len()),
+                )
+            })
+            .collect()
     }
-}
 
-#[derive(Debug, Clone)]
-pub struct GraphStats {
-    pub total_states: usize,
-    pub total_transitions: usize,
-    pub initial_states: usize,
-    pub terminal_states: usize,
-}
+    /// Group states into topological layers for rank-based layouts
+    ///
+    /// Each state is placed one layer after its deepest predecessor, so merge
+    /// targets line up after all of their sources. States within a layer are
+    /// sorted by slot and id; states on a cycle are appended as a final layer.
+    pub fn layers(&self) -> Vec<Vec<StateId>> {
+        let mut in_degree: HashMap<NodeIndex, usize> = self
+            .graph
+            .node_indices()
+            .map(|idx| {
+                let degree = self
+                    .graph
+                    .neighbors_directed(idx, Direction::Incoming)
+                    .count();
+                (idx, degree)
+            })
+            .collect();
+        let mut depth: HashMap<NodeIndex, usize> = HashMap::new();
+        let mut queue: VecDeque<NodeIndex> = in_degree
+            .iter()
+            .filter(|(_, degree)| **degree == 0)
+            .map(|(idx, _)| *idx)
+            .collect();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::data_source::{Asset, Datum, TxInput, TxOutput, UtxoRef, Witnesses};
+        while let Some(idx) = queue.pop_front() {
+            let current = *depth.entry(idx).or_insert(0);
+            for next in self.graph.neighbors_directed(idx, Direction::Outgoing) {
+                let next_depth = depth.entry(next).or_insert(0);
+                *next_depth = (*next_depth).max(current + 1);
 
-    fn create_test_transaction(
-        hash: &str,
-        block: u64,
-        slot: u64,
-        inputs: Vec<TxInput>,
-        outputs: Vec<TxOutput>,
-    ) -> Transaction {
-        Transaction {
-            hash: hash.to_string(),
-            block,
-            slot,
-            inputs,
-            outputs,
-            witnesses: Witnesses::default(),
-            metadata: None,
+                if let Some(degree) = in_degree.get_mut(&next) {
+                    *degree -= 1;
+                    if *degree == 0 {
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+
+        let layer_count = depth.values().max().map_or(0, |max| max + 1);
+        let mut layers: Vec<Vec<&State>> = vec![Vec::new(); layer_count];
+        let mut cyclic: Vec<&State> = Vec::new();
+        for idx in self.graph.node_indices() {
+            let state = &self.graph[idx];
+            match (in_degree[&idx], depth.get(&idx)) {
+                (0, Some(&layer)) => layers[layer].push(state),
+                _ => cyclic.push(state),
+            }
         }
+        if !cyclic.is_empty() {
+            layers.push(cyclic);
+        }
+
+        layers
+            .into_iter()
+            .filter(|layer| !layer.is_empty())
+            .map(|mut layer| {
+                layer.sort_by(|a, b| a.slot.cmp(&b.slot).then_with(|| a.id.cmp(&b.id)));
+                layer.into_iter().map(|state| state.id.clone()).collect()
+            })
+            .collect()
     }
 
-    #[test]
-    fn test_empty_graph() {
-        let graph = StateGraph::new("addr_script".to_string());
-        assert_eq!(graph.graph.node_count(), 0);
-        assert_eq!(graph.graph.edge_count(), 0);
+    /// States ordered by block, slot and id, for exports that must be reproducible
+    fn sorted_states(&self) -> Vec<&State> {
+        let mut states = self.all_states();
+        states.sort_by(|a, b| (a.block, a.slot, &a.id).cmp(&(b.block, b.slot, &b.id)));
+        states
     }
 
-    #[test]
-    fn test_build_linear_graph() {
-        // Create a simple linear flow: tx1 creates state1, tx2 consumes state1 and creates state2
-        let script_addr = "addr_script";
+    /// Transitions ordered by their source and then target state (in
+    /// [`Self::sorted_states`] order), then by transaction hash
+    fn sorted_transitions(&self, states: &[&State]) -> Vec<&Transition> {
+        let position: HashMap<&StateId, usize> = states
+            .iter()
+            .enumerate()
+            .map(|(pos, state)| (&state.id, pos))
+            .collect();
+        let mut transitions: Vec<&Transition> = self.graph.edge_weights().collect();
+        transitions.sort_by_key(|t| {
+            (
+                position.get(&t.from_state),
+                position.get(&t.to_state),
+                &t.tx_hash,
+            )
+        });
+        transitions
+    }
 
-        let tx1 = create_test_transaction(
-            "tx1",
-            100,
-            1000,
-            vec![],
-            vec![TxOutput {
-                address: script_addr.to_string(),
-                amount: vec![Asset::lovelace(10_000_000)],
-                datum: Some(Datum {
+    /// Edges to draw with their labels, in [`Self::sorted_transitions`] order
+    ///
+    /// With `merge_parallel_edges`, a transaction with several transitions is drawn as
+    /// one edge from each state it consumed into its junction, labelled with that
+    /// state's transition, and one unlabelled edge from the junction to each state
+    /// it created.
+    fn export_edges<'a>(&'a self, states: &[&State], options: &DotOptions) -> Vec<ExportEdge<'a>> {
+        let direct = |t: &'a Transition| ExportEdge {
+            from: EdgeEnd::State(&t.from_state),
+            to: EdgeEnd::State(&t.to_state),
+            tx_hash: &t.tx_hash,
+            label: t.display_label(),
+        };
+        let transitions = self.sorted_transitions(states);
+        if !options.merge_parallel_edges {
+            return transitions.into_iter().map(direct).collect();
+        }
+
+        let mut by_tx: HashMap<&str, Vec<&Transition>> = HashMap::new();
+        let mut order = Vec::new();
+        for transition in transitions {
+            by_tx
+                .entry(transition.tx_hash.as_str())
+                .or_insert_with(|| {
+                    order.push(transition.tx_hash.as_str());
+                    Vec::new()
+                })
+                .push(transition);
+        }
+
+        let mut edges = Vec::new();
+        for tx_hash in order {
+            let group = &by_tx[tx_hash];
+            if group.len() == 1 {
+                edges.push(direct(group[0]));
+                continue;
+            }
+
+            let junction = EdgeEnd::Junction(tx_hash);
+            let mut consumed = HashSet::new();
+            for transition in group {
+                if consumed.insert(&transition.from_state) {
+                    edges.push(ExportEdge {
+                        from: EdgeEnd::State(&transition.from_state),
+                        to: junction,
+                        tx_hash,
+                        label: transition.display_label(),
+                    });
+                }
+            }
+            let mut created = HashSet::new();
+            for transition in group {
+                if created.insert(&transition.to_state) {
+                    edges.push(ExportEdge {
+                        from: junction,
+                        to: EdgeEnd::State(&transition.to_state),
+                        tx_hash,
+                        label: String::new(),
+                    });
+                }
+            }
+        }
+        edges
+    }
+
+    /// Export to DOT format for Graphviz
+    ///
+    /// Nodes and edges are emitted in a stable order, so the same graph always
+    /// produces the same output.
+    pub fn to_dot(&self) -> String {
+        self.to_dot_with(&DotOptions::default())
+    }
+
+    /// Export to DOT format for Graphviz, drawing edges as set by `options`
+    pub fn to_dot_with(&self, options: &DotOptions) -> String {
+        let mut dot = "digraph StateGraph {\n".to_string();
+        dot.push_str("  rankdir=LR;\n");
+        dot.push_str("  node [shape=box, style=filled];\n\n");
+
+        let safe_id = |state_id: &StateId| state_id.replace(['#', '-'], "_");
+        let end_id = |end: EdgeEnd| match end {
+            EdgeEnd::State(id) => safe_id(id),
+            EdgeEnd::Junction(tx_hash) => format!("junction_{}", tx_hash.replace('-', "_")),
+        };
+
+        // Add nodes, grouped in one cluster per address when several are tracked
+        let states = self.sorted_states();
+        let node = |state: &State, indent: &str| {
+            let color = state.metadata.color.as_deref().unwrap_or("gray");
+            format!(
+                "{}\"{}\" [label=\"{}\", fillcolor=\"{}\"];\n",
+                indent,
+                safe_id(&state.id),
+                state.display_short(),
+                color
+            )
+        };
+        if self.script_addresses.len() > 1 {
+            for (i, address) in self.script_addresses.iter().enumerate() {
+                dot.push_str(&format!(
+                    "  subgraph \"cluster_{}\" {{\n    label=\"{}\";\n",
+                    i, address
+                ));
+                for state in states
+                    .iter()
+                    .filter(|state| state.metadata.script_address == *address)
+                {
+                    dot.push_str(&node(state, "    "));
+                }
+                dot.push_str("  }\n");
+            }
+        } else {
+            for state in &states {
+                dot.push_str(&node(state, "  "));
+            }
+        }
+
+        let edges = self.export_edges(&states, options);
+        for junction in junctions(&edges) {
+            dot.push_str(&format!(
+                "  \"{}\" [shape=point, label=\"\"];\n",
+                end_id(junction)
+            ));
+        }
+
+        dot.push('\n');
+
+        // Add edges
+        for edge in &edges {
+            if edge.label.is_empty() {
+                dot.push_str(&format!(
+                    "  \"{}\" -> \"{}\";\n",
+                    end_id(edge.from),
+                    end_id(edge.to)
+                ));
+            } else {
+                dot.push_str(&format!(
+                    "  \"{}\" -> \"{}\" [label=\"{}\"];\n",
+                    end_id(edge.from),
+                    end_id(edge.to),
+                    edge.label
+                ));
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Export nodes and edges as JSON for external tooling
+    ///
+    /// Nodes are ordered by block, slot and id; edges follow insertion order.
+    pub fn to_json(&self) -> serde_json::Value {
+        let states = self.sorted_states();
+
+        let nodes: Vec<serde_json::Value> = states
+            .iter()
+            .map(|state| {
+                let datum_fields = state
+                    .datum
+                    .as_ref()
+                    .and_then(|datum| datum.parsed.as_ref())
+                    .map(|parsed| parsed.fields.iter().collect::<BTreeMap<_, _>>());
+                serde_json::json!({
+                    "id": state.id,
+                    "script_address": state.metadata.script_address,
+                    "classification": state.metadata.classification,
+                    "block": state.block,
+                    "slot": state.slot,
+                    "ada_value": state.ada_value(),
+                    "datum_fields": datum_fields,
+                })
+            })
+            .collect();
+
+        let edges: Vec<serde_json::Value> = self
+            .graph
+            .edge_weights()
+            .map(|transition| {
+                let redeemer = transition
+                    .redeemer
+                    .as_ref()
+                    .and_then(|r| r.parsed.as_ref())
+                    .and_then(|parsed| parsed.name.as_ref());
+                serde_json::json!({
+                    "from": transition.from_state,
+                    "to": transition.to_state,
+                    "tx_hash": transition.tx_hash,
+                    "label": transition.label,
+                    "redeemer": redeemer,
+                    "value_delta": transition.value_delta,
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "script_address": self.script_address,
+            "script_addresses": self.script_addresses,
+            "nodes": nodes,
+            "edges": edges,
+        })
+    }
+
+    /// Export to a Mermaid `stateDiagram-v2`, which GitHub and most Markdown editors render
+    ///
+    /// States are declared layer by layer (see [`Self::layers`]) and styled by
+    /// classification. Merge and split transitions are annotated, and when several
+    /// addresses are tracked each one's states are wrapped in a composite state.
+    pub fn to_mermaid(&self) -> String {
+        self.to_mermaid_with(&DotOptions::default())
+    }
+
+    /// Export to a Mermaid `stateDiagram-v2`, drawing transitions as set by `options`
+    pub fn to_mermaid_with(&self, options: &DotOptions) -> String {
+        let states = self.sorted_states();
+
+        let mut mermaid = "stateDiagram-v2\n".to_string();
+        mermaid.push_str("    direction LR\n");
+        for class in [
+            StateClass::Initial,
+            StateClass::Active,
+            StateClass::Locked,
+            StateClass::Completed,
+            StateClass::Failed,
+            StateClass::Unknown,
+        ] {
+            mermaid.push_str(&format!(
+                "    classDef {} fill:{}\n",
+                class.name().to_lowercase(),
+                class.color()
+            ));
+        }
+        // With several addresses, outline each address's states in its own color.
+        // Composite states can't be used to group them: stateDiagram doesn't allow
+        // transitions between states nested in different composites.
+        let multi_address = self.script_addresses.len() > 1;
+        if multi_address {
+            for (i, address) in self.script_addresses.iter().enumerate() {
+                mermaid.push_str(&format!(
+                    "    %% address_{}: {}\n    classDef address_{} stroke:{},stroke-width:3px\n",
+                    i,
+                    address,
+                    i,
+                    ADDRESS_COLORS[i % ADDRESS_COLORS.len()]
+                ));
+            }
+        }
+        mermaid.push('\n');
+
+        // Declare states in topological order, layer by layer. This only orders the
+        // declarations; the renderer still picks the layout.
+        for (depth, layer) in self.layers().iter().enumerate() {
+            mermaid.push_str(&format!("    %% layer {}\n", depth));
+            for state in layer.iter().filter_map(|id| self.get_state(id)) {
+                mermaid.push_str(&format!(
+                    "    state \"{}\" as {}\n",
+                    state.display_short().replace('"', "#quot;"),
+                    mermaid_id(&state.id)
+                ));
+            }
+        }
+
+        // Junctions are joins for pure merges and forks otherwise
+        let kinds = self.transition_kinds();
+        let edges = self.export_edges(&states, options);
+        for junction in junctions(&edges) {
+            if let EdgeEnd::Junction(tx_hash) = junction {
+                let pseudo = match kinds.get(tx_hash) {
+                    Some(TransitionKind::Merge) => "join",
+                    _ => "fork",
+                };
+                mermaid.push_str(&format!(
+                    "    state {} <<{}>>\n",
+                    mermaid_end(junction),
+                    pseudo
+                ));
+            }
+        }
+
+        mermaid.push('\n');
+
+        // Add transitions, marking where states merge or split
+        for edge in &edges {
+            if edge.label.is_empty() {
+                mermaid.push_str(&format!(
+                    "    {} --> {}\n",
+                    mermaid_end(edge.from),
+                    mermaid_end(edge.to)
+                ));
+                continue;
+            }
+            let annotation = match kinds.get(edge.tx_hash) {
+                Some(TransitionKind::Merge) => " [merge]",
+                Some(TransitionKind::Split) => " [split]",
+                _ => "",
+            };
+            mermaid.push_str(&format!(
+                "    {} --> {} : {}{}\n",
+                mermaid_end(edge.from),
+                mermaid_end(edge.to),
+                edge.label,
+                annotation
+            ));
+        }
+
+        mermaid.push('\n');
+
+        // Apply classification styles
+        for state in &states {
+            mermaid.push_str(&format!(
+                "    class {} {}\n",
+                mermaid_id(&state.id),
+                state.metadata.classification.name().to_lowercase()
+            ));
+        }
+        if multi_address {
+            for state in &states {
+                if let Some(i) = self
+                    .script_addresses
+                    .iter()
+                    .position(|address| *address == state.metadata.script_address)
+                {
+                    mermaid.push_str(&format!(
+                        "    class {} address_{}\n",
+                        mermaid_id(&state.id),
+                        i
+                    ));
+                }
+            }
+        }
+
+        mermaid
+    }
+
+    /// Write the graph in Graphviz DOT format to `path`
+    pub fn write_dot_file(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, self.to_dot())?;
+        Ok(())
+    }
+
+    /// Save the graph's states, transitions and script metadata to `path` as JSON
+    ///
+    /// The file can be read back with [`StateGraph::load`] without fetching again.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let states = self.sorted_states();
+        let saved = SavedGraph {
+            script_address: self.script_address.clone(),
+            script_addresses: self.script_addresses.clone(),
+            transitions: self
+                .sorted_transitions(&states)
+                .into_iter()
+                .cloned()
+                .collect(),
+            states: states.into_iter().cloned().collect(),
+            unspent_utxos: self.unspent_utxos.as_ref().map(|utxos| {
+                let mut utxos: Vec<StateId> = utxos.iter().cloned().collect();
+                utxos.sort();
+                utxos
+            }),
+            failed_transactions: self.failed_transactions.iter().cloned().collect(),
+        };
+        std::fs::write(path, serde_json::to_string_pretty(&saved)?)?;
+        Ok(())
+    }
+
+    /// Load a graph written by [`StateGraph::save`], rebuilding the state index
+    pub fn load(path: &Path) -> Result<Self> {
+        let saved: SavedGraph = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+
+        let mut graph = StateGraph::new(saved.script_address);
+        if !saved.script_addresses.is_empty() {
+            graph.script_addresses = saved.script_addresses;
+        }
+        for state in saved.states {
+            graph.add_state(state);
+        }
+        for transition in saved.transitions {
+            let (from, to) = (transition.from_state.clone(), transition.to_state.clone());
+            if graph.add_transition(transition).is_none() {
+                return Err(crate::Error::state_machine(format!(
+                    "Saved graph {:?} has a transition between unknown states {} -> {}",
+                    path, from, to
+                )));
+            }
+        }
+        graph.unspent_utxos = saved.unspent_utxos.map(|utxos| utxos.into_iter().collect());
+        graph.failed_transactions = saved.failed_transactions.into_iter().collect();
+        Ok(graph)
+    }
+
+    /// Get a state by its ID
+    pub fn get_state(&self, state_id: &StateId) -> Option<&State> {
+        self.state_index
+            .get(state_id)
+            .and_then(|&idx| self.graph.node_weight(idx))
+    }
+
+    /// Get all states
+    pub fn all_states(&self) -> Vec<&State> {
+        self.graph
+            .node_indices()
+            .filter_map(|idx| self.graph.node_weight(idx))
+            .collect()
+    }
+
+    /// Get outgoing transitions from a state
+    pub fn outgoing_transitions(&self, state_id: &StateId) -> Vec<&Transition> {
+        if let Some(&node_idx) = self.state_index.get(state_id) {
+            self.graph
+                .edges_directed(node_idx, Direction::Outgoing)
+                .filter_map(|edge| self.graph.edge_weight(edge.id()))
+                .collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Number of (incoming, outgoing) transitions of a state, without scanning edges
+    ///
+    /// Unknown states have no transitions.
+    pub fn degree(&self, state_id: &StateId) -> (usize, usize) {
+        self.degrees.get(state_id).copied().unwrap_or_default()
+    }
+
+    /// Get incoming transitions to a state
+    pub fn incoming_transitions(&self, state_id: &StateId) -> Vec<&Transition> {
+        if let Some(&node_idx) = self.state_index.get(state_id) {
+            self.graph
+                .edges_directed(node_idx, Direction::Incoming)
+                .filter_map(|edge| self.graph.edge_weight(edge.id()))
+                .collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Distinct transition labels (see [`Transition::display_label`]), sorted
+    pub fn transition_labels(&self) -> Vec<String> {
+        let labels: BTreeSet<String> = self
+            .graph
+            .edge_weights()
+            .map(|transition| transition.display_label())
+            .collect();
+        labels.into_iter().collect()
+    }
+
+    /// States consumed or created by a transition with the given label
+    pub fn states_with_transition_label(&self, label: &str) -> HashSet<StateId> {
+        self.graph
+            .edge_weights()
+            .filter(|transition| transition.display_label() == label)
+            .flat_map(|transition| [transition.from_state.clone(), transition.to_state.clone()])
+            .collect()
+    }
+
+    /// Compare this graph with a newer snapshot `other`
+    pub fn diff(&self, other: &StateGraph) -> GraphDiff {
+        let only_in = |graph: &StateGraph, base: &StateGraph| {
+            let mut ids: Vec<StateId> = graph
+                .state_index
+                .keys()
+                .filter(|id| !base.state_index.contains_key(*id))
+                .cloned()
+                .collect();
+            ids.sort();
+            ids
+        };
+        let mut newly_terminal: Vec<StateId> = other
+            .find_terminal_states()
+            .into_iter()
+            .filter(|state| {
+                !self.state_index.contains_key(&state.id) || self.degree(&state.id).1 > 0
+            })
+            .map(|state| state.id.clone())
+            .collect();
+        newly_terminal.sort();
+        GraphDiff {
+            added_states: only_in(other, self),
+            removed_states: only_in(self, other),
+            newly_terminal,
+        }
+    }
+
+    /// Count how many states carry each datum hash
+    ///
+    /// States without a datum are not counted.
+    pub fn datum_hash_histogram(&self) -> HashMap<String, usize> {
+        let mut histogram = HashMap::new();
+        for state in self.graph.node_weights() {
+            if let Some(datum) = &state.datum {
+                *histogram.entry(datum.hash.clone()).or_insert(0) += 1;
+            }
+        }
+        histogram
+    }
+
+    /// Get graph statistics
+    pub fn stats(&self) -> GraphStats {
+        let histogram = self.datum_hash_histogram();
+
+        let mut most_reused_datums: Vec<(String, usize)> = histogram
+            .iter()
+            .filter(|(_, count)| **count > 1)
+            .map(|(hash, count)| (hash.clone(), *count))
+            .collect();
+        most_reused_datums.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        most_reused_datums.truncate(MAX_REUSED_DATUMS);
+
+        let live_states = self.live_states();
+        // Every (consumed, created) edge of a transaction carries a copy of the same
+        // redeemer, so each redeemer is counted once
+        let ex_units: HashMap<_, _> = self
+            .graph
+            .edge_weights()
+            .filter_map(|t| {
+                let redeemer = t.redeemer.as_ref()?;
+                Some((
+                    (t.tx_hash.as_str(), redeemer.tag, redeemer.index),
+                    redeemer.ex_units,
+                ))
+            })
+            .collect();
+        let count_class = |class: StateClass| {
+            self.graph
+                .node_weights()
+                .filter(|state| state.metadata.classification == class)
+                .count()
+        };
+
+        GraphStats {
+            live_states: live_states.len(),
+            live_lovelace: live_states.iter().map(|state| state.ada_value()).sum(),
+            total_states: self.graph.node_count(),
+            total_transitions: self.graph.edge_count(),
+            initial_states: self.find_initial_states().len(),
+            terminal_states: self.find_terminal_states().len(),
+            active_states: count_class(StateClass::Active),
+            completed_states: count_class(StateClass::Completed),
+            failed_states: count_class(StateClass::Failed),
+            locked_states: count_class(StateClass::Locked),
+            total_ada: self
+                .graph
+                .node_weights()
+                .map(|state| state.ada_value())
+                .sum(),
+            total_mem: ex_units.values().map(|units| units.mem).sum(),
+            total_steps: ex_units.values().map(|units| units.steps).sum(),
+            states_with_datum: histogram.values().sum(),
+            unique_datum_hashes: histogram.len(),
+            most_reused_datums,
+        }
+    }
+}
+
+/// Outline colors telling script addresses apart in the Mermaid export
+const ADDRESS_COLORS: [&str; 6] = [
+    "#1f77b4", "#d62728", "#2ca02c", "#9467bd", "#ff7f0e", "#8c564b",
+];
+
+/// Number of most-reused datum hashes reported in [`GraphStats`]
+const MAX_REUSED_DATUMS: usize = 3;
+
+/// On-disk form of a [`StateGraph`], see [`StateGraph::save`]
+#[derive(Serialize, Deserialize)]
+struct SavedGraph {
+    script_address: String,
+    /// Missing from graphs saved before several addresses could be tracked
+    #[serde(default)]
+    script_addresses: Vec<String>,
+    states: Vec<State>,
+    transitions: Vec<Transition>,
+    unspent_utxos: Option<Vec<StateId>>,
+    failed_transactions: BTreeSet<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GraphStats {
+    pub total_states: usize,
+    pub total_transitions: usize,
+    pub initial_states: usize,
+    pub terminal_states: usize,
+    pub active_states: usize,
+    pub completed_states: usize,
+    pub failed_states: usize,
+    pub locked_states: usize,
+    /// Lovelace summed over every state, spent or not
+    pub total_ada: u64,
+    /// Redeemer memory units summed over every transition
+    pub total_mem: u64,
+    /// Redeemer CPU steps summed over every transition
+    pub total_steps: u64,
+    /// Terminal states still unspent at the script address
+    pub live_states: usize,
+    /// Total lovelace held by live states
+    pub live_lovelace: u64,
+    pub states_with_datum: usize,
+    pub unique_datum_hashes: usize,
+    /// Datum hashes shared by more than one state, most reused first
+    pub most_reused_datums: Vec<(String, usize)>,
+}
+
+impl GraphStats {
+    /// Describe what the datum hash reuse pattern suggests about the contract
+    pub fn datum_reuse_hint(&self) -> &'static str {
+        if self.states_with_datum == 0 {
+            "no datums"
+        } else if self.unique_datum_hashes == self.states_with_datum {
+            "all unique (per-step state encoding)"
+        } else if self.unique_datum_hashes * 2 <= self.states_with_datum {
+            "high reuse (parameterized/constant datum)"
+        } else {
+            "partial reuse"
+        }
+    }
+}
+
+/// States that differ between two graphs, e.g. two snapshots taken in watch mode
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GraphDiff {
+    /// States only in the newer graph, sorted
+    pub added_states: Vec<StateId>,
+    /// States only in the older graph, sorted
+    pub removed_states: Vec<StateId>,
+    /// States terminal in the newer graph that were new or still had outgoing
+    /// transitions in the older one, sorted
+    pub newly_terminal: Vec<StateId>,
+}
+
+impl GraphDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_states.is_empty()
+            && self.removed_states.is_empty()
+            && self.newly_terminal.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_source::{Asset, Datum, ExUnits, TxInput, TxOutput, UtxoRef, Witnesses};
+
+    fn create_test_transaction(
+        hash: &str,
+        block: u64,
+        slot: u64,
+        inputs: Vec<TxInput>,
+        outputs: Vec<TxOutput>,
+    ) -> Transaction {
+        Transaction {
+            hash: hash.to_string(),
+            block,
+            slot,
+            inputs,
+            outputs,
+            witnesses: Witnesses::default(),
+            metadata: None,
+            size: None,
+            valid: true,
+        }
+    }
+
+    #[test]
+    fn test_empty_graph() {
+        let graph = StateGraph::new("addr_script".to_string());
+        assert_eq!(graph.graph.node_count(), 0);
+        assert_eq!(graph.graph.edge_count(), 0);
+    }
+
+    #[test]
+    fn test_build_linear_graph() {
+        // Create a simple linear flow: tx1 creates state1, tx2 consumes state1 and creates state2
+        let script_addr = "addr_script";
+
+        let tx1 = create_test_transaction(
+            "tx1",
+            100,
+            1000,
+            vec![],
+            vec![TxOutput {
+                address: script_addr.to_string(),
+                amount: vec![Asset::lovelace(10_000_000)],
+                datum: Some(Datum {
                     hash: "datum1".to_string(),
                     raw_cbor: vec![0x01],
                     parsed: None,
+                    reencoded: false,
                 }),
                 script_ref: None,
             }],
@@ -427,6 +1445,7 @@ mod tests {
                     hash: "datum2".to_string(),
                     raw_cbor: vec![0x02],
                     parsed: None,
+                    reencoded: false,
                 }),
                 script_ref: None,
             }],
@@ -471,6 +1490,228 @@ mod tests {
         assert_eq!(state.metadata.classification, StateClass::Initial);
     }
 
+    #[test]
+    fn test_invalid_transaction_state_is_failed() {
+        let script_addr = "addr_script";
+        let output = |lovelace| TxOutput {
+            address: script_addr.to_string(),
+            amount: vec![Asset::lovelace(lovelace)],
+            datum: None,
+            script_ref: None,
+        };
+
+        let tx1 = create_test_transaction("tx1", 100, 1000, vec![], vec![output(10_000_000)]);
+        let mut tx2 = create_test_transaction(
+            "tx2",
+            200,
+            2000,
+            vec![TxInput {
+                utxo_ref: UtxoRef::new("tx1", 0),
+                address: Some(script_addr.to_string()),
+                amount: Some(vec![Asset::lovelace(10_000_000)]),
+            }],
+            vec![output(9_000_000)],
+        );
+        tx2.valid = false;
+
+        let graph = StateGraph::build_from_transactions(&[tx1, tx2], script_addr, None).unwrap();
+
+        let initial = graph.get_state(&"tx1#0".to_string()).unwrap();
+        assert_eq!(initial.metadata.classification, StateClass::Initial);
+        // The failed transaction didn't spend its script input
+        assert!(graph.outgoing_transitions(&initial.id).is_empty());
+        assert_eq!(graph.graph.edge_count(), 0);
+        let failed = graph.get_state(&"tx2#0".to_string()).unwrap();
+        assert_eq!(failed.metadata.classification, StateClass::Failed);
+        assert_eq!(failed.metadata.color.as_deref(), Some("red"));
+    }
+
+    #[test]
+    fn test_build_with_classifier() {
+        // Terminal states that still hold at least 5 ADA are treated as locked
+        let classifier = |state: &State, graph: &StateGraph| {
+            (graph.outgoing_transitions(&state.id).is_empty()
+                && state.metadata.classification == StateClass::Completed
+                && state.ada_value() >= 5_000_000)
+                .then_some(StateClass::Locked)
+        };
+        let graph = StateGraph::build_with_classifier(
+            &branching_transactions(),
+            "addr_script",
+            None,
+            &classifier,
+        )
+        .unwrap();
+
+        let class_of = |id: &str| {
+            graph
+                .get_state(&id.to_string())
+                .unwrap()
+                .metadata
+                .classification
+        };
+        assert_eq!(class_of("tx2#1"), StateClass::Locked);
+        assert_eq!(
+            graph
+                .get_state(&"tx2#1".to_string())
+                .unwrap()
+                .metadata
+                .color
+                .as_deref(),
+            Some(StateClass::Locked.color())
+        );
+        // States the classifier declines keep their topological class
+        assert_eq!(class_of("tx3#0"), StateClass::Completed);
+        assert_eq!(class_of("tx2#0"), StateClass::Active);
+        assert_eq!(class_of("tx1#0"), StateClass::Initial);
+    }
+
+    #[test]
+    fn test_spend_redeemers_matched_to_sorted_inputs() {
+        let script_addr = "addr_script";
+        let script_output = |lovelace| TxOutput {
+            address: script_addr.to_string(),
+            amount: vec![Asset::lovelace(lovelace)],
+            datum: None,
+            script_ref: None,
+        };
+        let script_input = |tx_hash: &str| TxInput {
+            utxo_ref: UtxoRef::new(tx_hash, 0),
+            address: Some(script_addr.to_string()),
+            amount: None,
+        };
+        let redeemer = |tag, index, marker| Redeemer {
+            tag,
+            index,
+            raw_cbor: vec![marker],
+            parsed: None,
+            ex_units: ExUnits { mem: 0, steps: 0 },
+        };
+
+        let tx_a = create_test_transaction("aa", 100, 1000, vec![], vec![script_output(1)]);
+        let tx_b = create_test_transaction("bb", 101, 1010, vec![], vec![script_output(2)]);
+        // Inputs listed out of ledger order: bb#0 sorts after aa#0
+        let mut merge = create_test_transaction(
+            "cc",
+            200,
+            2000,
+            vec![script_input("bb"), script_input("aa")],
+            vec![script_output(3)],
+        );
+        merge.witnesses.redeemers = vec![
+            redeemer(RedeemerTag::Mint, 0, 0xff),
+            redeemer(RedeemerTag::Spend, 1, 0xb0),
+            redeemer(RedeemerTag::Spend, 0, 0xa0),
+        ];
+
+        let graph =
+            StateGraph::build_from_transactions(&[tx_a, tx_b, merge], script_addr, None).unwrap();
+        let redeemer_of = |from: &str| {
+            let transitions = graph.outgoing_transitions(&from.to_string());
+            assert_eq!(transitions.len(), 1);
+            transitions[0].redeemer.as_ref().unwrap().raw_cbor.clone()
+        };
+        assert_eq!(redeemer_of("aa#0"), vec![0xa0]);
+        assert_eq!(redeemer_of("bb#0"), vec![0xb0]);
+    }
+
+    #[test]
+    fn test_stats_total_ex_units() {
+        let script_addr = "addr_script";
+        let script_output = TxOutput {
+            address: script_addr.to_string(),
+            amount: vec![Asset::lovelace(1_000_000)],
+            datum: None,
+            script_ref: None,
+        };
+        let script_input = |tx_hash: &str| TxInput {
+            utxo_ref: UtxoRef::new(tx_hash, 0),
+            address: Some(script_addr.to_string()),
+            amount: None,
+        };
+        let spend = |mem, steps| Redeemer {
+            tag: RedeemerTag::Spend,
+            index: 0,
+            raw_cbor: vec![],
+            parsed: None,
+            ex_units: ExUnits { mem, steps },
+        };
+
+        let tx1 = create_test_transaction("tx1", 100, 1000, vec![], vec![script_output.clone()]);
+        let mut tx2 = create_test_transaction(
+            "tx2",
+            101,
+            1010,
+            vec![script_input("tx1")],
+            vec![script_output.clone()],
+        );
+        tx2.witnesses.redeemers = vec![spend(1_000, 20_000)];
+        let mut tx3 = create_test_transaction(
+            "tx3",
+            102,
+            1020,
+            vec![script_input("tx2")],
+            vec![script_output],
+        );
+        tx3.witnesses.redeemers = vec![spend(500, 7_000)];
+
+        let graph =
+            StateGraph::build_from_transactions(&[tx1, tx2, tx3], script_addr, None).unwrap();
+        let ex_units: Vec<ExUnits> = graph
+            .graph
+            .edge_weights()
+            .filter_map(Transition::ex_units)
+            .collect();
+        assert_eq!(ex_units.len(), 2);
+        assert_eq!(ex_units.iter().map(|units| units.mem).sum::<u64>(), 1_500);
+
+        let stats = graph.stats();
+        assert_eq!(stats.total_mem, 1_500);
+        assert_eq!(stats.total_steps, 27_000);
+    }
+
+    #[test]
+    fn test_stats_count_split_redeemer_once() {
+        let script_addr = "addr_script";
+        let script_output = TxOutput {
+            address: script_addr.to_string(),
+            amount: vec![Asset::lovelace(1_000_000)],
+            datum: None,
+            script_ref: None,
+        };
+
+        // tx2 spends one state into two, so both its edges carry the same redeemer
+        let tx1 = create_test_transaction("tx1", 100, 1000, vec![], vec![script_output.clone()]);
+        let mut tx2 = create_test_transaction(
+            "tx2",
+            101,
+            1010,
+            vec![TxInput {
+                utxo_ref: UtxoRef::new("tx1", 0),
+                address: Some(script_addr.to_string()),
+                amount: None,
+            }],
+            vec![script_output.clone(), script_output],
+        );
+        tx2.witnesses.redeemers = vec![Redeemer {
+            tag: RedeemerTag::Spend,
+            index: 0,
+            raw_cbor: vec![],
+            parsed: None,
+            ex_units: ExUnits {
+                mem: 1_000,
+                steps: 20_000,
+            },
+        }];
+
+        let graph = StateGraph::build_from_transactions(&[tx1, tx2], script_addr, None).unwrap();
+        assert_eq!(graph.graph.edge_count(), 2);
+
+        let stats = graph.stats();
+        assert_eq!(stats.total_mem, 1_000);
+        assert_eq!(stats.total_steps, 20_000);
+    }
+
     #[test]
     fn test_to_dot_output() {
         let script_addr = "addr_script";
@@ -492,7 +1733,70 @@ mod tests {
         let graph = StateGraph::build_from_transactions(&transactions, script_addr, None).unwrap();
 
         let dot = graph.to_dot();
-        assert!(dot.contains("Graph exported to"));
+        assert!(dot.starts_with("digraph StateGraph {"));
+        assert!(dot.contains("\"tx1_0\" [label=\"tx1#0: 10.00 ADA\", fillcolor=\"lightblue\"];"));
+        assert!(dot.trim_end().ends_with('}'));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("graph.dot");
+        graph.write_dot_file(&path).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(written, dot);
+    }
+
+    #[tokio::test]
+    async fn test_save_and_load_round_trip() {
+        use crate::data_source::DataSource;
+        use crate::data_source::mock::{MOCK_SCRIPT_ADDRESS, MockDataSource};
+
+        let transactions = MockDataSource::new()
+            .get_transactions_by_address(MOCK_SCRIPT_ADDRESS, Default::default())
+            .await
+            .unwrap();
+        let mut graph =
+            StateGraph::build_from_transactions(&transactions, MOCK_SCRIPT_ADDRESS, None).unwrap();
+        graph.set_unspent_utxos([UtxoRef::new(&transactions[4].hash, 0)]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("graph.json");
+        graph.save(&path).unwrap();
+        let loaded = StateGraph::load(&path);
+        let loaded = loaded.unwrap();
+
+        assert_eq!(loaded.script_address, graph.script_address);
+        assert_eq!(loaded.stats(), graph.stats());
+        assert_eq!(loaded.unspent_utxos, graph.unspent_utxos);
+        assert_eq!(loaded.to_dot(), graph.to_dot());
+    }
+
+    #[test]
+    fn test_dot_and_mermaid_output_is_deterministic() {
+        use crate::data_source::mock::MockScenarioBuilder;
+
+        let transactions = MockScenarioBuilder::new("addr_script")
+            .tree(3)
+            .linear(2)
+            .build();
+        let graph =
+            StateGraph::build_from_transactions(&transactions, "addr_script", None).unwrap();
+        assert_eq!(graph.to_dot(), graph.to_dot());
+
+        // Each build has its own state index; feeding transactions in another order
+        // changes insertion order but not the output
+        let mut reversed = transactions.clone();
+        reversed.reverse();
+        let rebuilt = StateGraph::build_from_transactions(&reversed, "addr_script", None).unwrap();
+        assert_eq!(graph.to_dot(), rebuilt.to_dot());
+        assert_eq!(graph.to_mermaid(), rebuilt.to_mermaid());
+
+        // Nodes follow block order
+        let dot = graph.to_dot();
+        let node_lines: Vec<&str> = dot
+            .lines()
+            .filter(|line| line.contains("[label="))
+            .collect();
+        let first = format!("\"{}_0\"", "0".repeat(63) + "1");
+        assert!(node_lines[0].trim_start().starts_with(&first));
     }
 
     #[test]
@@ -538,4 +1842,632 @@ mod tests {
         assert_eq!(stats.initial_states, 1);
         assert_eq!(stats.terminal_states, 1);
     }
+
+    #[tokio::test]
+    async fn test_graph_stats_mock_vesting_classes() {
+        use crate::data_source::DataSource;
+        use crate::data_source::mock::{MOCK_SCRIPT_ADDRESS, MockDataSource};
+
+        let transactions = MockDataSource::new()
+            .get_transactions_by_address(MOCK_SCRIPT_ADDRESS, Default::default())
+            .await
+            .unwrap();
+        let graph =
+            StateGraph::build_from_transactions(&transactions, MOCK_SCRIPT_ADDRESS, None).unwrap();
+
+        // Lock, two intermediate unlocks, then the last state holding the final tranche
+        let stats = graph.stats();
+        assert_eq!(stats.total_states, 4);
+        assert_eq!(stats.initial_states, 1);
+        assert_eq!(stats.active_states, 2);
+        assert_eq!(stats.completed_states, 1);
+        assert_eq!(stats.failed_states, 0);
+        assert_eq!(stats.locked_states, 0);
+        // 10,000 + 7,500 + 5,000 + 2,500 ADA
+        assert_eq!(stats.total_ada, 25_000_000_000);
+
+        // Each unlock releases 2,500 ADA
+        let deltas: Vec<_> = graph
+            .graph
+            .edge_weights()
+            .map(|transition| transition.value_delta)
+            .collect();
+        assert_eq!(deltas, vec![Some(-2_500_000_000); 3]);
+    }
+
+    #[test]
+    fn test_datum_hash_histogram() {
+        let script_addr = "addr_script";
+        let output_with_datum = |hash: &str| TxOutput {
+            address: script_addr.to_string(),
+            amount: vec![Asset::lovelace(2_000_000)],
+            datum: Some(Datum {
+                hash: hash.to_string(),
+                raw_cbor: vec![],
+                parsed: None,
+                reencoded: false,
+            }),
+            script_ref: None,
+        };
+
+        let tx1 = create_test_transaction(
+            "tx1",
+            100,
+            1000,
+            vec![],
+            vec![
+                output_with_datum("config"),
+                output_with_datum("config"),
+                output_with_datum("config"),
+                output_with_datum("unique"),
+                TxOutput {
+                    address: script_addr.to_string(),
+                    amount: vec![Asset::lovelace(2_000_000)],
+                    datum: None,
+                    script_ref: None,
+                },
+            ],
+        );
+
+        let graph = StateGraph::build_from_transactions(&[tx1], script_addr, None).unwrap();
+
+        let histogram = graph.datum_hash_histogram();
+        assert_eq!(histogram.len(), 2);
+        assert_eq!(histogram["config"], 3);
+        assert_eq!(histogram["unique"], 1);
+
+        let stats = graph.stats();
+        assert_eq!(stats.states_with_datum, 4);
+        assert_eq!(stats.unique_datum_hashes, 2);
+        assert_eq!(stats.most_reused_datums, vec![("config".to_string(), 3)]);
+        assert_eq!(
+            stats.datum_reuse_hint(),
+            "high reuse (parameterized/constant datum)"
+        );
+    }
+
+    /// Build a branching graph: tx1#0 -> {tx2#0, tx2#1}, tx2#0 -> tx3#0, plus unrelated tx4#0
+    fn branching_transactions() -> Vec<Transaction> {
+        let script_addr = "addr_script";
+        let script_output = |lovelace| TxOutput {
+            address: script_addr.to_string(),
+            amount: vec![Asset::lovelace(lovelace)],
+            datum: None,
+            script_ref: None,
+        };
+        let script_input = |tx_hash: &str, idx| TxInput {
+            utxo_ref: UtxoRef::new(tx_hash, idx),
+            address: Some(script_addr.to_string()),
+            amount: None,
+        };
+
+        vec![
+            create_test_transaction("tx1", 100, 1000, vec![], vec![script_output(10_000_000)]),
+            create_test_transaction(
+                "tx2",
+                200,
+                2000,
+                vec![script_input("tx1", 0)],
+                vec![script_output(5_000_000), script_output(5_000_000)],
+            ),
+            create_test_transaction(
+                "tx3",
+                300,
+                3000,
+                vec![script_input("tx2", 0)],
+                vec![script_output(4_000_000)],
+            ),
+            create_test_transaction("tx4", 400, 4000, vec![], vec![script_output(1_000_000)]),
+        ]
+    }
+
+    fn create_branching_graph() -> StateGraph {
+        StateGraph::build_from_transactions(&branching_transactions(), "addr_script", None).unwrap()
+    }
+
+    #[test]
+    fn test_merge_new_transactions() {
+        let transactions = branching_transactions();
+        let mut graph =
+            StateGraph::build_from_transactions(&transactions[..2], "addr_script", None).unwrap();
+        assert_eq!(graph.graph.node_count(), 3);
+        let tx2_0 = "tx2#0".to_string();
+        assert_eq!(
+            graph.get_state(&tx2_0).unwrap().metadata.classification,
+            StateClass::Completed
+        );
+
+        graph.merge(&transactions[2..], None);
+
+        let full = create_branching_graph();
+        assert_eq!(graph.graph.node_count(), full.graph.node_count());
+        assert_eq!(graph.graph.edge_count(), full.graph.edge_count());
+        assert!(graph.reachable_from(&tx2_0).contains(&"tx3#0".to_string()));
+        assert_eq!(
+            graph.get_state(&tx2_0).unwrap().metadata.classification,
+            StateClass::Active
+        );
+    }
+
+    #[test]
+    fn test_merge_skips_known_transactions() {
+        let transactions = branching_transactions();
+        let mut graph = create_branching_graph();
+
+        graph.merge(&transactions, None);
+
+        assert_eq!(graph.graph.node_count(), 5);
+        assert_eq!(graph.graph.edge_count(), 3);
+    }
+
+    #[test]
+    fn test_to_mermaid_output() {
+        let mermaid = create_branching_graph().to_mermaid();
+
+        assert!(mermaid.starts_with("stateDiagram-v2\n"));
+        assert!(mermaid.contains("    classDef initial fill:lightblue\n"));
+        assert!(mermaid.contains("    state \"tx1#0: 10.00 ADA\" as s_tx1_0\n"));
+        assert!(mermaid.contains("    s_tx1_0 --> s_tx2_0 : transition [split]\n"));
+        assert!(mermaid.contains("    s_tx1_0 --> s_tx2_1 : transition [split]\n"));
+        assert!(mermaid.contains("    s_tx2_0 --> s_tx3_0 : transition\n"));
+        assert!(!mermaid.contains("--> s_tx4_0"));
+        assert!(mermaid.contains("    class s_tx1_0 initial\n"));
+    }
+
+    #[tokio::test]
+    async fn test_subgraph_within_depth() {
+        use crate::data_source::DataSource;
+        use crate::data_source::mock::{MOCK_SCRIPT_ADDRESS, MockDataSource};
+
+        let transactions = MockDataSource::new()
+            .get_transactions_by_address(MOCK_SCRIPT_ADDRESS, Default::default())
+            .await
+            .unwrap();
+        let graph =
+            StateGraph::build_from_transactions(&transactions, MOCK_SCRIPT_ADDRESS, None).unwrap();
+
+        let pruned = graph.subgraph_within_depth(1);
+        let mut ids: Vec<&StateId> = pruned.graph.node_weights().map(|s| &s.id).collect();
+        ids.sort();
+        assert_eq!(ids, [&format!("{:064x}#0", 1), &format!("{:064x}#1", 2)]);
+        assert_eq!(pruned.graph.edge_count(), 1);
+        assert_eq!(
+            pruned.get_state(ids[0]).unwrap().metadata.classification,
+            StateClass::Initial
+        );
+
+        assert_eq!(graph.subgraph_within_depth(0).graph.node_count(), 1);
+        assert_eq!(
+            graph.subgraph_within_depth(10).graph.node_count(),
+            graph.graph.node_count()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_path() {
+        use crate::data_source::DataSource;
+        use crate::data_source::mock::{MOCK_SCRIPT_ADDRESS, MockDataSource};
+
+        let transactions = MockDataSource::new()
+            .get_transactions_by_address(MOCK_SCRIPT_ADDRESS, Default::default())
+            .await
+            .unwrap();
+        let graph =
+            StateGraph::build_from_transactions(&transactions, MOCK_SCRIPT_ADDRESS, None).unwrap();
+
+        let initial = format!("{:064x}#0", 1);
+        let last = graph.find_terminal_states()[0].id.clone();
+        let path = graph.find_path(&initial, &last).unwrap();
+        assert_eq!(path.len(), 3);
+        assert_eq!(path[0].from_state, initial);
+        assert_eq!(path[2].to_state, last);
+        assert!(path.windows(2).all(|w| w[0].to_state == w[1].from_state));
+
+        assert_eq!(graph.find_path(&last, &last).map(|p| p.len()), Some(0));
+        assert!(graph.find_path(&last, &initial).is_none());
+        assert!(
+            graph
+                .find_path(&initial, &"unknown#0".to_string())
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_degree_matches_transitions() {
+        use crate::data_source::DataSource;
+        use crate::data_source::mock::{MOCK_SCRIPT_ADDRESS, MockDataSource};
+
+        let transactions = MockDataSource::new()
+            .get_transactions_by_address(MOCK_SCRIPT_ADDRESS, Default::default())
+            .await
+            .unwrap();
+        let graph =
+            StateGraph::build_from_transactions(&transactions, MOCK_SCRIPT_ADDRESS, None).unwrap();
+        assert!(graph.graph.edge_count() > 0);
+
+        for state in graph.graph.node_weights() {
+            assert_eq!(
+                graph.degree(&state.id),
+                (
+                    graph.incoming_transitions(&state.id).len(),
+                    graph.outgoing_transitions(&state.id).len()
+                ),
+                "{}",
+                state.id
+            );
+        }
+        assert_eq!(graph.degree(&"unknown#0".to_string()), (0, 0));
+    }
+
+    #[test]
+    fn test_merge_parallel_edges() {
+        let script_addr = "addr_script";
+        let script_output = || TxOutput {
+            address: script_addr.to_string(),
+            amount: vec![Asset::lovelace(5_000_000)],
+            datum: None,
+            script_ref: None,
+        };
+        let script_input = |idx| TxInput {
+            utxo_ref: UtxoRef::new("tx1", idx),
+            address: Some(script_addr.to_string()),
+            amount: None,
+        };
+
+        // tx2 consumes both outputs of tx1 and creates two new ones
+        let transactions = vec![
+            create_test_transaction("tx1", 100, 1000, vec![], vec![script_output(); 2]),
+            create_test_transaction(
+                "tx2",
+                200,
+                2000,
+                vec![script_input(0), script_input(1)],
+                vec![script_output(); 2],
+            ),
+        ];
+        let graph = StateGraph::build_from_transactions(&transactions, script_addr, None).unwrap();
+        let edge_lines = |dot: &str| dot.lines().filter(|line| line.contains(" -> ")).count();
+
+        assert_eq!(edge_lines(&graph.to_dot()), 4);
+
+        let options = DotOptions {
+            merge_parallel_edges: true,
+        };
+        let dot = graph.to_dot_with(&options);
+        assert_eq!(edge_lines(&dot), 4);
+        assert!(dot.contains("  \"junction_tx2\" [shape=point, label=\"\"];\n"));
+        assert!(dot.contains("  \"tx1_0\" -> \"junction_tx2\" [label=\"transition\"];\n"));
+        assert!(dot.contains("  \"tx1_1\" -> \"junction_tx2\" [label=\"transition\"];\n"));
+        assert!(dot.contains("  \"junction_tx2\" -> \"tx2_0\";\n"));
+        assert!(dot.contains("  \"junction_tx2\" -> \"tx2_1\";\n"));
+
+        let mermaid = graph.to_mermaid_with(&options);
+        assert!(mermaid.contains("    state j_tx2 <<fork>>\n"));
+        assert_eq!(
+            mermaid
+                .lines()
+                .filter(|line| line.contains(" --> "))
+                .count(),
+            4
+        );
+        assert!(mermaid.contains("    s_tx1_0 --> j_tx2 : transition\n"));
+        assert!(mermaid.contains("    s_tx1_1 --> j_tx2 : transition\n"));
+        assert!(mermaid.contains("    j_tx2 --> s_tx2_0\n"));
+        assert!(mermaid.contains("    j_tx2 --> s_tx2_1\n"));
+
+        // tx1 created its states without consuming any, so it has no junction
+        assert!(!dot.contains("junction_tx1"));
+    }
+
+    #[test]
+    fn test_reachable_from_and_ancestors_of() {
+        let graph = create_branching_graph();
+
+        let mut future = graph.reachable_from(&"tx1#0".to_string());
+        future.sort();
+        assert_eq!(future, vec!["tx2#0", "tx2#1", "tx3#0"]);
+
+        assert_eq!(
+            graph.ancestors_of(&"tx3#0".to_string()),
+            vec!["tx2#0", "tx1#0"]
+        );
+        assert!(graph.ancestors_of(&"tx1#0".to_string()).is_empty());
+        assert!(graph.reachable_from(&"missing#0".to_string()).is_empty());
+    }
+
+    #[test]
+    fn test_live_states() {
+        let mut graph = create_branching_graph();
+
+        // Without the unspent set nothing is known to be live
+        assert!(graph.live_states().is_empty());
+
+        // tx2#1 is still unspent, tx3#0 and tx4#0 left the script address;
+        // tx1#0 is reported unspent but isn't terminal, so it isn't live
+        graph.set_unspent_utxos([UtxoRef::new("tx2", 1), UtxoRef::new("tx1", 0)]);
+
+        let live: Vec<&StateId> = graph.live_states().iter().map(|s| &s.id).collect();
+        assert_eq!(live, vec!["tx2#1"]);
+        assert!(graph.is_live(&"tx1#0".to_string()));
+
+        let stats = graph.stats();
+        assert_eq!(stats.live_states, 1);
+        assert_eq!(
+            stats.live_lovelace,
+            graph.get_state(&"tx2#1".to_string()).unwrap().ada_value()
+        );
+
+        // Focusing keeps the live information
+        let focused = graph.focus_subgraph(&"tx2#1".to_string());
+        assert_eq!(focused.live_states().len(), 1);
+    }
+
+    #[test]
+    fn test_transition_kinds_and_layers() {
+        let script_addr = "addr_script";
+        let script_output = || TxOutput {
+            address: script_addr.to_string(),
+            amount: vec![Asset::lovelace(2_000_000)],
+            datum: None,
+            script_ref: None,
+        };
+        let script_input = |hash: &str, index: u64| TxInput {
+            utxo_ref: UtxoRef::new(hash, index),
+            address: Some(script_addr.to_string()),
+            amount: None,
+        };
+
+        // tx1 creates two states, tx2 splits the first, tx3 merges everything left
+        let tx1 =
+            create_test_transaction("tx1", 1, 10, vec![], vec![script_output(), script_output()]);
+        let tx2 = create_test_transaction(
+            "tx2",
+            2,
+            20,
+            vec![script_input("tx1", 0)],
+            vec![script_output(), script_output()],
+        );
+        let tx3 = create_test_transaction(
+            "tx3",
+            3,
+            30,
+            vec![
+                script_input("tx1", 1),
+                script_input("tx2", 0),
+                script_input("tx2", 1),
+            ],
+            vec![script_output()],
+        );
+
+        let graph =
+            StateGraph::build_from_transactions(&[tx1, tx2, tx3], script_addr, None).unwrap();
+
+        let kinds = graph.transition_kinds();
+        assert_eq!(kinds.len(), 2);
+        assert_eq!(kinds["tx2"], TransitionKind::Split);
+        assert_eq!(kinds["tx3"], TransitionKind::Merge);
+
+        // The Mermaid export declares states rank by rank and annotates both shapes
+        let mermaid = graph.to_mermaid();
+        let declared: Vec<&str> = mermaid
+            .lines()
+            .filter(|line| line.starts_with("    state ") || line.starts_with("    %% layer"))
+            .map(str::trim)
+            .collect();
+        assert_eq!(
+            declared,
+            [
+                "%% layer 0",
+                "state \"tx1#0: 2.00 ADA\" as s_tx1_0",
+                "state \"tx1#1: 2.00 ADA\" as s_tx1_1",
+                "%% layer 1",
+                "state \"tx2#0: 2.00 ADA\" as s_tx2_0",
+                "state \"tx2#1: 2.00 ADA\" as s_tx2_1",
+                "%% layer 2",
+                "state \"tx3#0: 2.00 ADA\" as s_tx3_0",
+            ]
+        );
+        assert!(mermaid.contains("    s_tx1_0 --> s_tx2_0 : transition [split]\n"));
+        assert!(mermaid.contains("    s_tx1_1 --> s_tx3_0 : transition [merge]\n"));
+        assert!(mermaid.contains("    s_tx2_1 --> s_tx3_0 : transition [merge]\n"));
+
+        assert_eq!(
+            graph.layers(),
+            vec![
+                vec!["tx1#0".to_string(), "tx1#1".to_string()],
+                vec!["tx2#0".to_string(), "tx2#1".to_string()],
+                vec!["tx3#0".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_states_with_transition_label() {
+        let mut graph = create_branching_graph();
+        for transition in graph.graph.edge_weights_mut() {
+            let label = if transition.tx_hash == "tx2" {
+                "Split"
+            } else {
+                "Close"
+            };
+            transition.label = Some(label.to_string());
+        }
+
+        assert_eq!(graph.transition_labels(), vec!["Close", "Split"]);
+
+        let mut split: Vec<StateId> = graph
+            .states_with_transition_label("Split")
+            .into_iter()
+            .collect();
+        split.sort();
+        assert_eq!(split, vec!["tx1#0", "tx2#0", "tx2#1"]);
+
+        let mut close: Vec<StateId> = graph
+            .states_with_transition_label("Close")
+            .into_iter()
+            .collect();
+        close.sort();
+        assert_eq!(close, vec!["tx2#0", "tx3#0"]);
+
+        assert!(graph.states_with_transition_label("Unlock").is_empty());
+    }
+
+    #[test]
+    fn test_multiple_script_addresses() {
+        let output = |address: &str| TxOutput {
+            address: address.to_string(),
+            amount: vec![Asset::lovelace(5_000_000)],
+            datum: None,
+            script_ref: None,
+        };
+        // The vault's deposit is moved to the policy address by tx2
+        let transactions = vec![
+            create_test_transaction("tx1", 100, 1000, vec![], vec![output("addr_vault")]),
+            create_test_transaction(
+                "tx2",
+                200,
+                2000,
+                vec![TxInput {
+                    utxo_ref: UtxoRef::new("tx1", 0),
+                    address: Some("addr_vault".to_string()),
+                    amount: None,
+                }],
+                vec![output("addr_policy"), output("addr_wallet")],
+            ),
+        ];
+        let addresses = ["addr_vault".to_string(), "addr_policy".to_string()];
+
+        let graph =
+            StateGraph::build_from_transactions_multi(&transactions, &addresses, None).unwrap();
+        assert_eq!(graph.script_address, "addr_vault");
+        assert!(graph.tracks_address("addr_policy"));
+        assert!(!graph.tracks_address("addr_wallet"));
+        assert_eq!(graph.graph.node_count(), 2);
+        assert_eq!(graph.outgoing_transitions(&"tx1#0".to_string()).len(), 1);
+        assert_eq!(
+            graph
+                .get_state(&"tx1#0".to_string())
+                .unwrap()
+                .metadata
+                .script_address,
+            "addr_vault"
+        );
+        let policy_state = graph.get_state(&"tx2#0".to_string()).unwrap();
+        assert_eq!(policy_state.metadata.script_address, "addr_policy");
+        assert_eq!(policy_state.metadata.classification, StateClass::Completed);
+
+        // States stay top-level, since transitions may not cross composite states,
+        // and are outlined by address instead
+        let mermaid = graph.to_mermaid();
+        assert!(!mermaid.contains('{'));
+        assert!(mermaid.contains("    %% address_0: addr_vault\n    classDef address_0 stroke:"));
+        assert!(mermaid.contains("    %% address_1: addr_policy\n    classDef address_1 stroke:"));
+        assert!(mermaid.contains("    state \"tx2#0: 5.00 ADA\" as s_tx2_0\n"));
+        assert!(mermaid.contains("    s_tx1_0 --> s_tx2_0 : transition\n"));
+        assert!(mermaid.contains("    class s_tx1_0 address_0\n"));
+        assert!(mermaid.contains("    class s_tx2_0 address_1\n"));
+
+        let dot = graph.to_dot();
+        assert!(dot.contains("subgraph \"cluster_0\" {\n    label=\"addr_vault\";\n    \"tx1_0\""));
+        assert!(
+            dot.contains("subgraph \"cluster_1\" {\n    label=\"addr_policy\";\n    \"tx2_0\"")
+        );
+        assert_eq!(graph.to_json()["nodes"][1]["script_address"], "addr_policy");
+
+        // The single-address build only sees the vault
+        let vault = StateGraph::build_from_transactions(&transactions, "addr_vault", None).unwrap();
+        assert_eq!(vault.graph.node_count(), 1);
+        assert_eq!(vault.graph.edge_count(), 0);
+        assert!(!vault.to_dot().contains("subgraph"));
+        assert!(!vault.to_mermaid().contains("address_"));
+
+        assert!(StateGraph::build_from_transactions_multi(&transactions, &[], None).is_err());
+    }
+
+    #[test]
+    fn test_diff_cloned_graph() {
+        let graph = create_branching_graph();
+        let mut snapshot = graph.clone();
+        assert!(graph.diff(&snapshot).is_empty());
+
+        let state = graph.get_state(&"tx4#0".to_string()).unwrap();
+        snapshot.add_state(State::new(
+            UtxoRef::new("tx5", 0),
+            None,
+            state.output.clone(),
+            500,
+            5000,
+            "tx5".to_string(),
+        ));
+
+        let diff = graph.diff(&snapshot);
+        assert_eq!(diff.added_states, vec!["tx5#0"]);
+        assert!(diff.removed_states.is_empty());
+        assert_eq!(diff.newly_terminal, vec!["tx5#0"]);
+
+        let diff = snapshot.diff(&graph);
+        assert!(diff.added_states.is_empty());
+        assert_eq!(diff.removed_states, vec!["tx5#0"]);
+    }
+
+    #[test]
+    fn test_cycle_states_are_active() {
+        // 0 -> 1 -> 2 -> 0, entered from 3 and left to 4
+        let mut graph = StateGraph::new("addr_script".to_string());
+        for i in 0..5 {
+            let tx_hash = format!("tx{}", i);
+            graph.add_state(State::new(
+                UtxoRef::new(&tx_hash, 0),
+                None,
+                TxOutput {
+                    address: "addr_script".to_string(),
+                    amount: vec![Asset::lovelace(1_000_000)],
+                    datum: None,
+                    script_ref: None,
+                },
+                i,
+                i,
+                tx_hash,
+            ));
+        }
+        for (from, to) in [(0, 1), (1, 2), (2, 0), (3, 0), (2, 4)] {
+            graph.add_transition(Transition::new(
+                format!("tx{}#0", from),
+                format!("tx{}#0", to),
+                format!("tx{}", to),
+                None,
+            ));
+        }
+        graph.classify_states(None);
+
+        let class = |id: &str| {
+            graph
+                .get_state(&id.to_string())
+                .unwrap()
+                .metadata
+                .classification
+        };
+        for id in ["tx0#0", "tx1#0", "tx2#0"] {
+            assert_eq!(class(id), StateClass::Active, "{}", id);
+        }
+        assert_eq!(class("tx3#0"), StateClass::Initial);
+        assert_eq!(class("tx4#0"), StateClass::Completed);
+    }
+
+    #[test]
+    fn test_focus_subgraph() {
+        let graph = create_branching_graph();
+        let focused = graph.focus_subgraph(&"tx2#0".to_string());
+
+        // History (tx1#0) + the state itself + future (tx3#0); the sibling and
+        // the unrelated state are dropped
+        assert_eq!(focused.graph.node_count(), 3);
+        assert_eq!(focused.graph.edge_count(), 2);
+        assert!(focused.get_state(&"tx2#1".to_string()).is_none());
+        assert!(focused.get_state(&"tx4#0".to_string()).is_none());
+
+        // Classification is inherited from the full graph
+        let state = focused.get_state(&"tx2#0".to_string()).unwrap();
+        assert_eq!(state.metadata.classification, StateClass::Active);
+    }
 }
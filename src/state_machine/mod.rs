@@ -8,9 +8,9 @@ pub mod state;
 pub mod transition;
 
 // Re-export key types
-pub use graph::{GraphStats, StateGraph};
+pub use graph::{ClassifierFn, DotOptions, GraphDiff, GraphStats, StateGraph};
 pub use state::{State, StateClass, StateId, StateMetadata};
-pub use transition::Transition;
+pub use transition::{Transition, TransitionKind};
 
 /// Build state graph from transactions
 pub fn build_state_graph(
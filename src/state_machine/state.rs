@@ -23,6 +23,10 @@ pub struct StateMetadata {
     pub classification: StateClass,
     pub label: Option<String>,
     pub color: Option<String>,
+    /// Script address holding the state's UTXO, which tells contracts apart in a graph
+    /// tracking several addresses
+    #[serde(default)]
+    pub script_address: String,
 }
 
 /// State classification
@@ -71,6 +75,7 @@ impl State {
         tx_hash: String,
     ) -> Self {
         let id = utxo_ref.to_string();
+        let script_address = output.address.clone();
         Self {
             id,
             utxo_ref,
@@ -83,6 +88,7 @@ impl State {
                 classification: StateClass::Unknown,
                 label: None,
                 color: None,
+                script_address,
             },
         }
     }
@@ -99,13 +105,17 @@ impl State {
     }
 
     /// Get total ADA value in lovelace
+    ///
+    /// Unparseable quantities are skipped; the total saturates at `u64::MAX`.
     pub fn ada_value(&self) -> u64 {
-        self.output
+        let total: u128 = self
+            .output
             .amount
             .iter()
-            .filter(|a| a.unit == "lovelace")
-            .filter_map(|a| a.quantity.parse::<u64>().ok())
-            .sum()
+            .filter(|a| a.is_lovelace())
+            .filter_map(|a| a.amount().ok())
+            .sum();
+        u64::try_from(total).unwrap_or(u64::MAX)
     }
 
     /// Get a short display string
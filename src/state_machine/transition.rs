@@ -1,6 +1,6 @@
 //! Transition representation
 
-use crate::data_source::Redeemer;
+use crate::data_source::{ExUnits, Redeemer};
 use crate::state_machine::StateId;
 use serde::{Deserialize, Serialize};
 
@@ -12,6 +12,10 @@ pub struct Transition {
     pub tx_hash: String,
     pub redeemer: Option<Redeemer>,
     pub label: Option<String>,
+    /// Lovelace gained (positive) or released (negative) by the script across this
+    /// step, when known
+    #[serde(default)]
+    pub value_delta: Option<i64>,
 }
 
 impl Transition {
@@ -27,6 +31,7 @@ impl Transition {
             tx_hash,
             redeemer,
             label: None,
+            value_delta: None,
         }
     }
 
@@ -35,6 +40,11 @@ impl Transition {
         self
     }
 
+    pub fn with_value_delta(mut self, value_delta: i64) -> Self {
+        self.value_delta = Some(value_delta);
+        self
+    }
+
     /// Get display label for the transition
     pub fn display_label(&self) -> String {
         if let Some(label) = &self.label {
@@ -45,4 +55,75 @@ impl Transition {
             "transition".to_string()
         }
     }
+
+    /// Display label followed by the ADA moved, e.g. `Unlock (-2500 ADA)`
+    ///
+    /// [`Transition::display_label`] stays free of amounts so it can group transitions.
+    pub fn display_label_with_value(&self) -> String {
+        match self.value_delta {
+            Some(delta) => format!(
+                "{} ({:+} ADA)",
+                self.display_label(),
+                delta as f64 / 1_000_000.0
+            ),
+            None => self.display_label(),
+        }
+    }
+
+    /// Execution units budgeted by the redeemer, if the transition has one
+    pub fn ex_units(&self) -> Option<ExUnits> {
+        self.redeemer.as_ref().map(|redeemer| redeemer.ex_units)
+    }
+}
+
+/// Shape of the transaction behind a transition, by script UTXOs consumed and created
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TransitionKind {
+    /// One state consumed, one state created
+    Simple,
+    /// Many states consumed into one
+    Merge,
+    /// One state consumed into many
+    Split,
+    /// Many states consumed, many created
+    ManyToMany,
+}
+
+impl TransitionKind {
+    /// Classify a transaction from the number of script states it consumes and creates
+    pub fn from_counts(consumed: usize, created: usize) -> Self {
+        match (consumed > 1, created > 1) {
+            (false, false) => TransitionKind::Simple,
+            (true, false) => TransitionKind::Merge,
+            (false, true) => TransitionKind::Split,
+            (true, true) => TransitionKind::ManyToMany,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_label_with_value() {
+        let transition = Transition::new("a#0".into(), "b#0".into(), "b".into(), None)
+            .with_label("Unlock".to_string());
+        assert_eq!(transition.display_label_with_value(), "Unlock");
+
+        let transition = transition.with_value_delta(-2_500_000_000);
+        assert_eq!(transition.display_label_with_value(), "Unlock (-2500 ADA)");
+        assert_eq!(transition.display_label(), "Unlock");
+    }
+
+    #[test]
+    fn test_transition_kind_from_counts() {
+        assert_eq!(TransitionKind::from_counts(1, 1), TransitionKind::Simple);
+        assert_eq!(TransitionKind::from_counts(3, 1), TransitionKind::Merge);
+        assert_eq!(TransitionKind::from_counts(1, 2), TransitionKind::Split);
+        assert_eq!(
+            TransitionKind::from_counts(2, 2),
+            TransitionKind::ManyToMany
+        );
+    }
 }
@@ -1,16 +1,20 @@
 //! TUI application state
 
-use crate::data_source::Transaction;
+use super::theme::Theme;
+use crate::data_source::{Transaction, TxInput, TxOutput};
 use crate::state_machine::{
-    State, StateGraph, StateId,
+    State, StateClass, StateGraph, StateId, Transition,
     analyzer::{AnalysisReport, detect_pattern},
 };
+use petgraph::Direction;
 use ratatui::widgets::ListState;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 /// TUI application state
 pub struct App {
     pub state_graph: StateGraph,
     pub analysis_report: AnalysisReport,
+    pub state_depths: HashMap<StateId, usize>, // Transitions from the nearest initial state
     pub selected_state_index: usize,
     pub state_list_state: ListState,
     pub selected_transaction_index: usize,
@@ -18,17 +22,38 @@ pub struct App {
     pub view_mode: ViewMode,
     pub should_quit: bool,
     pub show_hex_view: bool, // For datum inspector: hex vs decoded view
+    pub focused_state: Option<StateId>,
+    pub live_only: bool, // Only list terminal states still unspent at the script
+    pub show_legend: bool, // Graph overview: state color legend overlay
+    pub theme: Theme,
+    pub status_message: Option<String>, // Transient status, e.g. while refreshing
+    pub notice: Option<String>,         // Footer confirmation, cleared by the next key press
+    pub search_query: Option<String>,   // Substring filter on state ids and transaction hashes
+    pub search_active: bool,            // Typing into the search query
+    pub transition_filter: Option<String>, // Transition label whose states are highlighted
+    pub datum_scroll: u16,              // Datum inspector: first visible line
+    datum_max_scroll: u16,              // Datum inspector: content lines below the viewport
+    pub detail_scroll: u16,             // State detail: first visible line of the state information
+    detail_max_scroll: u16,             // State detail: content lines below the viewport
+    full_graph: Option<StateGraph>,     // Unfocused graph, kept while a focus is active
+    /// State detail: route from the nearest initial state
+    pub selected_path: Option<(StateId, Vec<Transition>)>,
     states_list: Vec<StateId>,
-    transactions: Vec<Transaction>,
+    transactions: Vec<Transaction>, // Transactions matching the search query
+    all_transactions: Vec<Transaction>,
     view_stack: Vec<ViewMode>,
 }
 
+/// Items skipped by PageUp/PageDown in list views
+const PAGE_SIZE: usize = 10;
+
 /// View modes
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ViewMode {
     GraphOverview,
     StateDetail,
     TransactionList,
+    TransactionDetail,
     DatumInspector,
     PatternAnalysis,
     Help,
@@ -38,6 +63,7 @@ impl App {
     pub fn new(state_graph: StateGraph, transactions: Vec<Transaction>) -> Self {
         // Run pattern analysis
         let analysis_report = detect_pattern(&state_graph);
+        let state_depths = state_depths(&state_graph);
 
         // Get all states and sort by block/slot for consistent ordering
         let mut states: Vec<&State> = state_graph.all_states();
@@ -57,6 +83,7 @@ impl App {
         Self {
             state_graph,
             analysis_report,
+            state_depths,
             selected_state_index: 0,
             state_list_state,
             selected_transaction_index: 0,
@@ -64,12 +91,33 @@ impl App {
             view_mode: ViewMode::GraphOverview,
             should_quit: false,
             show_hex_view: false,
+            focused_state: None,
+            live_only: false,
+            show_legend: false,
+            theme: Theme::default(),
+            status_message: None,
+            notice: None,
+            search_query: None,
+            search_active: false,
+            transition_filter: None,
+            datum_scroll: 0,
+            datum_max_scroll: 0,
+            detail_scroll: 0,
+            selected_path: None,
+            detail_max_scroll: 0,
+            full_graph: None,
             states_list,
-            transactions,
+            transactions: transactions.clone(),
+            all_transactions: transactions,
             view_stack: Vec::new(),
         }
     }
 
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
     pub fn quit(&mut self) {
         self.should_quit = true;
     }
@@ -94,6 +142,7 @@ impl App {
 
     pub fn select_next(&mut self) {
         if !self.states_list.is_empty() {
+            self.detail_scroll = 0;
             self.selected_state_index = (self.selected_state_index + 1) % self.states_list.len();
             self.state_list_state
                 .select(Some(self.selected_state_index));
@@ -102,6 +151,7 @@ impl App {
 
     pub fn select_previous(&mut self) {
         if !self.states_list.is_empty() {
+            self.detail_scroll = 0;
             if self.selected_state_index == 0 {
                 self.selected_state_index = self.states_list.len() - 1;
             } else {
@@ -112,6 +162,60 @@ impl App {
         }
     }
 
+    /// Move the selection in the current list down by a page, stopping at the last item
+    pub fn page_down(&mut self) {
+        let (index, len) = self.list_position();
+        self.select_list_index((index + PAGE_SIZE).min(len.saturating_sub(1)));
+    }
+
+    /// Move the selection in the current list up by a page, stopping at the first item
+    pub fn page_up(&mut self) {
+        let (index, _) = self.list_position();
+        self.select_list_index(index.saturating_sub(PAGE_SIZE));
+    }
+
+    /// Select the first item of the current list
+    pub fn select_first(&mut self) {
+        self.select_list_index(0);
+    }
+
+    /// Select the last item of the current list
+    pub fn select_last(&mut self) {
+        let (_, len) = self.list_position();
+        self.select_list_index(len.saturating_sub(1));
+    }
+
+    /// Whether the current view navigates transactions rather than states
+    fn is_transaction_view(&self) -> bool {
+        matches!(
+            self.view_mode,
+            ViewMode::TransactionList | ViewMode::TransactionDetail
+        )
+    }
+
+    /// Selected index and length of the list the current view navigates
+    fn list_position(&self) -> (usize, usize) {
+        if self.is_transaction_view() {
+            (self.selected_transaction_index, self.transactions.len())
+        } else {
+            (self.selected_state_index, self.states_list.len())
+        }
+    }
+
+    fn select_list_index(&mut self, index: usize) {
+        if self.is_transaction_view() {
+            if !self.transactions.is_empty() {
+                self.datum_scroll = 0;
+                self.selected_transaction_index = index;
+                self.transaction_list_state.select(Some(index));
+            }
+        } else if !self.states_list.is_empty() {
+            self.detail_scroll = 0;
+            self.selected_state_index = index;
+            self.state_list_state.select(Some(index));
+        }
+    }
+
     pub fn get_selected_state(&self) -> Option<&State> {
         self.states_list
             .get(self.selected_state_index)
@@ -124,6 +228,7 @@ impl App {
 
     pub fn select_next_transaction(&mut self) {
         if !self.transactions.is_empty() {
+            self.datum_scroll = 0;
             self.selected_transaction_index =
                 (self.selected_transaction_index + 1) % self.transactions.len();
             self.transaction_list_state
@@ -133,6 +238,7 @@ impl App {
 
     pub fn select_previous_transaction(&mut self) {
         if !self.transactions.is_empty() {
+            self.datum_scroll = 0;
             if self.selected_transaction_index == 0 {
                 self.selected_transaction_index = self.transactions.len() - 1;
             } else {
@@ -151,22 +257,406 @@ impl App {
         &self.transactions
     }
 
+    /// States the selected transaction consumed and created, as (consumed, created)
+    pub fn states_for_selected_transaction(&self) -> (Vec<StateId>, Vec<StateId>) {
+        let Some(tx) = self.get_selected_transaction() else {
+            return (vec![], vec![]);
+        };
+        let in_graph = |id: &StateId| self.state_graph.state_index.contains_key(id);
+
+        let consumed = tx
+            .inputs
+            .iter()
+            .map(|input| input.utxo_ref.to_string())
+            .filter(in_graph)
+            .collect();
+        let created = (0..tx.outputs.len())
+            .map(|idx| format!("{}#{}", tx.hash, idx))
+            .filter(in_graph)
+            .collect();
+        (consumed, created)
+    }
+
+    /// Switch to the graph overview with the first state the selected transaction
+    /// created selected, or the first it consumed if it created none
+    pub fn show_states_for_selected_transaction(&mut self) {
+        let (consumed, created) = self.states_for_selected_transaction();
+        let Some(id) = created.first().or(consumed.first()) else {
+            self.set_status("Transaction does not touch any script state".to_string());
+            return;
+        };
+        let Some(pos) = self.states_list.iter().position(|listed| listed == id) else {
+            self.set_status(format!("State {} is hidden by the active filter", id));
+            return;
+        };
+
+        self.set_view_mode(ViewMode::GraphOverview);
+        self.select_list_index(pos);
+    }
+
+    /// Whether an input spends an output held by a tracked script address
+    pub fn is_script_input(&self, input: &TxInput) -> bool {
+        input
+            .address
+            .as_deref()
+            .is_some_and(|address| self.state_graph.tracks_address(address))
+            || self
+                .state_graph
+                .state_index
+                .contains_key(&input.utxo_ref.to_string())
+    }
+
+    /// Whether an output is locked at a tracked script address
+    pub fn is_script_output(&self, output: &TxOutput) -> bool {
+        self.state_graph.tracks_address(&output.address)
+    }
+
     pub fn toggle_hex_view(&mut self) {
         self.show_hex_view = !self.show_hex_view;
+        self.datum_scroll = 0;
     }
 
-    pub fn update_data(&mut self, state_graph: StateGraph, transactions: Vec<Transaction>) {
-        // Run pattern analysis
-        self.analysis_report = detect_pattern(&state_graph);
+    /// Record how many lines the datum inspector content takes and how many fit on
+    /// screen, so scrolling stops at the last line
+    pub fn set_datum_content_height(&mut self, content_lines: u16, viewport_lines: u16) {
+        self.datum_max_scroll = content_lines.saturating_sub(viewport_lines);
+        self.datum_scroll = self.datum_scroll.min(self.datum_max_scroll);
+    }
+
+    pub fn scroll_datum_down(&mut self, lines: u16) {
+        self.datum_scroll = self
+            .datum_scroll
+            .saturating_add(lines)
+            .min(self.datum_max_scroll);
+    }
+
+    pub fn scroll_datum_up(&mut self, lines: u16) {
+        self.datum_scroll = self.datum_scroll.saturating_sub(lines);
+    }
+
+    /// Record how many lines the state information takes and how many fit on screen,
+    /// so scrolling stops at the last line
+    pub fn set_detail_content_height(&mut self, content_lines: u16, viewport_lines: u16) {
+        self.detail_max_scroll = content_lines.saturating_sub(viewport_lines);
+        self.detail_scroll = self.detail_scroll.min(self.detail_max_scroll);
+    }
+
+    pub fn scroll_detail_down(&mut self, lines: u16) {
+        self.detail_scroll = self
+            .detail_scroll
+            .saturating_add(lines)
+            .min(self.detail_max_scroll);
+    }
+
+    pub fn scroll_detail_up(&mut self, lines: u16) {
+        self.detail_scroll = self.detail_scroll.saturating_sub(lines);
+    }
+
+    /// Trace the route from the nearest initial state to the selected state
+    ///
+    /// The route is kept until a path for another state is requested, and only shown
+    /// while that state is selected.
+    pub fn show_path_to_selected(&mut self) {
+        let Some(id) = self.states_list.get(self.selected_state_index).cloned() else {
+            return;
+        };
+
+        // Ancestors come in BFS order, so the first initial one is the nearest
+        let nearest_initial = std::iter::once(id.clone())
+            .chain(self.state_graph.ancestors_of(&id))
+            .find(|ancestor| self.state_graph.degree(ancestor).0 == 0);
+        let path = nearest_initial.and_then(|from| self.state_graph.find_path(&from, &id));
+
+        match path {
+            Some(path) if path.is_empty() => {
+                self.notice = Some("Selected state is an initial state".to_string());
+            }
+            Some(path) => {
+                self.notice = Some(format!("Path: {} transitions", path.len()));
+                self.selected_path = Some((id, path));
+            }
+            None => {
+                self.notice = Some("No path from an initial state".to_string());
+            }
+        }
+    }
+
+    /// Route traced by [`App::show_path_to_selected`], if it leads to the selected state
+    pub fn path_to_selected(&self) -> Option<&[Transition]> {
+        let (id, path) = self.selected_path.as_ref()?;
+        (self.states_list.get(self.selected_state_index) == Some(id)).then_some(path.as_slice())
+    }
+
+    /// Focus the view on the selected state's lineage (its history and future)
+    pub fn focus_selected_state(&mut self) {
+        let Some(id) = self.states_list.get(self.selected_state_index).cloned() else {
+            return;
+        };
+
+        let focused = self.state_graph.focus_subgraph(&id);
+        if self.full_graph.is_none() {
+            let full_graph = std::mem::replace(&mut self.state_graph, focused);
+            self.full_graph = Some(full_graph);
+        } else {
+            self.state_graph = focused;
+        }
+        self.focused_state = Some(id);
+        self.refresh_graph();
+    }
+
+    /// Restore the full graph after a focus
+    pub fn clear_focus(&mut self) {
+        if let Some(full_graph) = self.full_graph.take() {
+            self.state_graph = full_graph;
+            self.focused_state = None;
+            self.refresh_graph();
+        }
+    }
+
+    pub fn toggle_focus(&mut self) {
+        if self.focused_state.is_some() {
+            self.clear_focus();
+        } else {
+            self.focus_selected_state();
+        }
+    }
+
+    /// Toggle listing only live states (terminal and still unspent)
+    pub fn toggle_live_filter(&mut self) {
+        self.live_only = !self.live_only;
+        self.refresh_graph();
+    }
+
+    pub fn toggle_legend(&mut self) {
+        self.show_legend = !self.show_legend;
+    }
+
+    /// Number of states per classification shown in the graph overview footer
+    ///
+    /// Initial states are counted by topology (no incoming transitions), the rest by
+    /// their classification.
+    pub fn class_counts(&self) -> [(StateClass, usize); 5] {
+        let stats = self.state_graph.stats();
+        [
+            (StateClass::Initial, stats.initial_states),
+            (StateClass::Active, stats.active_states),
+            (StateClass::Locked, stats.locked_states),
+            (StateClass::Completed, stats.completed_states),
+            (StateClass::Failed, stats.failed_states),
+        ]
+    }
+
+    /// Highlight the states touched by the next transition label, then none again
+    pub fn cycle_transition_filter(&mut self) {
+        let labels = self.state_graph.transition_labels();
+        let next = match &self.transition_filter {
+            Some(current) => labels
+                .iter()
+                .position(|label| label == current)
+                .and_then(|pos| labels.get(pos + 1)),
+            None => labels.first(),
+        };
+        self.transition_filter = next.cloned();
+    }
 
-        // Preserve selected state ID if possible
-        let selected_id = self.states_list.get(self.selected_state_index).cloned();
+    /// States touched by a transition matching the filter, or `None` without a filter
+    pub fn transition_filter_states(&self) -> Option<HashSet<StateId>> {
+        self.transition_filter
+            .as_ref()
+            .map(|label| self.state_graph.states_with_transition_label(label))
+    }
+
+    /// Plain-text details of what is selected in the current view, for copying out
+    /// of the TUI: the state id and its datum, or the transaction hash (with its
+    /// datums in the datum inspector). Empty when nothing is selected.
+    pub fn dump_selection(&self) -> String {
+        match self.view_mode {
+            ViewMode::GraphOverview | ViewMode::StateDetail | ViewMode::PatternAnalysis => {
+                let Some(state) = self.get_selected_state() else {
+                    return String::new();
+                };
+                match &state.datum {
+                    Some(datum) => format!(
+                        "State: {}\nDatum hash: {}\nDatum CBOR: {}\n",
+                        state.id,
+                        datum.hash,
+                        hex::encode(&datum.raw_cbor)
+                    ),
+                    None => format!("State: {}\nDatum: none\n", state.id),
+                }
+            }
+            ViewMode::TransactionList | ViewMode::TransactionDetail => self
+                .get_selected_transaction()
+                .map(|tx| format!("Transaction: {}\n", tx.hash))
+                .unwrap_or_default(),
+            ViewMode::DatumInspector => {
+                let Some(tx) = self.get_selected_transaction() else {
+                    return String::new();
+                };
+                let mut dump = format!("Transaction: {}\n", tx.hash);
+                for (idx, output) in tx.outputs.iter().enumerate() {
+                    if let Ok(Some(datum)) =
+                        crate::parser::datum::extract_datum(output, Some(&tx.witnesses))
+                    {
+                        dump.push_str(&format!(
+                            "Output #{}: {}\nDatum CBOR: {}\n",
+                            idx,
+                            datum.hash,
+                            hex::encode(&datum.raw_cbor)
+                        ));
+                    }
+                }
+                dump
+            }
+            ViewMode::Help => String::new(),
+        }
+    }
+
+    pub fn set_status(&mut self, message: String) {
+        self.status_message = Some(message);
+    }
 
-        self.state_graph = state_graph;
-        self.transactions = transactions;
+    /// Replace the displayed data, keeping the selected state and transaction
+    ///
+    /// Incremental updates (the previous graph extended with new transactions) report
+    /// how many transactions arrived in the status line.
+    pub fn update_data(
+        &mut self,
+        state_graph: StateGraph,
+        transactions: Vec<Transaction>,
+        incremental: bool,
+    ) {
+        self.status_message = None;
+        if incremental {
+            let known: HashSet<&str> = self
+                .all_transactions
+                .iter()
+                .map(|tx| tx.hash.as_str())
+                .collect();
+            let new_count = transactions
+                .iter()
+                .filter(|tx| !known.contains(tx.hash.as_str()))
+                .count();
+            if new_count > 0 {
+                self.status_message = Some(format!(
+                    "{} new transaction{}",
+                    new_count,
+                    if new_count == 1 { "" } else { "s" }
+                ));
+            }
+        }
+        // Keep the current focus if the focused state still exists
+        match &self.focused_state {
+            Some(id) if state_graph.get_state(id).is_some() => {
+                self.state_graph = state_graph.focus_subgraph(id);
+                self.full_graph = Some(state_graph);
+            }
+            _ => {
+                self.state_graph = state_graph;
+                self.full_graph = None;
+                self.focused_state = None;
+            }
+        }
+        if let Some(label) = &self.transition_filter
+            && !self.state_graph.transition_labels().contains(label)
+        {
+            self.transition_filter = None;
+        }
+        self.all_transactions = transactions;
+        self.refresh_graph();
+        self.refresh_transactions();
+    }
+
+    /// Start typing a search query, keeping any current one
+    pub fn start_search(&mut self) {
+        self.search_active = true;
+        self.search_query.get_or_insert_with(String::new);
+    }
+
+    pub fn push_search_char(&mut self, c: char) {
+        self.search_query.get_or_insert_with(String::new).push(c);
+        self.apply_search();
+    }
+
+    pub fn pop_search_char(&mut self) {
+        if let Some(query) = &mut self.search_query {
+            query.pop();
+        }
+        self.apply_search();
+    }
+
+    /// Stop typing and keep the current query as a filter
+    pub fn finish_search(&mut self) {
+        self.search_active = false;
+        if self.search_query.as_deref() == Some("") {
+            self.search_query = None;
+        }
+    }
+
+    /// Stop typing and show everything again
+    pub fn clear_search(&mut self) {
+        self.search_active = false;
+        self.search_query = None;
+        self.apply_search();
+    }
+
+    /// Filter the state and transaction lists by the current query
+    pub fn apply_search(&mut self) {
+        self.refresh_graph();
+        self.refresh_transactions();
+    }
+
+    /// Whether an id or hash matches the search query (everything matches without one)
+    fn matches_search(&self, text: &str) -> bool {
+        self.search_query
+            .as_deref()
+            .is_none_or(|query| text.to_lowercase().contains(&query.to_lowercase()))
+    }
+
+    /// Rebuild the transaction list from the search query, preserving the selected
+    /// transaction if it is still listed
+    fn refresh_transactions(&mut self) {
+        let selected_tx_hash = self.get_selected_transaction().map(|tx| tx.hash.clone());
+
+        self.transactions = self
+            .all_transactions
+            .iter()
+            .filter(|tx| self.matches_search(&tx.hash))
+            .cloned()
+            .collect();
+
+        // Restore the selected transaction by hash, or reset if it is gone
+        let position = selected_tx_hash
+            .and_then(|hash| self.transactions.iter().position(|tx| tx.hash == hash));
+        self.selected_transaction_index = position.unwrap_or(0);
+        self.transaction_list_state
+            .select(if !self.transactions.is_empty() {
+                Some(self.selected_transaction_index)
+            } else {
+                None
+            });
+    }
+
+    /// Re-run analysis and rebuild the state list after the displayed graph changed,
+    /// preserving the selected state if it is still present
+    fn refresh_graph(&mut self) {
+        // Run pattern analysis
+        self.analysis_report = detect_pattern(&self.state_graph);
+        self.state_depths = state_depths(&self.state_graph);
+
+        // Preserve selected state ID if possible (the focused state takes precedence)
+        let selected_id = self
+            .focused_state
+            .clone()
+            .or_else(|| self.states_list.get(self.selected_state_index).cloned());
 
         // Rebuild states list
-        let mut states: Vec<&State> = self.state_graph.all_states();
+        let mut states: Vec<&State> = if self.live_only {
+            self.state_graph.live_states()
+        } else {
+            self.state_graph.all_states()
+        };
+        states.retain(|s| self.matches_search(&s.id));
         states.sort_by_key(|s| (s.block, s.slot));
         self.states_list = states.iter().map(|s| s.id.clone()).collect();
 
@@ -193,16 +683,38 @@ impl App {
                     None
                 });
         }
+    }
+}
+
+/// Depth of each state below its nearest initial state (one without incoming
+/// transitions), found by a breadth-first walk from all initial states at once
+///
+/// States only reachable through a cycle with no initial state have no depth.
+fn state_depths(graph: &StateGraph) -> HashMap<StateId, usize> {
+    let mut depths = HashMap::new();
+    let mut queue = VecDeque::new();
+    for node in graph.graph.node_indices() {
+        if graph
+            .graph
+            .neighbors_directed(node, Direction::Incoming)
+            .next()
+            .is_none()
+        {
+            depths.insert(graph.graph[node].id.clone(), 0);
+            queue.push_back((node, 0));
+        }
+    }
 
-        // Reset transaction selection (simplification)
-        self.selected_transaction_index = 0;
-        self.transaction_list_state
-            .select(if !self.transactions.is_empty() {
-                Some(0)
-            } else {
-                None
-            });
+    while let Some((node, depth)) = queue.pop_front() {
+        for next in graph.graph.neighbors_directed(node, Direction::Outgoing) {
+            let id = &graph.graph[next].id;
+            if !depths.contains_key(id) {
+                depths.insert(id.clone(), depth + 1);
+                queue.push_back((next, depth + 1));
+            }
+        }
     }
+    depths
 }
 
 #[cfg(test)]
@@ -226,6 +738,8 @@ mod tests {
             outputs,
             witnesses: Witnesses::default(),
             metadata: None,
+            size: None,
+            valid: true,
         }
     }
 
@@ -244,6 +758,7 @@ mod tests {
                     hash: "datum1".to_string(),
                     raw_cbor: vec![0x01],
                     parsed: None,
+                    reencoded: false,
                 }),
                 script_ref: None,
             }],
@@ -265,6 +780,7 @@ mod tests {
                     hash: "datum2".to_string(),
                     raw_cbor: vec![0x02],
                     parsed: None,
+                    reencoded: false,
                 }),
                 script_ref: None,
             }],
@@ -288,6 +804,103 @@ mod tests {
         (graph, transactions)
     }
 
+    #[test]
+    fn test_app_state_depths_in_tree() {
+        let script_addr = "addr_script";
+        let output = || TxOutput {
+            address: script_addr.to_string(),
+            amount: vec![Asset::lovelace(2_000_000)],
+            datum: None,
+            script_ref: None,
+        };
+        let input = |hash: &str, index| TxInput {
+            utxo_ref: UtxoRef::new(hash, index),
+            address: Some(script_addr.to_string()),
+            amount: None,
+        };
+
+        // a#0 -> b#0 -> c#0
+        //     -> b#1
+        let transactions = vec![
+            create_test_transaction("a", 100, 1000, vec![], vec![output()]),
+            create_test_transaction(
+                "b",
+                200,
+                2000,
+                vec![input("a", 0)],
+                vec![output(), output()],
+            ),
+            create_test_transaction("c", 300, 3000, vec![input("b", 0)], vec![output()]),
+        ];
+        let graph = StateGraph::build_from_transactions(&transactions, script_addr, None).unwrap();
+        let app = App::new(graph, transactions);
+
+        assert_eq!(app.state_depths["a#0"], 0);
+        assert_eq!(app.state_depths["b#0"], 1);
+        assert_eq!(app.state_depths["b#1"], 1);
+        assert_eq!(app.state_depths["c#0"], 2);
+    }
+
+    #[test]
+    fn test_app_path_to_selected() {
+        let (graph, transactions) = create_test_data();
+        let mut app = App::new(graph, transactions);
+
+        app.show_path_to_selected();
+        assert!(app.path_to_selected().is_none());
+        assert_eq!(
+            app.notice.as_deref(),
+            Some("Selected state is an initial state")
+        );
+
+        while app.get_selected_state().unwrap().id != "tx2#0" {
+            app.select_next();
+        }
+        app.show_path_to_selected();
+        let path = app.path_to_selected().unwrap();
+        assert_eq!(path.len(), 1);
+        assert_eq!(path[0].from_state, "tx1#0");
+        assert_eq!(path[0].to_state, "tx2#0");
+
+        app.select_previous();
+        assert!(app.path_to_selected().is_none());
+    }
+
+    #[test]
+    fn test_app_dump_selection() {
+        let (graph, transactions) = create_test_data();
+        let mut app = App::new(graph, transactions);
+
+        for mode in [
+            ViewMode::GraphOverview,
+            ViewMode::StateDetail,
+            ViewMode::PatternAnalysis,
+        ] {
+            app.set_view_mode(mode);
+            assert_eq!(
+                app.dump_selection(),
+                "State: tx1#0\nDatum hash: datum1\nDatum CBOR: 01\n"
+            );
+        }
+        app.select_next();
+        assert_eq!(app.dump_selection(), "State: tx3#0\nDatum: none\n");
+
+        app.select_next_transaction();
+        for mode in [ViewMode::TransactionList, ViewMode::TransactionDetail] {
+            app.set_view_mode(mode);
+            assert_eq!(app.dump_selection(), "Transaction: tx2\n");
+        }
+
+        app.set_view_mode(ViewMode::DatumInspector);
+        assert_eq!(
+            app.dump_selection(),
+            "Transaction: tx2\nOutput #0: datum2\nDatum CBOR: 02\n"
+        );
+
+        app.set_view_mode(ViewMode::Help);
+        assert_eq!(app.dump_selection(), "");
+    }
+
     #[test]
     fn test_app_creation() {
         let (graph, transactions) = create_test_data();
@@ -322,6 +935,80 @@ mod tests {
         assert_eq!(app.selected_state_index, 1);
     }
 
+    /// An app with `n` single-output transactions, each creating a state
+    fn create_long_app(n: usize) -> App {
+        let transactions: Vec<Transaction> = (0..n)
+            .map(|i| {
+                create_test_transaction(
+                    &format!("tx{}", i),
+                    i as u64,
+                    i as u64,
+                    vec![],
+                    vec![TxOutput {
+                        address: "addr_script".to_string(),
+                        amount: vec![Asset::lovelace(1_000_000)],
+                        datum: None,
+                        script_ref: None,
+                    }],
+                )
+            })
+            .collect();
+        let graph =
+            StateGraph::build_from_transactions(&transactions, "addr_script", None).unwrap();
+        App::new(graph, transactions)
+    }
+
+    #[test]
+    fn test_app_page_down_and_up() {
+        let mut app = create_long_app(25);
+
+        app.page_down();
+        assert_eq!(app.selected_state_index, 10);
+        assert_eq!(app.state_list_state.selected(), Some(10));
+        app.page_down();
+        app.page_down();
+        assert_eq!(app.selected_state_index, 24); // Clamped, no wrap
+
+        app.page_up();
+        assert_eq!(app.selected_state_index, 14);
+        app.page_up();
+        app.page_up();
+        assert_eq!(app.selected_state_index, 0);
+        assert_eq!(app.state_list_state.selected(), Some(0));
+
+        // Transaction views page through transactions instead
+        app.set_view_mode(ViewMode::TransactionList);
+        app.page_down();
+        assert_eq!(app.selected_transaction_index, 10);
+        assert_eq!(app.transaction_list_state.selected(), Some(10));
+        assert_eq!(app.selected_state_index, 0);
+    }
+
+    #[test]
+    fn test_app_select_first_and_last() {
+        let mut app = create_long_app(25);
+
+        app.select_last();
+        assert_eq!(app.selected_state_index, 24);
+        assert_eq!(app.state_list_state.selected(), Some(24));
+        app.select_first();
+        assert_eq!(app.selected_state_index, 0);
+
+        app.set_view_mode(ViewMode::TransactionList);
+        app.select_last();
+        assert_eq!(app.selected_transaction_index, 24);
+        assert_eq!(app.transaction_list_state.selected(), Some(24));
+        app.select_first();
+        assert_eq!(app.selected_transaction_index, 0);
+
+        // Empty lists keep nothing selected
+        let mut app = create_long_app(0);
+        app.select_last();
+        app.page_down();
+        assert_eq!(app.selected_state_index, 0);
+        assert_eq!(app.state_list_state.selected(), None);
+    }
+
     #[test]
     fn test_app_view_modes() {
         let (graph, transactions) = create_test_data();
@@ -418,4 +1105,296 @@ mod tests {
         app.select_previous_transaction();
         assert_eq!(app.selected_transaction_index, 2); // Wrapped backward
     }
+
+    #[test]
+    fn test_app_focus_selected_state() {
+        let (graph, transactions) = create_test_data();
+        let mut app = App::new(graph, transactions);
+
+        // Select tx2#0 (sorted: tx1#0, tx3#0, tx2#0) and focus on its lineage
+        app.select_previous();
+        assert_eq!(app.get_selected_state().unwrap().id, "tx2#0");
+        app.toggle_focus();
+
+        assert_eq!(app.focused_state.as_deref(), Some("tx2#0"));
+        assert_eq!(app.states_list(), ["tx1#0", "tx2#0"]);
+        assert_eq!(app.get_selected_state().unwrap().id, "tx2#0");
+
+        // Toggling again restores the full graph and keeps the selection
+        app.toggle_focus();
+        assert!(app.focused_state.is_none());
+        assert_eq!(app.states_list().len(), 3);
+        assert_eq!(app.get_selected_state().unwrap().id, "tx2#0");
+    }
+
+    #[test]
+    fn test_app_live_filter() {
+        let (mut graph, transactions) = create_test_data();
+        graph.set_unspent_utxos([UtxoRef::new("tx2", 0)]);
+        let mut app = App::new(graph, transactions);
+
+        app.toggle_live_filter();
+        assert_eq!(app.states_list(), ["tx2#0"]);
+
+        app.toggle_live_filter();
+        assert_eq!(app.states_list().len(), 3);
+    }
+
+    #[test]
+    fn test_app_class_counts() {
+        let (mut graph, transactions) = create_test_data();
+        let failed = graph.state_index["tx2#0"];
+        graph.graph[failed].metadata.classification = StateClass::Failed;
+        let mut app = App::new(graph, transactions);
+
+        // tx1#0 and the unlinked tx3#0 have no incoming transitions
+        assert_eq!(
+            app.class_counts(),
+            [
+                (StateClass::Initial, 2),
+                (StateClass::Active, 0),
+                (StateClass::Locked, 0),
+                (StateClass::Completed, 0),
+                (StateClass::Failed, 1),
+            ]
+        );
+
+        assert!(!app.show_legend);
+        app.toggle_legend();
+        assert!(app.show_legend);
+    }
+
+    #[test]
+    fn test_app_cycle_transition_filter() {
+        let (mut graph, transactions) = create_test_data();
+        for transition in graph.graph.edge_weights_mut() {
+            transition.label = Some("Unlock".to_string());
+        }
+        let mut app = App::new(graph, transactions.clone());
+        assert!(app.transition_filter_states().is_none());
+
+        app.cycle_transition_filter();
+        assert_eq!(app.transition_filter.as_deref(), Some("Unlock"));
+        let states = app.transition_filter_states().unwrap();
+        assert!(states.contains("tx1#0") && states.contains("tx2#0"));
+        assert!(!states.contains("tx3#0"));
+
+        app.cycle_transition_filter();
+        assert!(app.transition_filter.is_none());
+
+        // A label missing from refreshed data drops the filter
+        app.cycle_transition_filter();
+        let (graph, _) = create_test_data();
+        app.update_data(graph, transactions, false);
+        assert!(app.transition_filter.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_app_states_for_selected_transaction() {
+        use crate::data_source::mock::{MOCK_SCRIPT_ADDRESS, MockDataSource};
+        use crate::data_source::{DataSource, QueryParams};
+
+        let transactions = MockDataSource::new()
+            .get_transactions_by_address(MOCK_SCRIPT_ADDRESS, QueryParams::default())
+            .await
+            .unwrap();
+        let graph =
+            StateGraph::build_from_transactions(&transactions, MOCK_SCRIPT_ADDRESS, None).unwrap();
+        let mut app = App::new(graph, transactions);
+        let hash = |n: u8| format!("{:064x}", n);
+
+        // The first unlock spends the lock output and re-locks the remainder
+        app.set_view_mode(ViewMode::TransactionList);
+        app.select_next_transaction();
+        assert_eq!(app.get_selected_transaction().unwrap().hash, hash(2));
+        let (consumed, created) = app.states_for_selected_transaction();
+        assert_eq!(consumed, [format!("{}#0", hash(1))]);
+        assert_eq!(created, [format!("{}#1", hash(2))]);
+
+        app.show_states_for_selected_transaction();
+        assert_eq!(app.view_mode, ViewMode::GraphOverview);
+        assert_eq!(app.get_selected_state().unwrap().id, created[0]);
+    }
+
+    #[test]
+    fn test_app_script_inputs_and_outputs() {
+        let (graph, transactions) = create_test_data();
+        let app = App::new(graph, transactions);
+
+        let tx2 = &app.transactions()[1];
+        assert!(app.is_script_input(&tx2.inputs[0]));
+        assert!(app.is_script_output(&tx2.outputs[0]));
+
+        let wallet_input = TxInput {
+            utxo_ref: UtxoRef::new("wallet_tx", 1),
+            address: Some("addr_wallet".to_string()),
+            amount: None,
+        };
+        assert!(!app.is_script_input(&wallet_input));
+    }
+
+    #[test]
+    fn test_app_update_preserves_selected_transaction() {
+        let (graph, transactions) = create_test_data();
+        let mut app = App::new(graph, transactions);
+        app.select_next_transaction();
+        assert_eq!(app.get_selected_transaction().unwrap().hash, "tx2");
+
+        // A new transaction arrives at the top of the list
+        let (graph, mut transactions) = create_test_data();
+        let new_tx = create_test_transaction("tx0", 300, 3000, vec![], vec![]);
+        transactions.insert(0, new_tx);
+        app.update_data(graph, transactions, false);
+        assert_eq!(app.selected_transaction_index, 2);
+        assert_eq!(app.get_selected_transaction().unwrap().hash, "tx2");
+
+        // Incremental watch updates keep it too
+        let (graph, mut transactions) = create_test_data();
+        transactions.insert(
+            0,
+            create_test_transaction("tx00", 400, 4000, vec![], vec![]),
+        );
+        transactions.insert(1, create_test_transaction("tx0", 300, 3000, vec![], vec![]));
+        app.update_data(graph, transactions, true);
+        assert_eq!(app.get_selected_transaction().unwrap().hash, "tx2");
+        assert_eq!(app.transaction_list_state.selected(), Some(3));
+
+        // Falls back to the first transaction once the selected one is gone
+        let (graph, mut transactions) = create_test_data();
+        transactions.retain(|tx| tx.hash != "tx2");
+        app.update_data(graph, transactions, false);
+        assert_eq!(app.selected_transaction_index, 0);
+        assert_eq!(app.transaction_list_state.selected(), Some(0));
+    }
+
+    #[test]
+    fn test_app_search_filters_states_and_transactions() {
+        let (graph, transactions) = create_test_data();
+        let mut app = App::new(graph, transactions);
+        let all_states = app.states_list().len();
+
+        app.start_search();
+        assert!(app.search_active);
+        for c in "TX2".chars() {
+            app.push_search_char(c);
+        }
+        app.finish_search();
+
+        assert!(!app.search_active);
+        assert_eq!(app.search_query.as_deref(), Some("TX2"));
+        assert!(app.states_list().iter().all(|id| id.starts_with("tx2#")));
+        assert!(!app.states_list().is_empty());
+        assert_eq!(app.transactions().len(), 1);
+        assert_eq!(app.get_selected_transaction().unwrap().hash, "tx2");
+
+        // Editing the query refilters as it is typed
+        app.start_search();
+        app.pop_search_char();
+        assert_eq!(app.transactions().len(), 3);
+
+        app.clear_search();
+        assert_eq!(app.search_query, None);
+        assert_eq!(app.states_list().len(), all_states);
+        assert_eq!(app.transactions().len(), 3);
+        assert_eq!(app.get_selected_transaction().unwrap().hash, "tx2");
+    }
+
+    #[test]
+    fn test_app_search_without_matches() {
+        let (graph, transactions) = create_test_data();
+        let mut app = App::new(graph, transactions);
+
+        app.start_search();
+        app.push_search_char('z');
+        assert!(app.states_list().is_empty());
+        assert!(app.transactions().is_empty());
+        assert!(app.get_selected_state().is_none());
+        assert_eq!(app.transaction_list_state.selected(), None);
+
+        // An empty query is dropped when the search is confirmed
+        app.pop_search_char();
+        app.finish_search();
+        assert_eq!(app.search_query, None);
+    }
+
+    #[test]
+    fn test_app_datum_scroll_clamped() {
+        let (graph, transactions) = create_test_data();
+        let mut app = App::new(graph, transactions);
+
+        // 25 lines in a 10-line pane leaves 15 lines to scroll through
+        app.set_datum_content_height(25, 10);
+        app.scroll_datum_down(1);
+        assert_eq!(app.datum_scroll, 1);
+        app.scroll_datum_down(100);
+        assert_eq!(app.datum_scroll, 15);
+        app.scroll_datum_up(5);
+        assert_eq!(app.datum_scroll, 10);
+        app.scroll_datum_up(100);
+        assert_eq!(app.datum_scroll, 0);
+
+        // Shorter content pulls the offset back; content that fits does not scroll
+        app.scroll_datum_down(15);
+        app.set_datum_content_height(12, 10);
+        assert_eq!(app.datum_scroll, 2);
+        app.set_datum_content_height(5, 10);
+        app.scroll_datum_down(3);
+        assert_eq!(app.datum_scroll, 0);
+
+        // Selecting another transaction starts from the top
+        app.set_datum_content_height(25, 10);
+        app.scroll_datum_down(4);
+        app.select_next_transaction();
+        assert_eq!(app.datum_scroll, 0);
+    }
+
+    #[test]
+    fn test_app_detail_scroll_clamped_and_reset() {
+        let (graph, transactions) = create_test_data();
+        let mut app = App::new(graph, transactions);
+
+        app.set_detail_content_height(30, 10);
+        app.scroll_detail_down(15);
+        assert_eq!(app.detail_scroll, 15);
+        app.scroll_detail_down(15);
+        assert_eq!(app.detail_scroll, 20);
+        app.scroll_detail_up(5);
+        assert_eq!(app.detail_scroll, 15);
+
+        // Another state starts at the top
+        app.select_next();
+        assert_eq!(app.detail_scroll, 0);
+    }
+
+    #[test]
+    fn test_app_status_cleared_on_update() {
+        let (graph, transactions) = create_test_data();
+        let mut app = App::new(graph, transactions);
+
+        app.set_status("Refreshing...".to_string());
+        assert_eq!(app.status_message.as_deref(), Some("Refreshing..."));
+
+        let (graph, transactions) = create_test_data();
+        app.update_data(graph, transactions, false);
+        assert!(app.status_message.is_none());
+    }
+
+    #[test]
+    fn test_app_incremental_update_reports_new_transactions() {
+        let (graph, transactions) = create_test_data();
+        let mut app = App::new(graph, transactions);
+        app.select_next_transaction();
+
+        let (graph, mut transactions) = create_test_data();
+        transactions.push(create_test_transaction("tx4", 300, 3000, vec![], vec![]));
+        app.update_data(graph, transactions, true);
+        assert_eq!(app.status_message.as_deref(), Some("1 new transaction"));
+        assert_eq!(app.get_selected_transaction().unwrap().hash, "tx2");
+
+        // Nothing new: the status stays clear
+        let (graph, mut transactions) = create_test_data();
+        transactions.push(create_test_transaction("tx4", 300, 3000, vec![], vec![]));
+        app.update_data(graph, transactions, true);
+        assert!(app.status_message.is_none());
+    }
 }
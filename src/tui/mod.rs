@@ -2,6 +2,7 @@
 
 use crate::{Result, state_machine::StateGraph};
 use crossterm::{
+    cursor::Show,
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
@@ -11,22 +12,101 @@ use ratatui::{
     backend::{Backend, CrosstermBackend},
 };
 use std::io;
+use std::sync::Once;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, watch};
 
 pub mod app;
+pub mod theme;
 pub mod ui;
 
 use app::{App, ViewMode};
+use theme::Theme;
+
+/// File in the working directory that `y` writes the current selection to
+const SELECTION_DUMP_FILE: &str = "selection_dump.txt";
+
+/// Whether the terminal is in raw mode on the alternate screen and needs restoring
+static TERMINAL_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Restore the terminal set up by [`run`], once; later calls do nothing
+fn restore_terminal() -> io::Result<()> {
+    if !TERMINAL_ACTIVE.swap(false, Ordering::SeqCst) {
+        return Ok(());
+    }
+    disable_raw_mode()?;
+    execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        Show
+    )
+}
+
+/// Restore the terminal before the panic message is printed, so it stays readable
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let _ = restore_terminal();
+            default_hook(info);
+        }));
+    });
+}
+
+/// Runs the terminal restore when dropped, so early returns don't leave the terminal in
+/// raw mode
+struct TerminalGuard {
+    restore: fn() -> io::Result<()>,
+}
+
+impl TerminalGuard {
+    fn new(restore: fn() -> io::Result<()>) -> Self {
+        Self { restore }
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        if let Err(e) = (self.restore)() {
+            tracing::warn!("Failed to restore the terminal: {}", e);
+        }
+    }
+}
+
+/// New data for the TUI to display
+pub struct DataUpdate {
+    pub graph: StateGraph,
+    pub transactions: Vec<crate::data_source::Transaction>,
+    /// Whether `graph` extends the previous graph with new transactions rather than
+    /// being rebuilt from scratch
+    pub incremental: bool,
+}
+
+/// An updated graph and its transactions, or the reason the update failed
+pub type GraphUpdate = Result<DataUpdate>;
 
 /// Run the TUI application
+///
+/// Updates received on `update_receiver` replace the displayed data. Pressing `r`
+/// sends a request on `refresh_sender`, which should answer with an update.
+/// `shutdown` is set when the user quits, and the TUI quits when it is set elsewhere.
+/// Everything is drawn with the colors of `theme`.
 pub fn run(
     graph: StateGraph,
     transactions: Vec<crate::data_source::Transaction>,
-    update_receiver: Option<mpsc::Receiver<(StateGraph, Vec<crate::data_source::Transaction>)>>,
+    update_receiver: Option<mpsc::Receiver<GraphUpdate>>,
+    refresh_sender: Option<mpsc::Sender<()>>,
+    shutdown: Option<watch::Sender<bool>>,
+    theme: Theme,
 ) -> Result<()> {
     // Setup terminal
+    install_panic_hook();
     enable_raw_mode().map_err(|e| crate::Error::Tui(e.to_string()))?;
+    TERMINAL_ACTIVE.store(true, Ordering::SeqCst);
+    let _guard = TerminalGuard::new(restore_terminal);
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)
         .map_err(|e| crate::Error::Tui(e.to_string()))?;
@@ -34,20 +114,17 @@ pub fn run(
     let mut terminal = Terminal::new(backend).map_err(|e| crate::Error::Tui(e.to_string()))?;
 
     // Create app and run
-    let app = App::new(graph, transactions);
-    let res = run_app(&mut terminal, app, update_receiver);
+    let app = App::new(graph, transactions).with_theme(theme);
+    let res = run_app(
+        &mut terminal,
+        app,
+        update_receiver,
+        refresh_sender,
+        shutdown,
+    );
 
-    // Restore terminal
-    disable_raw_mode().map_err(|e| crate::Error::Tui(e.to_string()))?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )
-    .map_err(|e| crate::Error::Tui(e.to_string()))?;
-    terminal
-        .show_cursor()
-        .map_err(|e| crate::Error::Tui(e.to_string()))?;
+    // Restore terminal; the guard covers early returns, the panic hook covers panics
+    restore_terminal().map_err(|e| crate::Error::Tui(e.to_string()))?;
 
     res
 }
@@ -55,14 +132,25 @@ pub fn run(
 fn run_app<B: Backend>(
     terminal: &mut Terminal<B>,
     mut app: App,
-    mut update_receiver: Option<mpsc::Receiver<(StateGraph, Vec<crate::data_source::Transaction>)>>,
+    mut update_receiver: Option<mpsc::Receiver<GraphUpdate>>,
+    refresh_sender: Option<mpsc::Sender<()>>,
+    shutdown: Option<watch::Sender<bool>>,
 ) -> Result<()> {
     loop {
+        if shutdown.as_ref().is_some_and(|shutdown| *shutdown.borrow()) {
+            app.quit();
+        }
+
         // Check for updates
         if let Some(rx) = &mut update_receiver
-            && let Ok((new_graph, new_txs)) = rx.try_recv()
+            && let Ok(update) = rx.try_recv()
         {
-            app.update_data(new_graph, new_txs);
+            match update {
+                Ok(update) => {
+                    app.update_data(update.graph, update.transactions, update.incremental)
+                }
+                Err(e) => app.set_status(format!("Refresh failed: {}", e)),
+            }
         }
 
         terminal
@@ -72,6 +160,19 @@ fn run_app<B: Backend>(
         if event::poll(Duration::from_millis(100)).map_err(|e| crate::Error::Tui(e.to_string()))?
             && let Event::Key(key) = event::read().map_err(|e| crate::Error::Tui(e.to_string()))?
         {
+            // While typing a search query, keys edit the query instead of running commands
+            if app.search_active {
+                match key.code {
+                    KeyCode::Char(c) => app.push_search_char(c),
+                    KeyCode::Backspace => app.pop_search_char(),
+                    KeyCode::Enter => app.finish_search(),
+                    KeyCode::Esc => app.clear_search(),
+                    _ => {}
+                }
+                continue;
+            }
+
+            app.notice = None;
             match key.code {
                 KeyCode::Char('q') => {
                     app.quit();
@@ -88,6 +189,9 @@ fn run_app<B: Backend>(
                 KeyCode::Char('t') => {
                     app.set_view_mode(ViewMode::TransactionList);
                 }
+                KeyCode::Char('e') => {
+                    app.set_view_mode(ViewMode::TransactionDetail);
+                }
                 KeyCode::Char('i') => {
                     app.set_view_mode(ViewMode::DatumInspector);
                 }
@@ -98,12 +202,60 @@ fn run_app<B: Backend>(
                     // Toggle hex view in datum inspector
                     app.toggle_hex_view();
                 }
+                KeyCode::Char('z') => {
+                    // Focus on the selected state's lineage (or restore the full graph)
+                    app.toggle_focus();
+                }
+                KeyCode::Char('l') => {
+                    // Only list live states (terminal and still unspent)
+                    app.toggle_live_filter();
+                }
+                KeyCode::Char('L') => {
+                    // Compact state color legend over the graph overview
+                    app.toggle_legend();
+                }
+                KeyCode::Char('f') => {
+                    // Highlight the states touched by one transition label at a time
+                    app.cycle_transition_filter();
+                }
+                KeyCode::Char('P') if app.view_mode == ViewMode::StateDetail => {
+                    // List the route from the nearest initial state under the state info
+                    app.show_path_to_selected();
+                }
+                KeyCode::Char('s') if app.view_mode == ViewMode::TransactionList => {
+                    // Jump to the states the selected transaction created/consumed
+                    app.show_states_for_selected_transaction();
+                }
+                KeyCode::Char('y') => {
+                    // Clipboard access is platform dependent, so write to a file instead
+                    let dump = app.dump_selection();
+                    app.notice = Some(if dump.is_empty() {
+                        "Nothing selected".to_string()
+                    } else {
+                        match std::fs::write(SELECTION_DUMP_FILE, dump) {
+                            Ok(()) => format!("Selection written to {}", SELECTION_DUMP_FILE),
+                            Err(e) => format!("Failed to write {}: {}", SELECTION_DUMP_FILE, e),
+                        }
+                    });
+                }
+                KeyCode::Char('/') => {
+                    // Filter states and transactions by id/hash
+                    app.start_search();
+                }
+                KeyCode::Char('r') => {
+                    // Refetch and rebuild; a full request channel means one is already pending
+                    if let Some(tx) = &refresh_sender {
+                        let _ = tx.try_send(());
+                        app.set_status("Refreshing...".to_string());
+                    }
+                }
                 KeyCode::Tab => {
                     // Cycle through views
                     let next_mode = match app.view_mode {
                         ViewMode::GraphOverview => ViewMode::StateDetail,
                         ViewMode::StateDetail => ViewMode::TransactionList,
-                        ViewMode::TransactionList => ViewMode::DatumInspector,
+                        ViewMode::TransactionList => ViewMode::TransactionDetail,
+                        ViewMode::TransactionDetail => ViewMode::DatumInspector,
                         ViewMode::DatumInspector => ViewMode::PatternAnalysis,
                         ViewMode::PatternAnalysis => ViewMode::Help,
                         ViewMode::Help => ViewMode::GraphOverview,
@@ -116,7 +268,11 @@ fn run_app<B: Backend>(
                         ViewMode::GraphOverview | ViewMode::PatternAnalysis => {
                             app.select_previous()
                         }
-                        ViewMode::TransactionList => app.select_previous_transaction(),
+                        ViewMode::TransactionList | ViewMode::TransactionDetail => {
+                            app.select_previous_transaction()
+                        }
+                        ViewMode::StateDetail => app.scroll_detail_up(1),
+                        ViewMode::DatumInspector => app.scroll_datum_up(1),
                         _ => {} // Do nothing for other views
                     }
                 }
@@ -124,24 +280,95 @@ fn run_app<B: Backend>(
                     // Context-aware navigation
                     match app.view_mode {
                         ViewMode::GraphOverview | ViewMode::PatternAnalysis => app.select_next(),
-                        ViewMode::TransactionList => app.select_next_transaction(),
+                        ViewMode::TransactionList | ViewMode::TransactionDetail => {
+                            app.select_next_transaction()
+                        }
+                        ViewMode::StateDetail => app.scroll_detail_down(1),
+                        ViewMode::DatumInspector => app.scroll_datum_down(1),
                         _ => {} // Do nothing for other views
                     }
                 }
+                KeyCode::PageUp => match app.view_mode {
+                    ViewMode::StateDetail => app.scroll_detail_up(10),
+                    ViewMode::DatumInspector => app.scroll_datum_up(10),
+                    ViewMode::GraphOverview
+                    | ViewMode::PatternAnalysis
+                    | ViewMode::TransactionList => app.page_up(),
+                    _ => {}
+                },
+                KeyCode::PageDown => match app.view_mode {
+                    ViewMode::StateDetail => app.scroll_detail_down(10),
+                    ViewMode::DatumInspector => app.scroll_datum_down(10),
+                    ViewMode::GraphOverview
+                    | ViewMode::PatternAnalysis
+                    | ViewMode::TransactionList => app.page_down(),
+                    _ => {}
+                },
+                KeyCode::Home => match app.view_mode {
+                    ViewMode::GraphOverview
+                    | ViewMode::PatternAnalysis
+                    | ViewMode::TransactionList => app.select_first(),
+                    _ => {}
+                },
+                KeyCode::End => match app.view_mode {
+                    ViewMode::GraphOverview
+                    | ViewMode::PatternAnalysis
+                    | ViewMode::TransactionList => app.select_last(),
+                    _ => {}
+                },
 
                 KeyCode::Enter => match app.view_mode {
-                    ViewMode::TransactionList => app.set_view_mode(ViewMode::DatumInspector),
+                    ViewMode::TransactionList => app.set_view_mode(ViewMode::TransactionDetail),
+                    ViewMode::TransactionDetail => app.set_view_mode(ViewMode::DatumInspector),
                     _ => app.set_view_mode(ViewMode::StateDetail),
                 },
                 KeyCode::Esc => {
-                    app.pop_view_mode();
+                    // Clear an active filter before leaving the view
+                    if app.search_query.is_some() {
+                        app.clear_search();
+                    } else {
+                        app.pop_view_mode();
+                    }
                 }
                 _ => {}
             }
         }
 
         if app.should_quit {
+            if let Some(shutdown) = &shutdown {
+                shutdown.send_replace(true);
+            }
             return Ok(());
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    static RESTORES: AtomicUsize = AtomicUsize::new(0);
+
+    fn count_restore() -> io::Result<()> {
+        RESTORES.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    #[test]
+    fn test_terminal_guard_restores_on_drop() {
+        drop(TerminalGuard::new(count_restore));
+        assert_eq!(RESTORES.load(Ordering::SeqCst), 1);
+
+        // Also while unwinding from a panic in the UI loop
+        let result = std::panic::catch_unwind(|| {
+            let _guard = TerminalGuard::new(count_restore);
+            panic!("render failed");
+        });
+        assert!(result.is_err());
+        assert_eq!(RESTORES.load(Ordering::SeqCst), 2);
+
+        // Without a terminal set up, restoring touches nothing
+        assert!(restore_terminal().is_ok());
+    }
+}
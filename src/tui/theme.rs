@@ -0,0 +1,103 @@
+//! TUI color theme
+
+use crate::state_machine::StateClass;
+use ratatui::style::Color;
+
+/// Colors used to draw the TUI
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub initial: Color,
+    pub active: Color,
+    pub locked: Color,
+    pub completed: Color,
+    pub failed: Color,
+    pub unknown: Color,
+    /// View headers
+    pub accent: Color,
+    /// Text of the selected list row
+    pub highlight: Color,
+    /// Background of the selected list row
+    pub selection: Color,
+    /// States outside the current transition filter
+    pub dimmed: Color,
+    /// Regular text and footers
+    pub text: Color,
+    /// Inputs and outputs at the script address in the transaction detail
+    pub script: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            initial: Color::LightBlue,
+            active: Color::Yellow,
+            locked: Color::Magenta,
+            completed: Color::Green,
+            failed: Color::Red,
+            unknown: Color::Gray,
+            accent: Color::Cyan,
+            highlight: Color::Yellow,
+            selection: Color::DarkGray,
+            dimmed: Color::DarkGray,
+            text: Color::White,
+            script: Color::Green,
+        }
+    }
+}
+
+impl Theme {
+    /// Only white and grays, for `--no-color`; selection and emphasis still rely
+    /// on bold text and the selection background
+    pub fn monochrome() -> Self {
+        Self {
+            initial: Color::White,
+            active: Color::White,
+            locked: Color::White,
+            completed: Color::White,
+            failed: Color::White,
+            unknown: Color::Gray,
+            accent: Color::White,
+            highlight: Color::White,
+            selection: Color::DarkGray,
+            dimmed: Color::DarkGray,
+            text: Color::White,
+            script: Color::White,
+        }
+    }
+
+    /// Color of a state in lists, by classification
+    pub fn class_color(&self, class: &StateClass) -> Color {
+        match class {
+            StateClass::Initial => self.initial,
+            StateClass::Active => self.active,
+            StateClass::Completed => self.completed,
+            StateClass::Failed => self.failed,
+            StateClass::Locked => self.locked,
+            StateClass::Unknown => self.unknown,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monochrome_theme_has_no_class_colors() {
+        let theme = Theme::monochrome();
+        for class in [
+            StateClass::Initial,
+            StateClass::Active,
+            StateClass::Locked,
+            StateClass::Completed,
+            StateClass::Failed,
+            StateClass::Unknown,
+        ] {
+            assert!(matches!(
+                theme.class_color(&class),
+                Color::White | Color::Gray
+            ));
+        }
+        assert_ne!(theme, Theme::default());
+    }
+}
@@ -1,17 +1,20 @@
 //! TUI UI rendering
 
 use super::app::{App, ViewMode};
-use crate::state_machine::{StateClass, analyzer::ContractPattern};
+use super::theme::Theme;
+use crate::data_source::Asset;
+use crate::state_machine::{StateClass, Transition, analyzer::ContractPattern};
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout},
-    style::{Color, Modifier, Style},
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{
-        Block, Borders, List, ListItem, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState,
-        Wrap,
+        Block, Borders, Clear, List, ListItem, Paragraph, Scrollbar, ScrollbarOrientation,
+        ScrollbarState, Wrap,
     },
 };
+use std::collections::HashMap;
 
 /// Draw the UI based on current app state
 pub fn draw(f: &mut Frame, app: &mut App) {
@@ -19,9 +22,10 @@ pub fn draw(f: &mut Frame, app: &mut App) {
         ViewMode::GraphOverview => draw_graph_overview(f, app),
         ViewMode::StateDetail => draw_state_detail(f, app),
         ViewMode::TransactionList => draw_transaction_list(f, app),
+        ViewMode::TransactionDetail => draw_transaction_detail(f, app),
         ViewMode::DatumInspector => draw_datum_inspector(f, app),
         ViewMode::PatternAnalysis => draw_pattern_analysis(f, app),
-        ViewMode::Help => draw_help(f),
+        ViewMode::Help => draw_help(f, app),
     }
 }
 
@@ -36,18 +40,35 @@ fn draw_graph_overview(f: &mut Frame, app: &mut App) {
         .split(f.area());
 
     // Header
-    let header = Paragraph::new("Cardano State Machine Visualizer - Graph Overview")
+    let mut header_text = match &app.focused_state {
+        Some(id) => format!(
+            "Cardano State Machine Visualizer - Graph Overview (focused on {})",
+            id
+        ),
+        None => "Cardano State Machine Visualizer - Graph Overview".to_string(),
+    };
+    if app.live_only {
+        header_text.push_str(" [live only]");
+    }
+    if let Some(label) = &app.transition_filter {
+        header_text.push_str(&format!(" [action: {}]", label));
+    }
+    if let Some(status) = &app.status_message {
+        header_text.push_str(&format!(" - {}", status));
+    }
+    let header = Paragraph::new(header_text)
         .style(
             Style::default()
-                .fg(Color::Cyan)
+                .fg(app.theme.accent)
                 .add_modifier(Modifier::BOLD),
         )
         .block(Block::default().borders(Borders::ALL));
     f.render_widget(header, chunks[0]);
 
     // Calculate items and stats in a separate block to release immutable borrow of app
-    let (items, stats) = {
+    let (items, count, stats) = {
         let states_list = app.states_list();
+        let highlighted = app.transition_filter_states();
         let items: Vec<ListItem> = states_list
             .iter()
             .enumerate()
@@ -55,30 +76,39 @@ fn draw_graph_overview(f: &mut Frame, app: &mut App) {
                 let state = app.state_graph.get_state(state_id).unwrap();
                 let is_selected = idx == app.selected_state_index;
 
-                let color = match state.metadata.classification {
-                    StateClass::Initial => Color::LightBlue,
-                    StateClass::Active => Color::Yellow,
-                    StateClass::Completed => Color::Green,
-                    StateClass::Failed => Color::Red,
-                    StateClass::Locked => Color::Magenta,
-                    StateClass::Unknown => Color::Gray,
+                // Dim states untouched by the selected action
+                let dimmed = highlighted
+                    .as_ref()
+                    .is_some_and(|states| !states.contains(&state.id));
+                let color = if dimmed {
+                    app.theme.dimmed
+                } else {
+                    app.theme.class_color(&state.metadata.classification)
                 };
 
                 let prefix = if is_selected { "► " } else { "  " };
+                let badge = if app.state_graph.is_live(&state.id)
+                    && app.state_graph.degree(&state.id).1 == 0
+                {
+                    " [LIVE]"
+                } else {
+                    ""
+                };
                 let text = format!(
-                    "{}{} | Block: {} | Slot: {} | {} ADA",
+                    "{}{} | Block: {} | Slot: {} | {} ADA{}",
                     prefix,
                     state.id,
                     state.block,
                     state.slot,
-                    state.ada_value() as f64 / 1_000_000.0
+                    state.ada_value() as f64 / 1_000_000.0,
+                    badge
                 );
 
                 let style = if is_selected {
                     Style::default()
                         .fg(color)
                         .add_modifier(Modifier::BOLD)
-                        .bg(Color::DarkGray)
+                        .bg(app.theme.selection)
                 } else {
                     Style::default().fg(color)
                 };
@@ -87,7 +117,7 @@ fn draw_graph_overview(f: &mut Frame, app: &mut App) {
             })
             .collect();
 
-        (items, app.state_graph.stats())
+        (items, states_list.len(), app.state_graph.stats())
     };
 
     let list = List::new(items)
@@ -96,13 +126,12 @@ fn draw_graph_overview(f: &mut Frame, app: &mut App) {
     f.render_stateful_widget(list, chunks[1], &mut app.state_list_state);
 
     // Scrollbar
-    if stats.total_states > 0 {
+    if count > 0 {
         let scrollbar = Scrollbar::default()
             .orientation(ScrollbarOrientation::VerticalRight)
             .begin_symbol(Some("↑"))
             .end_symbol(Some("↓"));
-        let mut scrollbar_state =
-            ScrollbarState::new(stats.total_states).position(app.selected_state_index);
+        let mut scrollbar_state = ScrollbarState::new(count).position(app.selected_state_index);
         f.render_stateful_widget(
             scrollbar,
             chunks[1].inner(ratatui::layout::Margin {
@@ -114,27 +143,81 @@ fn draw_graph_overview(f: &mut Frame, app: &mut App) {
     }
 
     // Footer with stats and instructions
-    let current_idx = if stats.total_states > 0 {
+    let current_idx = if count > 0 {
         app.selected_state_index + 1
     } else {
         0
     };
-    let footer_text = format!(
-        "[{}/{}] States | Transitions: {} | Initial: {} | Terminal: {} | [↑/↓] Navigate | [Enter/d] Detail | [h/?] Help | [q] Quit",
-        current_idx,
-        stats.total_states,
-        stats.total_transitions,
-        stats.initial_states,
-        stats.terminal_states
-    );
-    let footer = Paragraph::new(footer_text)
-        .style(Style::default().fg(Color::White))
-        .block(Block::default().borders(Borders::ALL));
+    let footer_line = match search_footer(app) {
+        Some(text) => Line::from(text),
+        None => {
+            let mut spans = vec![Span::raw(format!(
+                "[{}/{}] States | Transitions: {} | ",
+                current_idx, count, stats.total_transitions
+            ))];
+            for (class, count) in app.class_counts() {
+                spans.push(Span::styled(
+                    format!("{:?}: {}", class, count),
+                    Style::default().fg(app.theme.class_color(&class)),
+                ));
+                spans.push(Span::raw(" | "));
+            }
+            spans.push(Span::raw(format!(
+                "Terminal: {} | Live: {} ({} ADA) | [↑/↓] Navigate | [Enter/d] Detail | [z] Focus | [l] Live | [f] Action | [L] Legend | [/] Search | [r] Refresh | [h/?] Help | [q] Quit",
+                stats.terminal_states,
+                stats.live_states,
+                stats.live_lovelace as f64 / 1_000_000.0
+            )));
+            Line::from(spans)
+        }
+    };
+    let footer = Paragraph::new(footer_line)
+        .style(Style::default().fg(app.theme.text))
+        .block(footer_block(app));
     f.render_widget(footer, chunks[2]);
+
+    if app.show_legend {
+        draw_legend(f, chunks[1], &app.theme);
+    }
+}
+
+/// Draw a compact state color legend in the top-right corner of `area`
+fn draw_legend(f: &mut Frame, area: Rect, theme: &Theme) {
+    let classes = [
+        StateClass::Initial,
+        StateClass::Active,
+        StateClass::Locked,
+        StateClass::Completed,
+        StateClass::Failed,
+        StateClass::Unknown,
+    ];
+    let lines: Vec<Line> = classes
+        .iter()
+        .map(|class| {
+            Line::from(vec![
+                Span::styled("■ ", Style::default().fg(theme.class_color(class))),
+                Span::raw(format!("{:?}", class)),
+            ])
+        })
+        .collect();
+
+    let width = 15.min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let legend_area = Rect {
+        x: area.x + area.width.saturating_sub(width + 2),
+        y: area.y + 1,
+        width,
+        height,
+    };
+    f.render_widget(Clear, legend_area);
+    f.render_widget(
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Legend")),
+        legend_area,
+    );
 }
 
 /// Draw detailed view of selected state
-fn draw_state_detail(f: &mut Frame, app: &App) {
+fn draw_state_detail(f: &mut Frame, app: &mut App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -148,14 +231,21 @@ fn draw_state_detail(f: &mut Frame, app: &App) {
     let header = Paragraph::new("State Detail View")
         .style(
             Style::default()
-                .fg(Color::Cyan)
+                .fg(app.theme.accent)
                 .add_modifier(Modifier::BOLD),
         )
         .block(Block::default().borders(Borders::ALL));
     f.render_widget(header, chunks[0]);
 
     // Details
-    if let Some(state) = app.get_selected_state() {
+    let selected = app.get_selected_state().map(|state| {
+        let mut info = format_state_info(state);
+        if let Some(path) = app.path_to_selected() {
+            info.push_str(&format_path(path));
+        }
+        (state.id.clone(), info)
+    });
+    if let Some((state_id, state_info)) = selected {
         let detail_chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
@@ -165,25 +255,36 @@ fn draw_state_detail(f: &mut Frame, app: &App) {
             ])
             .split(chunks[1]);
 
-        // State info
-        let state_info = format_state_info(state);
+        // State info, scrollable since states may hold many native assets (inside the borders)
+        let width = detail_chunks[0].width.saturating_sub(2);
+        let height = detail_chunks[0].height.saturating_sub(2);
+        app.set_detail_content_height(wrapped_line_count(&state_info, width), height);
         let state_widget = Paragraph::new(state_info)
             .block(
                 Block::default()
                     .borders(Borders::ALL)
                     .title("State Information"),
             )
-            .wrap(Wrap { trim: false });
+            .wrap(Wrap { trim: false })
+            .scroll((app.detail_scroll, 0));
         f.render_widget(state_widget, detail_chunks[0]);
 
         // Incoming transitions
-        let incoming = app.state_graph.incoming_transitions(&state.id);
+        let incoming = app.state_graph.incoming_transitions(&state_id);
         let incoming_text = if incoming.is_empty() {
             "No incoming transitions (Initial state)".to_string()
         } else {
             incoming
                 .iter()
-                .map(|t| format!("← {} (tx: {})", t.from_state, &t.tx_hash[..8]))
+                .map(|t| {
+                    format!(
+                        "← {} {} (tx: {}){}",
+                        t.from_state,
+                        t.display_label_with_value(),
+                        &t.tx_hash[..8],
+                        format_ex_units(t)
+                    )
+                })
                 .collect::<Vec<_>>()
                 .join("\n")
         };
@@ -197,13 +298,21 @@ fn draw_state_detail(f: &mut Frame, app: &App) {
         f.render_widget(incoming_widget, detail_chunks[1]);
 
         // Outgoing transitions
-        let outgoing = app.state_graph.outgoing_transitions(&state.id);
+        let outgoing = app.state_graph.outgoing_transitions(&state_id);
         let outgoing_text = if outgoing.is_empty() {
             "No outgoing transitions (Terminal state)".to_string()
         } else {
             outgoing
                 .iter()
-                .map(|t| format!("→ {} (tx: {})", t.to_state, &t.tx_hash[..8]))
+                .map(|t| {
+                    format!(
+                        "→ {} {} (tx: {}){}",
+                        t.to_state,
+                        t.display_label_with_value(),
+                        &t.tx_hash[..8],
+                        format_ex_units(t)
+                    )
+                })
                 .collect::<Vec<_>>()
                 .join("\n")
         };
@@ -222,13 +331,31 @@ fn draw_state_detail(f: &mut Frame, app: &App) {
     }
 
     // Footer
-    let footer =
-        Paragraph::new("[↑/↓] Navigate | [g/Esc] Back to Overview | [h/?] Help | [q] Quit")
-            .style(Style::default().fg(Color::White))
-            .block(Block::default().borders(Borders::ALL));
+    let footer = Paragraph::new(
+        "[↑/↓/PgUp/PgDn] Scroll | [P] Path | [g/Esc] Back to Overview | [h/?] Help | [q] Quit",
+    )
+    .style(Style::default().fg(app.theme.text))
+    .block(footer_block(app));
     f.render_widget(footer, chunks[2]);
 }
 
+/// Format a traced path as one line per transition, appended to the state information
+fn format_path(path: &[Transition]) -> String {
+    let mut text = String::from("\nPath from initial state:\n");
+    if let Some(first) = path.first() {
+        text.push_str(&format!("  {}\n", first.from_state));
+    }
+    for t in path {
+        text.push_str(&format!(
+            "  → {} {} (tx: {})\n",
+            t.to_state,
+            t.display_label_with_value(),
+            t.tx_hash.get(..8).unwrap_or(&t.tx_hash)
+        ));
+    }
+    text
+}
+
 /// Draw transaction list view
 fn draw_transaction_list(f: &mut Frame, app: &mut App) {
     let chunks = Layout::default()
@@ -244,7 +371,7 @@ fn draw_transaction_list(f: &mut Frame, app: &mut App) {
     let header = Paragraph::new("Transaction List")
         .style(
             Style::default()
-                .fg(Color::Cyan)
+                .fg(app.theme.accent)
                 .add_modifier(Modifier::BOLD),
         )
         .block(Block::default().borders(Borders::ALL));
@@ -260,20 +387,11 @@ fn draw_transaction_list(f: &mut Frame, app: &mut App) {
                 let is_selected = idx == app.selected_transaction_index;
 
                 // Count inputs/outputs at script address
-                let script_inputs = tx
-                    .inputs
-                    .iter()
-                    .filter(|i| {
-                        app.state_graph
-                            .state_index
-                            .contains_key(&i.utxo_ref.to_string())
-                    })
-                    .count();
-
+                let script_inputs = tx.inputs.iter().filter(|i| app.is_script_input(i)).count();
                 let script_outputs = tx
                     .outputs
                     .iter()
-                    .filter(|o| o.address == app.state_graph.script_address)
+                    .filter(|o| app.is_script_output(o))
                     .count();
 
                 let hash_display = if tx.hash.len() > 16 {
@@ -290,11 +408,11 @@ fn draw_transaction_list(f: &mut Frame, app: &mut App) {
 
                 let style = if is_selected {
                     Style::default()
-                        .fg(Color::Yellow)
+                        .fg(app.theme.highlight)
                         .add_modifier(Modifier::BOLD)
-                        .bg(Color::DarkGray)
+                        .bg(app.theme.selection)
                 } else {
-                    Style::default().fg(Color::White)
+                    Style::default().fg(app.theme.text)
                 };
 
                 ListItem::new(text).style(style)
@@ -332,18 +450,133 @@ fn draw_transaction_list(f: &mut Frame, app: &mut App) {
     } else {
         0
     };
-    let footer_text = format!(
-        "[{}/{}] Transactions | [↑/↓] Navigate | [Enter/i] Inspect Datum | [g] Graph | [d] Details | [h/?] Help | [q] Quit",
+    let footer_text = search_footer(app).unwrap_or_else(|| format!(
+        "[{}/{}] Transactions | [↑/↓] Navigate | [Enter/e] Inputs/Outputs | [i] Inspect Datum | [s] States | [/] Search | [g] Graph | [d] Details | [h/?] Help | [q] Quit",
         current_idx, tx_count
-    );
+    ));
     let footer = Paragraph::new(footer_text)
-        .style(Style::default().fg(Color::White))
+        .style(Style::default().fg(app.theme.text))
+        .block(footer_block(app));
+    f.render_widget(footer, chunks[2]);
+}
+
+/// Draw transaction detail view: every input and output of the selected transaction
+fn draw_transaction_detail(f: &mut Frame, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3), // Header
+            Constraint::Min(0),    // Inputs and outputs
+            Constraint::Length(3), // Footer
+        ])
+        .split(f.area());
+
+    // Header
+    let header = Paragraph::new("Transaction Detail")
+        .style(
+            Style::default()
+                .fg(app.theme.accent)
+                .add_modifier(Modifier::BOLD),
+        )
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(header, chunks[0]);
+
+    let Some(tx) = app.get_selected_transaction() else {
+        let no_selection = Paragraph::new(
+            "No transaction selected.\nNavigate to a transaction in the Transaction List view first.",
+        )
         .block(Block::default().borders(Borders::ALL));
+        f.render_widget(no_selection, chunks[1]);
+        return;
+    };
+
+    let script_style = Style::default()
+        .fg(app.theme.script)
+        .add_modifier(Modifier::BOLD);
+    let other_style = Style::default().fg(app.theme.text);
+    let marker = |at_script: bool| if at_script { "★ " } else { "  " };
+
+    let mut lines = vec![
+        Line::from(format!("Transaction: {}", tx.hash)),
+        Line::from(format!("Block: {} | Slot: {}", tx.block, tx.slot)),
+        Line::from(match &tx.metadata {
+            Some(metadata) => format!("Metadata: labels {}", metadata.labels().join(", ")),
+            None => "Metadata: none".to_string(),
+        }),
+        Line::from(""),
+        Line::from(Span::styled(
+            format!("Inputs ({})", tx.inputs.len()),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+    ];
+    for input in &tx.inputs {
+        let at_script = app.is_script_input(input);
+        let amount = input
+            .amount
+            .as_deref()
+            .map(format_assets)
+            .unwrap_or_else(|| "unknown amount".to_string());
+        lines.push(Line::from(Span::styled(
+            format!(
+                "{}{} | {} | {}",
+                marker(at_script),
+                input.utxo_ref,
+                input.address.as_deref().unwrap_or("unknown address"),
+                amount
+            ),
+            if at_script { script_style } else { other_style },
+        )));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        format!("Outputs ({})", tx.outputs.len()),
+        Style::default().add_modifier(Modifier::BOLD),
+    )));
+    for (idx, output) in tx.outputs.iter().enumerate() {
+        let at_script = app.is_script_output(output);
+        let datum = match &output.datum {
+            Some(datum) => format!("datum {}", datum.hash),
+            None => "no datum".to_string(),
+        };
+        lines.push(Line::from(Span::styled(
+            format!(
+                "{}#{} | {} | {} | {}",
+                marker(at_script),
+                idx,
+                output.address,
+                format_assets(&output.amount),
+                datum
+            ),
+            if at_script { script_style } else { other_style },
+        )));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled("★", script_style),
+        Span::raw(" at the tracked script address"),
+    ]));
+
+    let content_widget = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Inputs & Outputs"),
+        )
+        .wrap(Wrap { trim: false });
+    f.render_widget(content_widget, chunks[1]);
+
+    // Footer
+    let footer_text = "[↑/↓] Previous/Next Tx | [Enter/i] Inspect Datum | [t/Esc] Transaction List | [h/?] Help | [q] Quit";
+    let footer = Paragraph::new(footer_text)
+        .style(Style::default().fg(app.theme.text))
+        .block(footer_block(app));
     f.render_widget(footer, chunks[2]);
 }
 
 /// Draw datum inspector view
-fn draw_datum_inspector(f: &mut Frame, app: &App) {
+fn draw_datum_inspector(f: &mut Frame, app: &mut App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -362,7 +595,7 @@ fn draw_datum_inspector(f: &mut Frame, app: &App) {
     let header = Paragraph::new(format!("Datum Inspector - {}", view_type))
         .style(
             Style::default()
-                .fg(Color::Cyan)
+                .fg(app.theme.accent)
                 .add_modifier(Modifier::BOLD),
         )
         .block(Block::default().borders(Borders::ALL));
@@ -372,7 +605,11 @@ fn draw_datum_inspector(f: &mut Frame, app: &App) {
     let content = if let Some(tx) = app.get_selected_transaction() {
         let mut datum_text = String::new();
         datum_text.push_str(&format!("Transaction: {}\n", tx.hash));
-        datum_text.push_str(&format!("Block: {} | Slot: {}\n\n", tx.block, tx.slot));
+        datum_text.push_str(&format!("Block: {} | Slot: {}", tx.block, tx.slot));
+        if let Some(size) = tx.size {
+            datum_text.push_str(&format!(" | Size: {} bytes", size));
+        }
+        datum_text.push_str("\n\n");
 
         // Extract datums from outputs
         let datum_extractor = crate::parser::datum::DatumExtractor::new();
@@ -387,21 +624,22 @@ fn draw_datum_inspector(f: &mut Frame, app: &App) {
                         if app.show_hex_view {
                             // Hex view
                             datum_text.push_str(&format!("Hash: {}\n", datum.hash));
-                            datum_text.push_str("CBOR (hex): ");
-                            for byte in &datum.raw_cbor {
-                                datum_text.push_str(&format!("{:02x}", byte));
-                            }
-                            datum_text.push('\n');
+                            datum_text.push_str("CBOR:\n");
+                            datum_text.push_str(&hexdump(&datum.raw_cbor));
                         } else {
                             // Decoded view
                             if let Some(ref parsed) = datum.parsed {
                                 if !parsed.fields.is_empty() {
-                                    datum_text.push_str("Schema Fields:\n");
-                                    for (key, val) in &parsed.fields {
+                                    datum_text.push_str("Fields:\n");
+                                    for (key, val) in sorted_fields(&parsed.fields) {
                                         datum_text.push_str(&format!("  {}: {}\n", key, val));
                                     }
                                     datum_text.push('\n');
                                 }
+                                if let Some(ref mismatch) = parsed.schema_mismatch {
+                                    datum_text
+                                        .push_str(&format!("Schema mismatch: {}\n", mismatch));
+                                }
                                 datum_text.push_str(&format!(
                                     "Raw: {}\n",
                                     parsed.raw.to_human_readable()
@@ -426,27 +664,80 @@ fn draw_datum_inspector(f: &mut Frame, app: &App) {
             .to_string()
     };
 
+    // Inside the borders
+    let width = chunks[1].width.saturating_sub(2);
+    let height = chunks[1].height.saturating_sub(2);
+    app.set_datum_content_height(wrapped_line_count(&content, width), height);
+
     let content_widget = Paragraph::new(content)
         .block(Block::default().borders(Borders::ALL).title("Datum Data"))
-        .wrap(Wrap { trim: false });
+        .wrap(Wrap { trim: false })
+        .scroll((app.datum_scroll, 0));
     f.render_widget(content_widget, chunks[1]);
 
     // Footer
-    let footer_text =
-        "[x] Toggle Hex/Decoded | [t] Transaction List | [g] Graph | [h/?] Help | [q] Quit";
+    let footer_text = "[↑/↓/PgUp/PgDn] Scroll | [x] Toggle Hex/Decoded | [t] Transaction List | [g] Graph | [h/?] Help | [q] Quit";
     let footer = Paragraph::new(footer_text)
-        .style(Style::default().fg(Color::White))
-        .block(Block::default().borders(Borders::ALL));
+        .style(Style::default().fg(app.theme.text))
+        .block(footer_block(app));
     f.render_widget(footer, chunks[2]);
 }
 
+/// Format bytes like `hexdump -C`: 16-byte rows of offset, hex columns split in two
+/// groups of 8, and printable ASCII (anything else as `.`)
+fn hexdump(bytes: &[u8]) -> String {
+    let mut dump = String::new();
+    for (row, chunk) in bytes.chunks(16).enumerate() {
+        let mut hex = String::new();
+        for i in 0..16 {
+            if i == 8 {
+                hex.push(' ');
+            }
+            match chunk.get(i) {
+                Some(byte) => hex.push_str(&format!("{:02x} ", byte)),
+                None => hex.push_str("   "),
+            }
+        }
+        let ascii: String = chunk
+            .iter()
+            .map(|&byte| {
+                if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        dump.push_str(&format!("{:08x}  {} |{}|\n", row * 16, hex, ascii));
+    }
+    dump
+}
+
+/// Number of lines `text` takes when wrapped to `width` columns
+///
+/// Long unbroken runs such as CBOR hex wrap at the pane edge; word wrapping can
+/// take a few more lines, so this may slightly undercount prose.
+fn wrapped_line_count(text: &str, width: u16) -> u16 {
+    let width = usize::from(width.max(1));
+    let lines: usize = text
+        .lines()
+        .map(|line| line.chars().count().div_ceil(width).max(1))
+        .sum();
+    u16::try_from(lines).unwrap_or(u16::MAX)
+}
+
 /// Draw pattern analysis view
 fn draw_pattern_analysis(f: &mut Frame, app: &mut App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(3), // Header
-            Constraint::Length(5), // Metrics
+            // Metrics, with a line for the first cycle if there is one
+            Constraint::Length(if app.analysis_report.cycles.is_empty() {
+                5
+            } else {
+                6
+            }),
             Constraint::Min(0),    // Visualization
             Constraint::Length(3), // Footer
         ])
@@ -456,7 +747,7 @@ fn draw_pattern_analysis(f: &mut Frame, app: &mut App) {
     let header = Paragraph::new("Pattern Analysis")
         .style(
             Style::default()
-                .fg(Color::Cyan)
+                .fg(app.theme.accent)
                 .add_modifier(Modifier::BOLD),
         )
         .block(Block::default().borders(Borders::ALL));
@@ -464,13 +755,38 @@ fn draw_pattern_analysis(f: &mut Frame, app: &mut App) {
 
     // Metrics
     let report = &app.analysis_report;
-    let metrics_text = format!(
-        "Detected Pattern: {}\nBranching Factor: {:.2} | Max Depth: {} | Has Cycles: {}",
+    let stats = app.state_graph.stats();
+    let most_reused = stats
+        .most_reused_datums
+        .first()
+        .map(|(hash, count)| {
+            let hash_short = if hash.len() > 12 { &hash[..12] } else { hash };
+            format!(" | Most Reused: {}... ({}x)", hash_short, count)
+        })
+        .unwrap_or_default();
+    let mut metrics_text = format!(
+        "Detected Pattern: {}\nBranching Factor: {:.2} | Max Depth: {} | Has Cycles: {}\nDatum Hashes: {} unique across {} states{} | {}",
         report.pattern.display_name(),
         report.branching_factor,
         report.max_depth,
-        report.has_cycles
+        report.has_cycles,
+        stats.unique_datum_hashes,
+        stats.states_with_datum,
+        most_reused,
+        stats.datum_reuse_hint()
     );
+    if let Some(cycle) = report.cycles.first() {
+        let more = match report.cycles.len() - 1 {
+            0 => String::new(),
+            n => format!(" (+{} more)", n),
+        };
+        metrics_text.push_str(&format!(
+            "\nCycle: {} → {}{}",
+            cycle.join(" → "),
+            cycle[0],
+            more
+        ));
+    }
     let metrics = Paragraph::new(metrics_text)
         .block(Block::default().borders(Borders::ALL).title("Metrics"))
         .wrap(Wrap { trim: false });
@@ -479,6 +795,7 @@ fn draw_pattern_analysis(f: &mut Frame, app: &mut App) {
     // Visualization
     let viz_title = match report.pattern {
         ContractPattern::Linear => "Timeline View (Linear)",
+        ContractPattern::Vesting => "Timeline View (Vesting)",
         ContractPattern::Tree => "Branching View (Tree)",
         ContractPattern::Cyclic => "Cycle View",
         ContractPattern::Unknown => "Graph View",
@@ -503,19 +820,34 @@ fn draw_pattern_analysis(f: &mut Frame, app: &mut App) {
                             state.display_short()
                         )
                     }
+                    ContractPattern::Vesting => {
+                        format!(
+                            "{} | Block {} | {} ADA remaining",
+                            state.id,
+                            state.block,
+                            state.ada_value() as f64 / 1_000_000.0
+                        )
+                    }
                     ContractPattern::Tree => {
-                        // TODO: Calculate depth for indentation
-                        format!("{} | {}", state.id, state.display_short())
+                        let depth = app.state_depths.get(state_id).copied().unwrap_or(0);
+                        let branch = if depth > 0 { "└─ " } else { "" };
+                        format!(
+                            "{}{}{} | {}",
+                            "  ".repeat(depth.saturating_sub(1)),
+                            branch,
+                            state.id,
+                            state.display_short()
+                        )
                     }
                     _ => format!("{} | {}", state.id, state.display_short()),
                 };
 
                 let style = if is_selected {
                     Style::default()
-                        .fg(Color::Yellow)
+                        .fg(app.theme.highlight)
                         .add_modifier(Modifier::BOLD)
                 } else {
-                    Style::default().fg(Color::White)
+                    Style::default().fg(app.theme.text)
                 };
 
                 ListItem::new(content).style(style)
@@ -556,13 +888,13 @@ fn draw_pattern_analysis(f: &mut Frame, app: &mut App) {
         "[{}/{}] | [Tab] Cycle Views | [q] Quit",
         current_idx, count
     ))
-    .style(Style::default().fg(Color::White))
-    .block(Block::default().borders(Borders::ALL));
+    .style(Style::default().fg(app.theme.text))
+    .block(footer_block(app));
     f.render_widget(footer, chunks[3]);
 }
 
 /// Draw help screen
-fn draw_help(f: &mut Frame) {
+fn draw_help(f: &mut Frame, app: &App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -576,7 +908,7 @@ fn draw_help(f: &mut Frame) {
     let header = Paragraph::new("Help")
         .style(
             Style::default()
-                .fg(Color::Cyan)
+                .fg(app.theme.accent)
                 .add_modifier(Modifier::BOLD),
         )
         .block(Block::default().borders(Borders::ALL));
@@ -589,8 +921,16 @@ fn draw_help(f: &mut Frame) {
             Style::default().add_modifier(Modifier::BOLD),
         )]),
         Line::from("  ↑/↓          - Navigate through items (context-aware)"),
+        Line::from("  PgUp/PgDn    - Move 10 items through a list"),
+        Line::from("  Home/End     - Jump to the first/last item"),
         Line::from("  Enter        - Open detail view (context-aware)"),
         Line::from("  Esc          - Return to graph overview"),
+        Line::from("  z            - Focus on selected state's lineage (toggle)"),
+        Line::from("  l            - Show only live (unspent terminal) states (toggle)"),
+        Line::from("  f            - Highlight states touched by the next transition action"),
+        Line::from("  L            - Show the state color legend (toggle)"),
+        Line::from("  r            - Refetch data and rebuild the graph"),
+        Line::from("  /            - Filter states and transactions by id/hash (Esc clears)"),
         Line::from(""),
         Line::from(vec![Span::styled(
             "Views",
@@ -599,21 +939,37 @@ fn draw_help(f: &mut Frame) {
         Line::from("  g            - Graph overview (state list)"),
         Line::from("  d            - State detail view"),
         Line::from("  t            - Transaction list"),
+        Line::from("  e            - Transaction inputs/outputs"),
         Line::from("  i            - Datum inspector"),
         Line::from("  p            - Pattern analysis (via Tab cycling)"),
         Line::from("  h or ?       - This help screen"),
         Line::from("  Tab          - Cycle through views"),
         Line::from(""),
+        Line::from(vec![Span::styled(
+            "State Detail",
+            Style::default().add_modifier(Modifier::BOLD),
+        )]),
+        Line::from("  ↑/↓ PgUp/Dn  - Scroll the state information"),
+        Line::from("  P            - Trace the path from the nearest initial state"),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "Transaction List",
+            Style::default().add_modifier(Modifier::BOLD),
+        )]),
+        Line::from("  s            - Show the states the transaction created"),
+        Line::from(""),
         Line::from(vec![Span::styled(
             "Datum Inspector",
             Style::default().add_modifier(Modifier::BOLD),
         )]),
         Line::from("  x            - Toggle hex/decoded view"),
+        Line::from("  ↑/↓ PgUp/Dn  - Scroll the datum"),
         Line::from(""),
         Line::from(vec![Span::styled(
             "General",
             Style::default().add_modifier(Modifier::BOLD),
         )]),
+        Line::from("  y            - Write the selection to selection_dump.txt"),
         Line::from("  q            - Quit application"),
         Line::from(""),
         Line::from(vec![Span::styled(
@@ -622,27 +978,42 @@ fn draw_help(f: &mut Frame) {
         )]),
         Line::from(vec![
             Span::raw("  "),
-            Span::styled("■", Style::default().fg(Color::LightBlue)),
+            Span::styled(
+                "■",
+                Style::default().fg(app.theme.class_color(&StateClass::Initial)),
+            ),
             Span::raw(" Initial    - No incoming transitions"),
         ]),
         Line::from(vec![
             Span::raw("  "),
-            Span::styled("■", Style::default().fg(Color::Yellow)),
+            Span::styled(
+                "■",
+                Style::default().fg(app.theme.class_color(&StateClass::Active)),
+            ),
             Span::raw(" Active     - Has both incoming and outgoing"),
         ]),
         Line::from(vec![
             Span::raw("  "),
-            Span::styled("■", Style::default().fg(Color::Green)),
+            Span::styled(
+                "■",
+                Style::default().fg(app.theme.class_color(&StateClass::Completed)),
+            ),
             Span::raw(" Completed  - No outgoing transitions"),
         ]),
         Line::from(vec![
             Span::raw("  "),
-            Span::styled("■", Style::default().fg(Color::Red)),
+            Span::styled(
+                "■",
+                Style::default().fg(app.theme.class_color(&StateClass::Failed)),
+            ),
             Span::raw(" Failed     - Error state"),
         ]),
         Line::from(vec![
             Span::raw("  "),
-            Span::styled("■", Style::default().fg(Color::Magenta)),
+            Span::styled(
+                "■",
+                Style::default().fg(app.theme.class_color(&StateClass::Locked)),
+            ),
             Span::raw(" Locked     - Temporarily locked"),
         ]),
     ];
@@ -658,11 +1029,75 @@ fn draw_help(f: &mut Frame) {
 
     // Footer
     let footer = Paragraph::new("[g/Esc] Back to Overview | [q] Quit")
-        .style(Style::default().fg(Color::White))
-        .block(Block::default().borders(Borders::ALL));
+        .style(Style::default().fg(app.theme.text))
+        .block(footer_block(app));
     f.render_widget(footer, chunks[2]);
 }
 
+/// Bordered footer block, titled with the pending notice if there is one
+fn footer_block(app: &App) -> Block<'_> {
+    let block = Block::default().borders(Borders::ALL);
+    match &app.notice {
+        Some(notice) => block.title(notice.as_str()),
+        None => block,
+    }
+}
+
+/// Footer text while a search query is being typed or filtering the lists
+fn search_footer(app: &App) -> Option<String> {
+    let query = app.search_query.as_ref()?;
+    Some(if app.search_active {
+        format!("Search: {}█ | [Enter] Apply | [Esc] Clear", query)
+    } else {
+        format!(
+            "Filter: \"{}\" | [/] Edit | [Esc] Clear | [↑/↓] Navigate | [h/?] Help | [q] Quit",
+            query
+        )
+    })
+}
+
+/// Redeemer budget suffix for a transition list entry, empty without a redeemer
+fn format_ex_units(transition: &crate::state_machine::Transition) -> String {
+    transition
+        .ex_units()
+        .map(|units| format!(" [mem: {}, steps: {}]", units.mem, units.steps))
+        .unwrap_or_default()
+}
+
+/// Format a value as ADA followed by any native assets
+fn format_assets(amount: &[Asset]) -> String {
+    amount
+        .iter()
+        .map(format_asset)
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+/// Format a single asset, showing lovelace as ADA and native assets by name
+fn format_asset(asset: &Asset) -> String {
+    let name = match (asset.policy_id(), asset.asset_name_hex()) {
+        (Some(policy_id), Some(asset_name)) => {
+            let asset_name = asset
+                .asset_name_utf8()
+                .unwrap_or_else(|| asset_name.to_string());
+            format!("{} (policy {})", asset_name, policy_id)
+        }
+        _ => asset.unit.clone(),
+    };
+    match asset.amount() {
+        Ok(lovelace) if asset.is_lovelace() => format!("{} ADA", lovelace as f64 / 1_000_000.0),
+        Ok(quantity) => format!("{} {}", quantity, name),
+        Err(_) => format!("{} {} (invalid)", asset.quantity, name),
+    }
+}
+
+/// Datum fields ordered by name, so nested fields follow their parent
+fn sorted_fields(fields: &HashMap<String, String>) -> Vec<(&String, &String)> {
+    let mut fields: Vec<_> = fields.iter().collect();
+    fields.sort();
+    fields
+}
+
 /// Format state information for detail view
 fn format_state_info(state: &crate::state_machine::State) -> String {
     let mut info = String::new();
@@ -675,6 +1110,7 @@ fn format_state_info(state: &crate::state_machine::State) -> String {
     info.push_str(&format!("Block: {}\n", state.block));
     info.push_str(&format!("Slot: {}\n", state.slot));
     info.push_str(&format!("Transaction: {}\n", state.tx_hash));
+    info.push_str(&format!("Script: {}\n", state.metadata.script_address));
     info.push_str(&format!(
         "ADA Value: {} ADA\n",
         state.ada_value() as f64 / 1_000_000.0
@@ -685,8 +1121,8 @@ fn format_state_info(state: &crate::state_machine::State) -> String {
         info.push_str(&format!("Datum CBOR: {} bytes\n", datum.raw_cbor.len()));
         if let Some(ref parsed) = datum.parsed {
             if !parsed.fields.is_empty() {
-                info.push_str("Schema Fields:\n");
-                for (key, val) in &parsed.fields {
+                info.push_str("Fields:\n");
+                for (key, val) in sorted_fields(&parsed.fields) {
                     info.push_str(&format!("  {}: {}\n", key, val));
                 }
             }
@@ -699,9 +1135,47 @@ fn format_state_info(state: &crate::state_machine::State) -> String {
     // Output details
     info.push_str(&format!("\nOutput Address: {}\n", state.output.address));
     info.push_str("Assets:\n");
-    for asset in &state.output.amount {
-        info.push_str(&format!("  {} {}\n", asset.quantity, asset.unit));
+    for asset in state
+        .output
+        .amount
+        .iter()
+        .filter(|asset| asset.is_lovelace())
+    {
+        info.push_str(&format!("  {}\n", format_asset(asset)));
+    }
+    for group in Asset::group_by_policy(&state.output.amount) {
+        info.push_str(&format!(
+            "  Policy {} ({} asset{}, total {}):\n",
+            group.policy_id,
+            group.assets.len(),
+            if group.assets.len() == 1 { "" } else { "s" },
+            group.total
+        ));
+        for asset in group.assets {
+            let name = asset
+                .asset_name_utf8()
+                .or_else(|| asset.asset_name_hex().map(str::to_string))
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| "(no name)".to_string());
+            info.push_str(&format!("    {} {}\n", asset.quantity, name));
+        }
     }
 
     info
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hexdump() {
+        let bytes: Vec<u8> = b"\xd8\x79\x9f\x4bHello world!\n".to_vec();
+        assert_eq!(
+            hexdump(&bytes),
+            "00000000  d8 79 9f 4b 48 65 6c 6c  6f 20 77 6f 72 6c 64 21  |.y.KHello world!|\n\
+             00000010  0a                                                |.|\n"
+        );
+        assert_eq!(hexdump(&[]), "");
+    }
+}